@@ -0,0 +1,433 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::compression::Compression;
+use crate::json_reader::{read_all, InputFormat, ParseControl, ParseError};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use zip::ZipArchive;
+
+/// Largest member that is read into memory to be parsed.
+const MAX_MEMBER_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// The kinds of archive whose members can be browsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+}
+
+impl ArchiveKind {
+    /// Recognizes an archive by its extension, including compressed tar files
+    /// such as `results.tar.gz` or `results.tgz`.
+    pub fn from_path(path: &Path) -> Option<(Self, Option<Compression>)> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "zip" => Some((ArchiveKind::Zip, None)),
+            "tar" => Some((ArchiveKind::Tar, None)),
+            "tgz" => Some((ArchiveKind::Tar, Some(Compression::Gzip))),
+            _ => {
+                let (compression, inner_path) = Compression::from_path(path)?;
+                match ArchiveKind::from_path(&inner_path)? {
+                    (kind, None) => Some((kind, Some(compression))),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Name of the archive format, as shown in the archive's summary.
+    pub fn label(self) -> &'static str {
+        match self {
+            ArchiveKind::Zip => "zip",
+            ArchiveKind::Tar => "tar",
+        }
+    }
+}
+
+/// A file inside an archive that slopjson can parse.
+#[derive(Debug)]
+pub struct ArchiveMember {
+    /// Path of the member within the archive
+    pub name: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Index of a zip entry, or byte offset of a tar entry's data
+    position: u64,
+}
+
+/// Where an archive's bytes are read from.
+///
+/// Zip readers and tar files are locked while a member is read, as they are
+/// read by seeking, and members are read both for the tree and by search on
+/// another thread.
+#[derive(Debug)]
+enum ArchiveData {
+    Zip(Mutex<ZipArchive<File>>),
+    ZipInMemory(Mutex<ZipArchive<Cursor<Vec<u8>>>>),
+    Tar(Mutex<File>),
+    TarInMemory(Vec<u8>),
+}
+
+/// A zip or tar archive whose members are read on request.
+///
+/// Opening the archive only lists its members. Uncompressed archives on disk
+/// are read in place; compressed tar files and pipes are read into memory.
+#[derive(Debug)]
+pub struct Archive {
+    kind: ArchiveKind,
+    data: ArchiveData,
+    members: Vec<ArchiveMember>,
+}
+
+impl Archive {
+    /// Opens an archive and lists the members in formats slopjson can parse.
+    pub fn open(
+        path: &Path,
+        kind: ArchiveKind,
+        compression: Option<Compression>,
+        control: &ParseControl,
+    ) -> Result<Self, ParseError> {
+        control.check_cancelled()?;
+        let file = File::open(path).map_err(io_error)?;
+        let regular_file = file.metadata().is_ok_and(|metadata| metadata.is_file());
+
+        let data = match (kind, compression) {
+            (ArchiveKind::Zip, None) if regular_file => {
+                ArchiveData::Zip(Mutex::new(ZipArchive::new(file).map_err(zip_error)?))
+            }
+            (ArchiveKind::Tar, None) if regular_file => ArchiveData::Tar(Mutex::new(file)),
+            _ => {
+                let bytes = match compression {
+                    Some(compression) => read_all(compression.decoder(file)?, None, control)?,
+                    None => read_all(file, None, control)?,
+                };
+                match kind {
+                    ArchiveKind::Zip => ArchiveData::ZipInMemory(Mutex::new(
+                        ZipArchive::new(Cursor::new(bytes)).map_err(zip_error)?,
+                    )),
+                    ArchiveKind::Tar => ArchiveData::TarInMemory(bytes),
+                }
+            }
+        };
+
+        let members = match &data {
+            ArchiveData::Zip(zip) => list_zip_members(&mut lock(zip)),
+            ArchiveData::ZipInMemory(zip) => list_zip_members(&mut lock(zip)),
+            ArchiveData::Tar(file) => list_tar_members(&*lock(file))?,
+            ArchiveData::TarInMemory(bytes) => list_tar_members(Cursor::new(bytes.as_slice()))?,
+        };
+        Ok(Self {
+            kind,
+            data,
+            members,
+        })
+    }
+
+    pub fn kind(&self) -> ArchiveKind {
+        self.kind
+    }
+
+    /// The members that can be parsed, in archive order.
+    pub fn members(&self) -> &[ArchiveMember] {
+        &self.members
+    }
+
+    /// Reads the whole content of a member.
+    ///
+    /// Sizes come from the archive's headers, which may be corrupt, so they are
+    /// checked against the archive's own size before anything is read, and a
+    /// member over 1 GiB is refused rather than read into memory.
+    pub fn read_member(&self, member: usize) -> Result<Vec<u8>, ParseError> {
+        let member = self
+            .members
+            .get(member)
+            .ok_or_else(|| ParseError::IoError(format!("member {} out of range", member)))?;
+        if member.size > MAX_MEMBER_SIZE {
+            return Err(ParseError::IoError(format!(
+                "{} is too large to read ({} bytes, the limit is {})",
+                member.name, member.size, MAX_MEMBER_SIZE
+            )));
+        }
+        match &self.data {
+            ArchiveData::Zip(zip) => read_zip_member(&mut lock(zip), member),
+            ArchiveData::ZipInMemory(zip) => read_zip_member(&mut lock(zip), member),
+            ArchiveData::Tar(file) => {
+                let mut file = lock(file);
+                let archive_size = file.metadata().map_err(io_error)?.len();
+                check_tar_member_bounds(member, archive_size)?;
+                file.seek(SeekFrom::Start(member.position))
+                    .map_err(io_error)?;
+                read_to_limit(&mut *file, member, member.size)
+            }
+            ArchiveData::TarInMemory(bytes) => {
+                check_tar_member_bounds(member, bytes.len() as u64)?;
+                let start = member.position as usize;
+                Ok(bytes[start..start + member.size as usize].to_vec())
+            }
+        }
+    }
+}
+
+/// Locks a zip reader or tar file. A read that panicked leaves nothing to
+/// undo, as every read starts by seeking to its member.
+fn lock<T>(reader: &Mutex<T>) -> MutexGuard<'_, T> {
+    reader.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Whether a member's name says it is in a format slopjson can parse.
+fn is_supported_member(name: &str) -> bool {
+    let path = Path::new(name);
+    match Compression::from_path(path) {
+        Some((_, inner_path)) => InputFormat::from_path(&inner_path).is_some(),
+        None => InputFormat::from_path(path).is_some(),
+    }
+}
+
+fn list_zip_members<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Vec<ArchiveMember> {
+    (0..zip.len())
+        .filter_map(|idx| {
+            let entry = zip.by_index_raw(idx).ok()?;
+            (entry.is_file() && is_supported_member(entry.name())).then(|| ArchiveMember {
+                name: entry.name().to_string(),
+                size: entry.size(),
+                position: idx as u64,
+            })
+        })
+        .collect()
+}
+
+fn read_zip_member<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    member: &ArchiveMember,
+) -> Result<Vec<u8>, ParseError> {
+    let mut entry = zip.by_index(member.position as usize).map_err(zip_error)?;
+    // The uncompressed size can be larger than the archive, so it is only capped
+    let bytes = read_to_limit(&mut entry, member, MAX_MEMBER_SIZE + 1)?;
+    if bytes.len() as u64 > MAX_MEMBER_SIZE {
+        return Err(ParseError::IoError(format!(
+            "{} is too large to read (over {} bytes)",
+            member.name, MAX_MEMBER_SIZE
+        )));
+    }
+    Ok(bytes)
+}
+
+/// Reads up to `limit` bytes of a member, growing the buffer as data arrives
+/// rather than trusting the size in its header. Fails if the member ends
+/// before the size in its header.
+fn read_to_limit(
+    reader: &mut impl Read,
+    member: &ArchiveMember,
+    limit: u64,
+) -> Result<Vec<u8>, ParseError> {
+    let mut bytes = Vec::new();
+    reader
+        .take(limit)
+        .read_to_end(&mut bytes)
+        .map_err(io_error)?;
+    if (bytes.len() as u64) < member.size {
+        return Err(ParseError::IoError(format!(
+            "{} is truncated: {} of {} bytes",
+            member.name,
+            bytes.len(),
+            member.size
+        )));
+    }
+    Ok(bytes)
+}
+
+/// Checks that a tar member's data, as its header describes it, is inside the archive.
+fn check_tar_member_bounds(member: &ArchiveMember, archive_size: u64) -> Result<(), ParseError> {
+    match member.position.checked_add(member.size) {
+        Some(end) if end <= archive_size => Ok(()),
+        _ => Err(ParseError::IoError(format!(
+            "{} claims {} bytes, more than the archive holds",
+            member.name, member.size
+        ))),
+    }
+}
+
+fn list_tar_members(reader: impl Read + Seek) -> Result<Vec<ArchiveMember>, ParseError> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for entry in archive.entries_with_seek().map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .map_err(io_error)?
+            .to_string_lossy()
+            .into_owned();
+        if is_supported_member(&name) {
+            members.push(ArchiveMember {
+                name,
+                size: entry.size(),
+                position: entry.raw_file_position(),
+            });
+        }
+    }
+    Ok(members)
+}
+
+fn io_error(error: std::io::Error) -> ParseError {
+    ParseError::IoError(error.to_string())
+}
+
+fn zip_error(error: zip::result::ZipError) -> ParseError {
+    ParseError::IoError(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Writes a tar archive holding the given files.
+    fn tar_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("slopjson-archive-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_archive_kind_from_path() {
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("export.ZIP")),
+            Some((ArchiveKind::Zip, None))
+        );
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("results.tar.gz")),
+            Some((ArchiveKind::Tar, Some(Compression::Gzip)))
+        );
+        assert_eq!(
+            ArchiveKind::from_path(Path::new("results.tgz")),
+            Some((ArchiveKind::Tar, Some(Compression::Gzip)))
+        );
+        assert_eq!(ArchiveKind::from_path(Path::new("data.json.gz")), None);
+    }
+
+    #[test]
+    fn test_tar_members() {
+        let bytes = tar_bytes(&[
+            ("results/a.json", b"{\"a\": 1}"),
+            ("results/README.md", b"# notes"),
+            ("results/b.yaml", b"b: 2\n"),
+        ]);
+
+        // Read in place
+        let path = temp_path("plain.tar");
+        std::fs::write(&path, &bytes).unwrap();
+        let archive =
+            Archive::open(&path, ArchiveKind::Tar, None, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let names: Vec<&str> = archive.members().iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["results/a.json", "results/b.yaml"]);
+        assert_eq!(archive.read_member(1).unwrap(), b"b: 2\n");
+
+        // Decompressed into memory
+        let path = temp_path("packed.tar.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(&bytes).unwrap();
+        encoder.finish().unwrap();
+        let archive = Archive::open(
+            &path,
+            ArchiveKind::Tar,
+            Some(Compression::Gzip),
+            &ParseControl::default(),
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(archive.members().len(), 2);
+        assert_eq!(archive.read_member(0).unwrap(), b"{\"a\": 1}");
+    }
+
+    #[test]
+    fn test_zip_members() {
+        let path = temp_path("export.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.add_directory("data/", options).unwrap();
+        writer.start_file("data/rows.jsonl", options).unwrap();
+        writer.write_all(b"{\"id\": 1}\n{\"id\": 2}\n").unwrap();
+        writer.start_file("data/image.png", options).unwrap();
+        writer.write_all(b"\x89PNG").unwrap();
+        writer.finish().unwrap();
+
+        let archive =
+            Archive::open(&path, ArchiveKind::Zip, None, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(archive.members().len(), 1);
+        assert_eq!(archive.members()[0].name, "data/rows.jsonl");
+        assert_eq!(
+            archive.read_member(0).unwrap(),
+            b"{\"id\": 1}\n{\"id\": 2}\n"
+        );
+        assert!(archive.read_member(1).is_err());
+    }
+
+    /// Overwrites the size field of the first header of a tar archive, as a
+    /// corrupt or hostile archive might have it, and fixes up the checksum.
+    fn set_first_member_size(bytes: &mut [u8], size: u64) {
+        let field = format!("{:011o}\0", size);
+        bytes[124..136].copy_from_slice(field.as_bytes());
+        bytes[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = bytes[..512].iter().map(|byte| u32::from(*byte)).sum();
+        bytes[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    }
+
+    #[test]
+    fn test_tar_member_with_corrupt_size() {
+        for size in [4 * 1024 * 1024, 2 * MAX_MEMBER_SIZE] {
+            let mut bytes = tar_bytes(&[("a.json", b"{\"a\": 1}")]);
+            set_first_member_size(&mut bytes, size);
+
+            let path = temp_path("corrupt.tar");
+            std::fs::write(&path, &bytes).unwrap();
+            let archive =
+                Archive::open(&path, ArchiveKind::Tar, None, &ParseControl::default()).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(archive.members()[0].size, size);
+            assert!(matches!(
+                archive.read_member(0),
+                Err(ParseError::IoError(_))
+            ));
+
+            let archive = Archive {
+                kind: ArchiveKind::Tar,
+                data: ArchiveData::TarInMemory(bytes),
+                members: archive.members,
+            };
+            assert!(archive.read_member(0).is_err());
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::arrow_json::{arrow_schema_to_json, record_batch_to_json_rows};
+use crate::json_reader::ParseError;
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::record_batch::RecordBatch;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Starts an Arrow IPC file, including Feather version 2 files.
+pub const ARROW_FILE_MAGIC: &[u8] = b"ARROW1";
+
+/// An Arrow IPC file or stream whose rows are converted to JSON on request.
+///
+/// The record batches are read into memory when the file is opened, in Arrow's
+/// columnar form; `read_rows` converts just the rows that are asked for.
+#[derive(Debug)]
+pub struct ArrowFile {
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    /// True for the IPC file format (with a footer), false for the stream format
+    is_file_format: bool,
+    /// Key-value metadata from the file footer, sorted by key
+    custom_metadata: BTreeMap<String, String>,
+}
+
+impl ArrowFile {
+    /// Opens an Arrow IPC file or stream on disk and reads its record batches.
+    pub fn open(path: &Path) -> Result<Self, ParseError> {
+        let data = std::fs::read(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+        Self::from_bytes(data)
+    }
+
+    /// Reads the record batches of an Arrow IPC file or stream that is already in memory.
+    ///
+    /// Content starting with `ARROW1` is read as the file format, anything else
+    /// as the stream format.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ParseError> {
+        let invalid = |e: arrow::error::ArrowError| ParseError::InvalidArrow(e.to_string());
+        if data.starts_with(ARROW_FILE_MAGIC) {
+            let reader = FileReader::try_new(Cursor::new(data), None).map_err(invalid)?;
+            let schema = reader.schema();
+            let custom_metadata = reader.custom_metadata().clone().into_iter().collect();
+            let batches = reader.collect::<Result<_, _>>().map_err(invalid)?;
+            Ok(Self {
+                schema,
+                batches,
+                is_file_format: true,
+                custom_metadata,
+            })
+        } else {
+            let reader = StreamReader::try_new(Cursor::new(data), None).map_err(invalid)?;
+            let schema = reader.schema();
+            let batches = reader.collect::<Result<_, _>>().map_err(invalid)?;
+            Ok(Self {
+                schema,
+                batches,
+                is_file_format: false,
+                custom_metadata: BTreeMap::new(),
+            })
+        }
+    }
+
+    /// Total number of rows across all record batches.
+    pub fn num_rows(&self) -> usize {
+        self.batches.iter().map(RecordBatch::num_rows).sum()
+    }
+
+    /// Number of record batches in the file.
+    pub fn num_batches(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Describes the file's schema and record batches, as shown under the "Metadata" node.
+    pub fn metadata_json(&self) -> Value {
+        serde_json::json!({
+            "format": if self.is_file_format { "file" } else { "stream" },
+            "num_rows": self.num_rows(),
+            "schema_metadata": self.schema.metadata().iter().collect::<BTreeMap<_, _>>(),
+            "custom_metadata": self.custom_metadata,
+            "arrow_schema": arrow_schema_to_json(&self.schema),
+            "record_batches": self
+                .batches
+                .iter()
+                .map(|batch| serde_json::json!({ "num_rows": batch.num_rows() }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Converts up to `len` rows starting at row `start`.
+    pub fn read_rows(&self, start: usize, len: usize) -> Result<Vec<Value>, ParseError> {
+        let end = start.saturating_add(len).min(self.num_rows());
+        let mut rows = Vec::with_capacity(end.saturating_sub(start));
+        let mut batch_start = 0;
+        for batch in &self.batches {
+            let batch_end = batch_start + batch.num_rows();
+            if batch_end > start && batch_start < end {
+                let offset = start.saturating_sub(batch_start);
+                let length = end.min(batch_end) - batch_start - offset;
+                let batch_rows = record_batch_to_json_rows(&batch.slice(offset, length))
+                    .map_err(|e| ParseError::InvalidArrow(e.to_string()))?;
+                rows.extend(batch_rows);
+            }
+            batch_start = batch_end;
+        }
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::writer::{FileWriter, StreamWriter};
+    use std::sync::Arc;
+
+    /// Record batches of `rows_per_batch` rows holding `0..num_rows` and their names.
+    fn batches(num_rows: i64, rows_per_batch: i64) -> (SchemaRef, Vec<RecordBatch>) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batches = (0..num_rows)
+            .step_by(rows_per_batch as usize)
+            .map(|first| {
+                let ids = first..(first + rows_per_batch).min(num_rows);
+                let names = ids.clone().map(|id| format!("row {}", id));
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int64Array::from_iter_values(ids)),
+                        Arc::new(StringArray::from_iter_values(names)),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect();
+        (schema, batches)
+    }
+
+    #[test]
+    fn test_read_rows_across_batches() {
+        let (schema, batches) = batches(25, 10);
+        let mut data = Vec::new();
+        let mut writer = FileWriter::try_new(&mut data, &schema).unwrap();
+        writer.write_metadata("writer", "slopjson");
+        for batch in &batches {
+            writer.write(batch).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+
+        let arrow = ArrowFile::from_bytes(data).unwrap();
+        assert_eq!(arrow.num_rows(), 25);
+        assert_eq!(arrow.num_batches(), 3);
+
+        let rows = arrow.read_rows(8, 5).unwrap();
+        let ids: Vec<i64> = rows.iter().map(|row| row["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, (8..13).collect::<Vec<_>>());
+        assert_eq!(rows[0]["name"], "row 8");
+        assert_eq!(arrow.read_rows(20, 10).unwrap().len(), 5);
+        assert!(arrow.read_rows(25, 10).unwrap().is_empty());
+
+        let metadata = arrow.metadata_json();
+        assert_eq!(metadata["format"], "file");
+        assert_eq!(metadata["custom_metadata"]["writer"], "slopjson");
+        assert_eq!(metadata["arrow_schema"][1]["data_type"], "Utf8");
+    }
+
+    #[test]
+    fn test_stream_format() {
+        let (schema, batches) = batches(4, 3);
+        let mut data = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut data, &schema).unwrap();
+        for batch in &batches {
+            writer.write(batch).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+
+        let arrow = ArrowFile::from_bytes(data).unwrap();
+        assert_eq!(arrow.metadata_json()["format"], "stream");
+        assert_eq!(arrow.read_rows(0, 10).unwrap().len(), 4);
+
+        assert!(matches!(
+            ArrowFile::from_bytes(b"ARROW1 but not really".to_vec()),
+            Err(ParseError::InvalidArrow(_))
+        ));
+    }
+}
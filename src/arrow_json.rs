@@ -0,0 +1,1027 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Converting Arrow record batches, and the metadata of Parquet and Arrow
+//! files, to JSON values.
+
+use crate::binary_reader::bytes_to_json;
+use crate::json_value::float_to_json;
+use arrow::datatypes::TimeUnit;
+use serde_json::Value;
+
+/// Timezones of timestamps that are shown in UTC, with a `Z` suffix.
+const UTC_TIMEZONES: [&str; 2] = ["+00:00", "UTC"];
+
+/// Describes a Parquet file's metadata as JSON, for the synthetic "Metadata" node.
+///
+/// Includes the Arrow schema, the Parquet column types, and per row group the row
+/// count, sizes and column chunk statistics, along with the writer (`created_by`)
+/// and the key-value metadata.
+pub fn parquet_metadata_to_json(
+    metadata: &parquet::file::metadata::ParquetMetaData,
+    arrow_schema: &arrow::datatypes::Schema,
+) -> Value {
+    use serde_json::json;
+
+    let file_metadata = metadata.file_metadata();
+
+    let key_value_metadata: serde_json::Map<String, Value> = file_metadata
+        .key_value_metadata()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| {
+                    let value = entry
+                        .value
+                        .clone()
+                        .map(Value::String)
+                        .unwrap_or(Value::Null);
+                    (entry.key.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let parquet_columns: Vec<Value> = file_metadata
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| {
+            json!({
+                "path": column.path().string(),
+                "physical_type": column.physical_type().to_string(),
+                "logical_type": column.logical_type().map(|t| format!("{:?}", t)),
+                "converted_type": column.converted_type().to_string(),
+                "repetition": column.self_type().get_basic_info().repetition().to_string(),
+            })
+        })
+        .collect();
+
+    let row_groups: Vec<Value> = metadata
+        .row_groups()
+        .iter()
+        .map(|row_group| {
+            let columns: Vec<Value> = row_group
+                .columns()
+                .iter()
+                .map(|column| {
+                    json!({
+                        "path": column.column_path().string(),
+                        "compression": column.compression().to_string(),
+                        "encodings": column
+                            .encodings()
+                            .iter()
+                            .map(|encoding| encoding.to_string())
+                            .collect::<Vec<_>>(),
+                        "num_values": column.num_values(),
+                        "compressed_size": column.compressed_size(),
+                        "uncompressed_size": column.uncompressed_size(),
+                        "statistics": column.statistics().map(|statistics| {
+                            parquet_statistics_to_json(statistics, column.column_descr())
+                        }),
+                    })
+                })
+                .collect();
+            json!({
+                "num_rows": row_group.num_rows(),
+                "compressed_size": row_group.compressed_size(),
+                "uncompressed_size": row_group.total_byte_size(),
+                "columns": columns,
+            })
+        })
+        .collect();
+
+    json!({
+        "created_by": file_metadata.created_by(),
+        "version": file_metadata.version(),
+        "num_rows": file_metadata.num_rows(),
+        "key_value_metadata": key_value_metadata,
+        "arrow_schema": arrow_schema_to_json(arrow_schema),
+        "parquet_schema": parquet_columns,
+        "row_groups": row_groups,
+    })
+}
+
+/// Describes the fields of an Arrow schema as JSON, for the "Metadata" node of
+/// Parquet and Arrow files.
+pub fn arrow_schema_to_json(schema: &arrow::datatypes::Schema) -> Value {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            serde_json::json!({
+                "name": field.name(),
+                "data_type": field.data_type().to_string(),
+                "nullable": field.is_nullable(),
+            })
+        })
+        .collect()
+}
+
+/// Describes a column chunk's statistics as JSON.
+///
+/// Min and max are shown as their physical values: numbers for numeric columns,
+/// strings for byte arrays annotated as text and `{"$binary": ...}` objects
+/// for other byte arrays, as for binary values in the rows.
+fn parquet_statistics_to_json(
+    statistics: &parquet::file::statistics::Statistics,
+    column: &parquet::schema::types::ColumnDescriptor,
+) -> Value {
+    use parquet::basic::{ConvertedType, LogicalType};
+    use parquet::data_type::AsBytes;
+    use parquet::file::statistics::Statistics;
+    use serde_json::json;
+
+    let is_text = matches!(
+        column.logical_type(),
+        Some(LogicalType::String | LogicalType::Enum | LogicalType::Json)
+    ) || matches!(
+        column.converted_type(),
+        ConvertedType::UTF8 | ConvertedType::ENUM | ConvertedType::JSON
+    );
+    let bytes_to_json = |bytes: &[u8]| match std::str::from_utf8(bytes) {
+        Ok(text) if is_text => Value::String(text.to_string()),
+        _ => bytes_to_json(bytes),
+    };
+
+    let (min, max) = match statistics {
+        Statistics::Boolean(s) => (s.min_opt().map(|v| json!(v)), s.max_opt().map(|v| json!(v))),
+        Statistics::Int32(s) => (s.min_opt().map(|v| json!(v)), s.max_opt().map(|v| json!(v))),
+        Statistics::Int64(s) => (s.min_opt().map(|v| json!(v)), s.max_opt().map(|v| json!(v))),
+        Statistics::Int96(s) => (
+            s.min_opt().map(|v| json!(v.to_string())),
+            s.max_opt().map(|v| json!(v.to_string())),
+        ),
+        Statistics::Float(s) => (
+            s.min_opt().map(|v| float_to_json(*v as f64)),
+            s.max_opt().map(|v| float_to_json(*v as f64)),
+        ),
+        Statistics::Double(s) => (
+            s.min_opt().map(|v| float_to_json(*v)),
+            s.max_opt().map(|v| float_to_json(*v)),
+        ),
+        Statistics::ByteArray(s) => (
+            s.min_opt().map(|v| bytes_to_json(v.as_bytes())),
+            s.max_opt().map(|v| bytes_to_json(v.as_bytes())),
+        ),
+        Statistics::FixedLenByteArray(s) => (
+            s.min_opt().map(|v| bytes_to_json(v.as_bytes())),
+            s.max_opt().map(|v| bytes_to_json(v.as_bytes())),
+        ),
+    };
+
+    json!({
+        "min": min,
+        "max": max,
+        "null_count": statistics.null_count_opt(),
+        "distinct_count": statistics.distinct_count_opt(),
+    })
+}
+
+/// Converts every row of a record batch into a JSON object keyed by column name.
+pub fn record_batch_to_json_rows(
+    batch: &arrow::record_batch::RecordBatch,
+) -> Result<Vec<Value>, arrow::error::ArrowError> {
+    let schema = batch.schema();
+    let mut rows = vec![serde_json::Map::new(); batch.num_rows()];
+
+    // Convert column by column, so per-column work (downcasts, dictionary values) happens once
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let values = arrow_array_to_json(column.as_ref())?;
+        for (row, value) in rows.iter_mut().zip(values) {
+            row.insert(field.name().clone(), value);
+        }
+    }
+
+    Ok(rows.into_iter().map(Value::Object).collect())
+}
+
+/// Formats days since the epoch as a date, e.g. `2024-01-02`.
+///
+/// Like the other scalar conversions below, this matches Arrow's display
+/// format, and is shared with readers that decode values without Arrow arrays.
+/// Values out of chrono's range are kept as numbers.
+pub fn date_to_json(days: i32) -> Value {
+    match arrow::temporal_conversions::date32_to_datetime(days) {
+        Some(datetime) => Value::String(format!("{:?}", datetime.date())),
+        None => Value::from(days),
+    }
+}
+
+/// Formats a time of day, given in `unit` since midnight, e.g. `10:20:30.500`.
+pub fn time_to_json(value: i64, unit: TimeUnit) -> Value {
+    use arrow::temporal_conversions::*;
+
+    let time = match unit {
+        TimeUnit::Second => i32::try_from(value).ok().and_then(time32s_to_time),
+        TimeUnit::Millisecond => i32::try_from(value).ok().and_then(time32ms_to_time),
+        TimeUnit::Microsecond => time64us_to_time(value),
+        TimeUnit::Nanosecond => time64ns_to_time(value),
+    };
+    match time {
+        Some(time) => Value::String(format!("{:?}", time)),
+        None => Value::from(value),
+    }
+}
+
+/// Formats a timestamp, given in `unit` since the epoch, e.g.
+/// `2024-01-02T03:04:05`, or `2024-01-02T03:04:05Z` if it is in UTC.
+pub fn timestamp_to_json(value: i64, unit: TimeUnit, utc: bool) -> Value {
+    use arrow::temporal_conversions::*;
+
+    let datetime = match unit {
+        TimeUnit::Second => timestamp_s_to_datetime(value),
+        TimeUnit::Millisecond => timestamp_ms_to_datetime(value),
+        TimeUnit::Microsecond => timestamp_us_to_datetime(value),
+        TimeUnit::Nanosecond => timestamp_ns_to_datetime(value),
+    };
+    match datetime {
+        Some(datetime) if utc => Value::String(format!("{:?}Z", datetime)),
+        Some(datetime) => Value::String(format!("{:?}", datetime)),
+        None => Value::from(value),
+    }
+}
+
+/// Formats a decimal from its unscaled value, e.g. `12345` with scale 2 as `123.45`.
+pub fn decimal_to_json(unscaled: i128, precision: u8, scale: i8) -> Value {
+    use arrow::datatypes::{Decimal128Type, DecimalType};
+
+    Value::String(Decimal128Type::format_decimal(unscaled, precision, scale))
+}
+
+/// Converts every element of an Arrow array into a JSON value.
+///
+/// Nested types (lists, structs, maps) are converted recursively and dictionaries
+/// are decoded to their values, and binary data becomes `{"$binary": ...}` objects.
+/// Dates, times, timestamps and decimals are formatted by the scalar conversions
+/// above, e.g. `2024-01-02T03:04:05Z` or `123.45`, and other types with no JSON
+/// counterpart (intervals, durations, ...) become strings in Arrow's display
+/// format. Nulls become `Value::Null`.
+pub fn arrow_array_to_json(
+    array: &dyn arrow::array::Array,
+) -> Result<Vec<Value>, arrow::error::ArrowError> {
+    use arrow::array::{Array, AsArray, OffsetSizeTrait, PrimitiveArray};
+    use arrow::datatypes::*;
+    use arrow::util::display::{ArrayFormatter, FormatOptions};
+
+    fn primitives<T: ArrowPrimitiveType>(
+        array: &PrimitiveArray<T>,
+        convert: impl Fn(T::Native) -> Value,
+    ) -> Vec<Value> {
+        array
+            .iter()
+            .map(|value| value.map(&convert).unwrap_or(Value::Null))
+            .collect()
+    }
+
+    fn timestamps<T: ArrowPrimitiveType<Native = i64>>(
+        array: &dyn Array,
+        unit: TimeUnit,
+        utc: bool,
+    ) -> Vec<Value> {
+        primitives(array.as_primitive::<T>(), |v| {
+            timestamp_to_json(v, unit, utc)
+        })
+    }
+
+    fn binaries<'a>(values: impl Iterator<Item = Option<&'a [u8]>>) -> Vec<Value> {
+        values
+            .map(|value| value.map(bytes_to_json).unwrap_or(Value::Null))
+            .collect()
+    }
+
+    /// Converts the child values from the first offset to the last, which for a
+    /// sliced array are only part of the child array.
+    fn child_values<O: OffsetSizeTrait>(
+        values: &dyn Array,
+        offsets: &[O],
+    ) -> Result<(usize, Vec<Value>), arrow::error::ArrowError> {
+        let start = offsets.first().map_or(0, |offset| offset.as_usize());
+        let end = offsets.last().map_or(0, |offset| offset.as_usize());
+        let values = arrow_array_to_json(values.slice(start, end - start).as_ref())?;
+        Ok((start, values))
+    }
+
+    fn lists<O: OffsetSizeTrait>(
+        array: &arrow::array::GenericListArray<O>,
+    ) -> Result<Vec<Value>, arrow::error::ArrowError> {
+        let (start, items) = child_values(array.values().as_ref(), array.value_offsets())?;
+        Ok(array
+            .value_offsets()
+            .windows(2)
+            .map(|w| Value::Array(items[w[0].as_usize() - start..w[1].as_usize() - start].to_vec()))
+            .collect())
+    }
+
+    let mut values: Vec<Value> = match array.data_type() {
+        DataType::Null => vec![Value::Null; array.len()],
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|value| value.map(Value::Bool).unwrap_or(Value::Null))
+            .collect(),
+        DataType::Int8 => primitives(array.as_primitive::<Int8Type>(), Value::from),
+        DataType::Int16 => primitives(array.as_primitive::<Int16Type>(), Value::from),
+        DataType::Int32 => primitives(array.as_primitive::<Int32Type>(), Value::from),
+        DataType::Int64 => primitives(array.as_primitive::<Int64Type>(), Value::from),
+        DataType::UInt8 => primitives(array.as_primitive::<UInt8Type>(), Value::from),
+        DataType::UInt16 => primitives(array.as_primitive::<UInt16Type>(), Value::from),
+        DataType::UInt32 => primitives(array.as_primitive::<UInt32Type>(), Value::from),
+        DataType::UInt64 => primitives(array.as_primitive::<UInt64Type>(), Value::from),
+        DataType::Float16 => primitives(array.as_primitive::<Float16Type>(), |v| {
+            float_to_json(v.to_f64())
+        }),
+        DataType::Float32 => primitives(array.as_primitive::<Float32Type>(), |v| {
+            float_to_json(v as f64)
+        }),
+        DataType::Float64 => primitives(array.as_primitive::<Float64Type>(), float_to_json),
+        DataType::Date32 => primitives(array.as_primitive::<Date32Type>(), date_to_json),
+        DataType::Time32(TimeUnit::Second) => {
+            primitives(array.as_primitive::<Time32SecondType>(), |v| {
+                time_to_json(v.into(), TimeUnit::Second)
+            })
+        }
+        DataType::Time32(TimeUnit::Millisecond) => {
+            primitives(array.as_primitive::<Time32MillisecondType>(), |v| {
+                time_to_json(v.into(), TimeUnit::Millisecond)
+            })
+        }
+        DataType::Time64(TimeUnit::Microsecond) => {
+            primitives(array.as_primitive::<Time64MicrosecondType>(), |v| {
+                time_to_json(v, TimeUnit::Microsecond)
+            })
+        }
+        DataType::Time64(TimeUnit::Nanosecond) => {
+            primitives(array.as_primitive::<Time64NanosecondType>(), |v| {
+                time_to_json(v, TimeUnit::Nanosecond)
+            })
+        }
+        DataType::Timestamp(unit, timezone)
+            if timezone
+                .as_deref()
+                .is_none_or(|timezone| UTC_TIMEZONES.contains(&timezone)) =>
+        {
+            let utc = timezone.is_some();
+            match unit {
+                TimeUnit::Second => timestamps::<TimestampSecondType>(array, *unit, utc),
+                TimeUnit::Millisecond => timestamps::<TimestampMillisecondType>(array, *unit, utc),
+                TimeUnit::Microsecond => timestamps::<TimestampMicrosecondType>(array, *unit, utc),
+                TimeUnit::Nanosecond => timestamps::<TimestampNanosecondType>(array, *unit, utc),
+            }
+        }
+        DataType::Decimal128(precision, scale) => {
+            primitives(array.as_primitive::<Decimal128Type>(), |v| {
+                decimal_to_json(v, *precision, *scale)
+            })
+        }
+        DataType::Utf8 => array
+            .as_string::<i32>()
+            .iter()
+            .map(|value| value.map(Value::from).unwrap_or(Value::Null))
+            .collect(),
+        DataType::LargeUtf8 => array
+            .as_string::<i64>()
+            .iter()
+            .map(|value| value.map(Value::from).unwrap_or(Value::Null))
+            .collect(),
+        DataType::Utf8View => array
+            .as_string_view()
+            .iter()
+            .map(|value| value.map(Value::from).unwrap_or(Value::Null))
+            .collect(),
+        DataType::Binary => binaries(array.as_binary::<i32>().iter()),
+        DataType::LargeBinary => binaries(array.as_binary::<i64>().iter()),
+        DataType::BinaryView => binaries(array.as_binary_view().iter()),
+        DataType::FixedSizeBinary(_) => binaries(array.as_fixed_size_binary().iter()),
+        DataType::List(_) => lists(array.as_list::<i32>())?,
+        DataType::LargeList(_) => lists(array.as_list::<i64>())?,
+        DataType::FixedSizeList(_, _) => {
+            let list = array.as_fixed_size_list();
+            let length = list.value_length() as usize;
+            let items = if list.is_empty() {
+                Vec::new()
+            } else {
+                let start = list.value_offset(0) as usize;
+                arrow_array_to_json(list.values().slice(start, list.len() * length).as_ref())?
+            };
+            (0..list.len())
+                .map(|idx| Value::Array(items[idx * length..(idx + 1) * length].to_vec()))
+                .collect()
+        }
+        DataType::Struct(fields) => {
+            let struct_array = array.as_struct();
+            let mut objects = vec![serde_json::Map::new(); struct_array.len()];
+            for (field, column) in fields.iter().zip(struct_array.columns()) {
+                let field_values = arrow_array_to_json(column.as_ref())?;
+                for (object, value) in objects.iter_mut().zip(field_values) {
+                    object.insert(field.name().clone(), value);
+                }
+            }
+            objects.into_iter().map(Value::Object).collect()
+        }
+        DataType::Map(_, _) => {
+            // Maps become objects; non-string keys use their JSON text as the key
+            let map = array.as_map();
+            let (first, keys) = child_values(map.keys().as_ref(), map.value_offsets())?;
+            let (_, entries) = child_values(map.values().as_ref(), map.value_offsets())?;
+            map.value_offsets()
+                .windows(2)
+                .map(|w| {
+                    let (start, end) = (w[0] as usize - first, w[1] as usize - first);
+                    let object = keys[start..end]
+                        .iter()
+                        .zip(&entries[start..end])
+                        .map(|(key, value)| {
+                            let key = match key {
+                                Value::String(s) => s.clone(),
+                                other => other.to_string(),
+                            };
+                            (key, value.clone())
+                        })
+                        .collect();
+                    Value::Object(object)
+                })
+                .collect()
+        }
+        DataType::Dictionary(_, _) => {
+            // Decode the dictionary values once, then look each key up. A slice
+            // shares its whole dictionary, so if the dictionary holds more values
+            // than the slice has keys, decode just the values the keys use
+            let dictionary = array.as_any_dictionary();
+            if dictionary.values().len() > array.len() {
+                let keys = arrow::array::UInt64Array::from_iter_values(
+                    dictionary
+                        .normalized_keys()
+                        .into_iter()
+                        .map(|key| key as u64),
+                );
+                let used = arrow::compute::take(dictionary.values().as_ref(), &keys, None)?;
+                arrow_array_to_json(used.as_ref())?
+            } else {
+                let dictionary_values = arrow_array_to_json(dictionary.values().as_ref())?;
+                if dictionary_values.is_empty() {
+                    vec![Value::Null; array.len()]
+                } else {
+                    dictionary
+                        .normalized_keys()
+                        .into_iter()
+                        .map(|key| dictionary_values[key].clone())
+                        .collect()
+                }
+            }
+        }
+        _ => {
+            let formatter = ArrayFormatter::try_new(array, &FormatOptions::default())?;
+            (0..array.len())
+                .map(|idx| Value::String(formatter.value(idx).to_string()))
+                .collect()
+        }
+    };
+
+    // Nested, dictionary and formatted values were built without looking at the validity bitmap
+    if array.null_count() > 0 {
+        for (idx, value) in values.iter_mut().enumerate() {
+            if array.is_null(idx) {
+                *value = Value::Null;
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_reader::{parse_parquet_content, ParseResult};
+
+    #[test]
+    fn test_parse_parquet_with_nullable_fields() {
+        use arrow::array::{BooleanArray, Float64Array};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::arrow_writer::ArrowWriter;
+        use parquet::file::properties::WriterProperties;
+        use std::sync::Arc;
+
+        // Create schema with nullable fields
+        let schema = Schema::new(vec![
+            Field::new("active", DataType::Boolean, true),
+            Field::new("score", DataType::Float64, true),
+        ]);
+
+        // Create sample data with nulls
+        let active_array = BooleanArray::from(vec![Some(true), None, Some(false)]);
+        let score_array = Float64Array::from(vec![Some(95.5), None, Some(87.0)]);
+
+        // Create record batch
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(active_array), Arc::new(score_array)],
+        )
+        .unwrap();
+
+        // Write to Parquet in memory
+        let mut buffer = Vec::new();
+        let props = WriterProperties::builder().build();
+        let mut writer =
+            ArrowWriter::try_new(&mut buffer, batch.schema().clone(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        // Parse the Parquet data
+        let result = parse_parquet_content(buffer).unwrap();
+
+        match result {
+            ParseResult::PagedParquet(parquet) => {
+                let arr = parquet.read_rows(0, parquet.num_rows()).unwrap();
+                assert_eq!(arr.len(), 3);
+
+                // Check first row
+                assert_eq!(arr[0]["active"], true);
+                assert_eq!(arr[0]["score"].as_f64().unwrap(), 95.5);
+
+                // Check second row (with nulls)
+                assert_eq!(arr[1]["active"], Value::Null);
+                assert_eq!(arr[1]["score"], Value::Null);
+
+                // Check third row
+                assert_eq!(arr[2]["active"], false);
+                assert_eq!(arr[2]["score"].as_f64().unwrap(), 87.0);
+            }
+            _ => panic!("Expected Parquet result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_parquet_metadata() {
+        use arrow::array::{BinaryArray, Int64Array, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::arrow_writer::ArrowWriter;
+        use parquet::file::metadata::KeyValue;
+        use parquet::file::properties::WriterProperties;
+        use std::sync::Arc;
+
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("tag", DataType::Binary, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int64Array::from(vec![3, 1, 2])),
+                Arc::new(StringArray::from(vec![Some("b"), None, Some("a")])),
+                Arc::new(BinaryArray::from(vec![&b"b"[..], b"a", b"b"])),
+            ],
+        )
+        .unwrap();
+
+        let mut buffer = Vec::new();
+        let props = WriterProperties::builder()
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                "origin".to_string(),
+                "test".to_string(),
+            )]))
+            .build();
+        let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let metadata = match parse_parquet_content(buffer).unwrap() {
+            ParseResult::PagedParquet(parquet) => parquet.metadata_json(),
+            _ => panic!("Expected Parquet result"),
+        };
+
+        assert_eq!(metadata["num_rows"], 3);
+        assert!(metadata["created_by"].is_string());
+        assert_eq!(metadata["key_value_metadata"]["origin"], "test");
+
+        assert_eq!(metadata["arrow_schema"][0]["name"], "id");
+        assert_eq!(metadata["arrow_schema"][0]["data_type"], "Int64");
+        assert_eq!(metadata["arrow_schema"][0]["nullable"], false);
+        assert_eq!(metadata["arrow_schema"][1]["data_type"], "Utf8");
+
+        assert_eq!(metadata["parquet_schema"][0]["path"], "id");
+        assert_eq!(metadata["parquet_schema"][0]["physical_type"], "INT64");
+        assert_eq!(metadata["parquet_schema"][1]["physical_type"], "BYTE_ARRAY");
+
+        let row_group = &metadata["row_groups"][0];
+        assert_eq!(row_group["num_rows"], 3);
+        let id_stats = &row_group["columns"][0]["statistics"];
+        assert_eq!(id_stats["min"], 1);
+        assert_eq!(id_stats["max"], 3);
+        assert_eq!(id_stats["null_count"], 0);
+        let name_stats = &row_group["columns"][1]["statistics"];
+        assert_eq!(name_stats["min"], "a");
+        assert_eq!(name_stats["max"], "b");
+        assert_eq!(name_stats["null_count"], 1);
+        let tag_stats = &row_group["columns"][2]["statistics"];
+        assert_eq!(tag_stats["min"], serde_json::json!({"$binary": "YQ=="}));
+        assert_eq!(tag_stats["max"], serde_json::json!({"$binary": "Yg=="}));
+    }
+
+    /// Writes a single-column batch to Parquet, parses it back and returns the column's values.
+    fn parquet_column_round_trip(
+        field: arrow::datatypes::Field,
+        array: arrow::array::ArrayRef,
+    ) -> Vec<Value> {
+        use arrow::datatypes::Schema;
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::arrow_writer::ArrowWriter;
+        use std::sync::Arc;
+
+        let name = field.name().clone();
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(vec![field])), vec![array]).unwrap();
+
+        let mut buffer = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        match parse_parquet_content(buffer).unwrap() {
+            ParseResult::PagedParquet(parquet) => parquet
+                .read_rows(0, parquet.num_rows())
+                .unwrap()
+                .into_iter()
+                .map(|row| row[&name].clone())
+                .collect(),
+            _ => panic!("Expected Parquet result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_parquet_int32() {
+        use arrow::array::Int32Array;
+        use arrow::datatypes::{DataType, Field};
+        use std::sync::Arc;
+
+        let values = parquet_column_round_trip(
+            Field::new("n", DataType::Int32, true),
+            Arc::new(Int32Array::from(vec![Some(-7), None, Some(i32::MAX)])),
+        );
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!(-7),
+                Value::Null,
+                serde_json::json!(i32::MAX)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_large_utf8() {
+        use arrow::array::LargeStringArray;
+        use arrow::datatypes::{DataType, Field};
+        use std::sync::Arc;
+
+        let values = parquet_column_round_trip(
+            Field::new("s", DataType::LargeUtf8, true),
+            Arc::new(LargeStringArray::from(vec![Some("héllo"), None])),
+        );
+        assert_eq!(values, vec![serde_json::json!("héllo"), Value::Null]);
+    }
+
+    #[test]
+    fn test_parse_parquet_timestamp() {
+        use arrow::array::TimestampMillisecondArray;
+        use arrow::datatypes::{DataType, Field, TimeUnit};
+        use std::sync::Arc;
+
+        let values = parquet_column_round_trip(
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into())),
+                true,
+            ),
+            Arc::new(
+                TimestampMillisecondArray::from(vec![Some(1_704_164_645_000), None])
+                    .with_timezone("+00:00"),
+            ),
+        );
+        assert_eq!(
+            values,
+            vec![serde_json::json!("2024-01-02T03:04:05Z"), Value::Null]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_date32() {
+        use arrow::array::Date32Array;
+        use arrow::datatypes::{DataType, Field};
+        use std::sync::Arc;
+
+        let values = parquet_column_round_trip(
+            Field::new("d", DataType::Date32, false),
+            Arc::new(Date32Array::from(vec![0, 19_724])),
+        );
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!("1970-01-01"),
+                serde_json::json!("2024-01-02")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_decimal128() {
+        use arrow::array::Decimal128Array;
+        use arrow::datatypes::{DataType, Field};
+        use std::sync::Arc;
+
+        let array = Decimal128Array::from(vec![Some(12_345), Some(-5), None])
+            .with_precision_and_scale(10, 2)
+            .unwrap();
+        let values = parquet_column_round_trip(
+            Field::new("price", DataType::Decimal128(10, 2), true),
+            Arc::new(array),
+        );
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!("123.45"),
+                serde_json::json!("-0.05"),
+                Value::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_binary() {
+        use arrow::array::BinaryArray;
+        use arrow::datatypes::{DataType, Field};
+        use std::sync::Arc;
+
+        let values = parquet_column_round_trip(
+            Field::new("b", DataType::Binary, true),
+            Arc::new(BinaryArray::from(vec![
+                Some(&[0xde_u8, 0xad, 0xbe, 0xef][..]),
+                None,
+            ])),
+        );
+        assert_eq!(
+            values,
+            vec![serde_json::json!({"$binary": "3q2+7w=="}), Value::Null]
+        );
+    }
+
+    #[test]
+    fn test_temporal_and_decimal_values_match_arrow_display() {
+        use arrow::array::*;
+        use arrow::util::display::{ArrayFormatter, FormatOptions};
+        use std::sync::Arc;
+
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(Date32Array::from(vec![0, 19724, -1])),
+            Arc::new(Time32MillisecondArray::from(vec![0, 37_230_500])),
+            Arc::new(Time64NanosecondArray::from(vec![1, 37_230_000_000_000])),
+            Arc::new(TimestampMicrosecondArray::from(vec![
+                0,
+                1_700_000_000_123_456,
+            ])),
+            Arc::new(
+                TimestampMillisecondArray::from(vec![1_700_000_000_000]).with_timezone("+00:00"),
+            ),
+            Arc::new(
+                Decimal128Array::from(vec![12345, -5, 0])
+                    .with_precision_and_scale(6, 2)
+                    .unwrap(),
+            ),
+        ];
+        for array in arrays {
+            let formatter =
+                ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default()).unwrap();
+            let expected: Vec<Value> = (0..array.len())
+                .map(|idx| Value::String(formatter.value(idx).to_string()))
+                .collect();
+            assert_eq!(
+                arrow_array_to_json(array.as_ref()).unwrap(),
+                expected,
+                "{}",
+                array.data_type()
+            );
+        }
+    }
+
+    #[test]
+    fn test_sliced_nested_arrays() {
+        use arrow::array::*;
+        use arrow::datatypes::Int32Type;
+
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            Some(vec![Some(3)]),
+            None,
+            Some(vec![Some(4), Some(5)]),
+        ]);
+        assert_eq!(
+            arrow_array_to_json(&list.slice(1, 3)).unwrap(),
+            vec![
+                serde_json::json!([3]),
+                Value::Null,
+                serde_json::json!([4, 5])
+            ]
+        );
+
+        let fixed = FixedSizeListArray::from_iter_primitive::<Int32Type, _, _>(
+            vec![
+                Some(vec![Some(1), Some(2)]),
+                Some(vec![Some(3), Some(4)]),
+                Some(vec![Some(5), Some(6)]),
+            ],
+            2,
+        );
+        assert_eq!(
+            arrow_array_to_json(&fixed.slice(1, 2)).unwrap(),
+            vec![serde_json::json!([3, 4]), serde_json::json!([5, 6])]
+        );
+
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        for (key, value) in [("a", 1), ("b", 2), ("c", 3)] {
+            builder.keys().append_value(key);
+            builder.values().append_value(value);
+            builder.append(true).unwrap();
+        }
+        let map = builder.finish();
+        assert_eq!(
+            arrow_array_to_json(&map.slice(1, 2)).unwrap(),
+            vec![serde_json::json!({"b": 2}), serde_json::json!({"c": 3})]
+        );
+
+        let dictionary: DictionaryArray<Int32Type> = vec!["x", "y", "z", "x"].into_iter().collect();
+        assert_eq!(
+            arrow_array_to_json(&dictionary.slice(2, 2)).unwrap(),
+            vec![serde_json::json!("z"), serde_json::json!("x")]
+        );
+        let with_nulls: DictionaryArray<Int32Type> =
+            vec![Some("x"), None, Some("y")].into_iter().collect();
+        assert_eq!(
+            arrow_array_to_json(&with_nulls.slice(1, 1)).unwrap(),
+            vec![Value::Null]
+        );
+    }
+
+    #[test]
+    fn test_binary_is_told_apart_from_text() {
+        use arrow::array::{BinaryArray, FixedSizeBinaryArray, StringArray};
+
+        let text = arrow_array_to_json(&StringArray::from(vec!["hi"])).unwrap();
+        let binary = arrow_array_to_json(&BinaryArray::from(vec![&b"hi"[..]])).unwrap();
+        let fixed = FixedSizeBinaryArray::try_from_iter(vec![b"hi"].into_iter()).unwrap();
+        assert_eq!(text, vec![serde_json::json!("hi")]);
+        assert_eq!(binary, vec![serde_json::json!({"$binary": "aGk="})]);
+        assert_eq!(arrow_array_to_json(&fixed).unwrap(), binary);
+    }
+
+    #[test]
+    fn test_parse_parquet_dictionary() {
+        use arrow::array::DictionaryArray;
+        use arrow::datatypes::{DataType, Field, Int32Type};
+        use std::sync::Arc;
+
+        let array: DictionaryArray<Int32Type> = vec![Some("red"), Some("blue"), None, Some("red")]
+            .into_iter()
+            .collect();
+        let values = parquet_column_round_trip(
+            Field::new(
+                "color",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Arc::new(array),
+        );
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!("red"),
+                serde_json::json!("blue"),
+                Value::Null,
+                serde_json::json!("red")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_list() {
+        use arrow::array::ListArray;
+        use arrow::datatypes::{DataType, Field, Int64Type};
+        use std::sync::Arc;
+
+        let array = ListArray::from_iter_primitive::<Int64Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![]),
+            Some(vec![None, Some(3)]),
+        ]);
+        let values = parquet_column_round_trip(
+            Field::new(
+                "items",
+                DataType::List(Arc::new(Field::new_list_field(DataType::Int64, true))),
+                true,
+            ),
+            Arc::new(array),
+        );
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!([1, 2]),
+                Value::Null,
+                serde_json::json!([]),
+                serde_json::json!([null, 3])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_large_list() {
+        use arrow::array::LargeListArray;
+        use arrow::datatypes::{DataType, Field, Int32Type};
+        use std::sync::Arc;
+
+        let array = LargeListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(10)]),
+            Some(vec![Some(20), Some(30)]),
+        ]);
+        let values = parquet_column_round_trip(
+            Field::new(
+                "items",
+                DataType::LargeList(Arc::new(Field::new_list_field(DataType::Int32, true))),
+                false,
+            ),
+            Arc::new(array),
+        );
+        assert_eq!(
+            values,
+            vec![serde_json::json!([10]), serde_json::json!([20, 30])]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_struct() {
+        use arrow::array::{ArrayRef, Int64Array, StringArray, StructArray};
+        use arrow::buffer::NullBuffer;
+        use arrow::datatypes::{DataType, Field, Fields};
+        use std::sync::Arc;
+
+        let fields = Fields::from(vec![
+            Field::new("name", DataType::Utf8, true),
+            Field::new("age", DataType::Int64, true),
+        ]);
+        let array = StructArray::new(
+            fields.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![Some("Alice"), Some("x")])) as ArrayRef,
+                Arc::new(Int64Array::from(vec![Some(30), Some(0)])) as ArrayRef,
+            ],
+            Some(NullBuffer::from(vec![true, false])),
+        );
+        let values = parquet_column_round_trip(
+            Field::new("person", DataType::Struct(fields), true),
+            Arc::new(array),
+        );
+        assert_eq!(
+            values,
+            vec![serde_json::json!({"name": "Alice", "age": 30}), Value::Null]
+        );
+    }
+
+    #[test]
+    fn test_parse_parquet_map() {
+        use arrow::array::{Array, Int32Builder, MapBuilder, StringBuilder};
+        use arrow::datatypes::Field;
+        use std::sync::Arc;
+
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.keys().append_value("b");
+        builder.values().append_value(2);
+        builder.append(true).unwrap();
+        builder.append(false).unwrap();
+        let array = builder.finish();
+
+        let values = parquet_column_round_trip(
+            Field::new("attrs", array.data_type().clone(), true),
+            Arc::new(array),
+        );
+        assert_eq!(
+            values,
+            vec![serde_json::json!({"a": 1, "b": 2}), Value::Null]
+        );
+    }
+}
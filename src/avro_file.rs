@@ -0,0 +1,871 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::arrow_json::{date_to_json, decimal_to_json, time_to_json, timestamp_to_json};
+use crate::binary_reader::bytes_to_json;
+use crate::compression::Compression;
+use crate::json_reader::ParseError;
+use crate::json_value::float_to_json;
+use arrow::datatypes::TimeUnit;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Starts an Avro object container file.
+pub const AVRO_MAGIC: &[u8] = b"Obj\x01";
+
+/// Length of the marker that follows the header and every block.
+const SYNC_MARKER_LEN: usize = 16;
+
+/// Maximum nesting of values, so corrupt input can't overflow the stack.
+const MAX_DEPTH: usize = 512;
+
+/// An Avro object container file whose rows are decoded on request.
+///
+/// Opening the file reads its header and the position of each block; `read_rows`
+/// decompresses and decodes just the blocks that overlap the requested range.
+/// Records become JSON objects keyed by field name, and logical types (dates,
+/// times, timestamps and decimals) and bytes are shown as their Arrow
+/// counterparts are for Parquet files.
+#[derive(Debug)]
+pub struct AvroFile {
+    data: Vec<u8>,
+    schema: Schema,
+    named: Vec<Schema>,
+    /// The schema as written in the file
+    schema_json: Value,
+    codec: String,
+    /// Header metadata other than the schema and codec
+    metadata: Map<String, Value>,
+    blocks: Vec<Block>,
+}
+
+/// Where a block of rows is in the file.
+#[derive(Debug)]
+struct Block {
+    /// Offset of the block's (possibly compressed) data
+    offset: usize,
+    size: usize,
+    num_rows: usize,
+}
+
+impl AvroFile {
+    /// Opens an Avro file on disk and indexes its blocks.
+    pub fn open(path: &Path) -> Result<Self, ParseError> {
+        let data = std::fs::read(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+        Self::from_bytes(data)
+    }
+
+    /// Indexes the blocks of an Avro file that is already in memory.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ParseError> {
+        if !data.starts_with(AVRO_MAGIC) {
+            return Err(ParseError::InvalidAvro(
+                "not an Avro object container file".to_string(),
+            ));
+        }
+        let invalid = |(offset, message): (usize, String)| {
+            ParseError::InvalidAvro(format!("at byte {}: {}", offset, message))
+        };
+
+        let mut decoder = Decoder {
+            data: &data,
+            offset: AVRO_MAGIC.len(),
+        };
+        // Kept in file order, for the metadata node
+        let mut header = Vec::new();
+        decoder
+            .blocks(|decoder| {
+                let key = decoder.string()?;
+                let value = decoder.bytes()?;
+                header.push((key, value));
+                Ok(())
+            })
+            .map_err(invalid)?;
+        let sync = decoder.take(SYNC_MARKER_LEN).map_err(invalid)?;
+
+        let mut blocks = Vec::new();
+        while decoder.offset < data.len() {
+            let num_rows = decoder.length().map_err(invalid)?;
+            let size = decoder.length().map_err(invalid)?;
+            let offset = decoder.offset;
+            decoder.take(size).map_err(invalid)?;
+            if decoder.take(SYNC_MARKER_LEN).map_err(invalid)? != sync {
+                return Err(invalid((
+                    decoder.offset - SYNC_MARKER_LEN,
+                    "sync marker does not match the header".to_string(),
+                )));
+            }
+            blocks.push(Block {
+                offset,
+                size,
+                num_rows,
+            });
+        }
+
+        let schema_json: Value = remove_header_entry(&mut header, "avro.schema")
+            .ok_or_else(|| ParseError::InvalidAvro("the header has no schema".to_string()))
+            .and_then(|schema| {
+                serde_json::from_slice(schema).map_err(|e| {
+                    ParseError::InvalidAvro(format!("the schema is not valid JSON: {}", e))
+                })
+            })?;
+        let mut parser = SchemaParser::default();
+        let schema = parser
+            .parse(&schema_json, "")
+            .map_err(|e| ParseError::InvalidAvro(format!("invalid schema: {}", e)))?;
+        let codec = match remove_header_entry(&mut header, "avro.codec") {
+            Some(codec) => String::from_utf8_lossy(codec).into_owned(),
+            None => "null".to_string(),
+        };
+        if !matches!(
+            codec.as_str(),
+            "null" | "deflate" | "snappy" | "zstandard" | "bzip2" | "xz"
+        ) {
+            return Err(ParseError::InvalidAvro(format!(
+                "unsupported codec '{}'",
+                codec
+            )));
+        }
+        let metadata = header
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match std::str::from_utf8(value) {
+                    Ok(text) => Value::String(text.to_string()),
+                    Err(_) => bytes_to_json(value),
+                };
+                (key, value)
+            })
+            .collect();
+
+        Ok(Self {
+            data,
+            schema,
+            named: parser.named,
+            schema_json,
+            codec,
+            metadata,
+            blocks,
+        })
+    }
+
+    /// Total number of rows across all blocks.
+    pub fn num_rows(&self) -> usize {
+        self.blocks.iter().map(|block| block.num_rows).sum()
+    }
+
+    /// Number of blocks in the file.
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Describes the file's schema and blocks, as shown under the "Metadata" node.
+    pub fn metadata_json(&self) -> Value {
+        serde_json::json!({
+            "codec": self.codec,
+            "num_rows": self.num_rows(),
+            "metadata": self.metadata,
+            "avro_schema": self.schema_json,
+            "blocks": self
+                .blocks
+                .iter()
+                .map(|block| serde_json::json!({
+                    "num_rows": block.num_rows,
+                    "compressed_size": block.size,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Decodes up to `len` rows starting at row `start`.
+    ///
+    /// Only the blocks overlapping the range are decompressed.
+    pub fn read_rows(&self, start: usize, len: usize) -> Result<Vec<Value>, ParseError> {
+        let end = start.saturating_add(len).min(self.num_rows());
+        let mut rows = Vec::with_capacity(end.saturating_sub(start));
+        let mut block_start = 0;
+        for block in &self.blocks {
+            let block_end = block_start + block.num_rows;
+            if block_end > start && block_start < end {
+                let invalid = |message: String| {
+                    ParseError::InvalidAvro(format!("block at byte {}: {}", block.offset, message))
+                };
+                let data = self.decompress(block).map_err(invalid)?;
+                let mut decoder = Decoder {
+                    data: &data,
+                    offset: 0,
+                };
+                for row in block_start..end.min(block_end) {
+                    let value = decoder
+                        .value(&self.schema, &self.named, 0)
+                        .map_err(|(_, message)| invalid(message))?;
+                    if row >= start {
+                        rows.push(value);
+                    }
+                }
+            }
+            block_start = block_end;
+        }
+        Ok(rows)
+    }
+
+    fn decompress(&self, block: &Block) -> Result<Vec<u8>, String> {
+        let data = &self.data[block.offset..block.offset + block.size];
+        let compression = match self.codec.as_str() {
+            "null" => return Ok(data.to_vec()),
+            "snappy" => {
+                // The compressed data is followed by a CRC32 of the uncompressed data
+                let compressed = &data[..data.len().saturating_sub(4)];
+                return snap::raw::Decoder::new()
+                    .decompress_vec(compressed)
+                    .map_err(|e| e.to_string());
+            }
+            "deflate" => {
+                let mut decompressed = Vec::new();
+                flate2::read::DeflateDecoder::new(data)
+                    .read_to_end(&mut decompressed)
+                    .map_err(|e| e.to_string())?;
+                return Ok(decompressed);
+            }
+            "zstandard" => Compression::Zstd,
+            "bzip2" => Compression::Bzip2,
+            _ => Compression::Xz,
+        };
+        let mut decompressed = Vec::new();
+        compression
+            .decoder(data)
+            .map_err(|e| e.to_string())?
+            .read_to_end(&mut decompressed)
+            .map_err(|e| e.to_string())?;
+        Ok(decompressed)
+    }
+}
+
+/// Removes the header entry named `key`, returning its value.
+fn remove_header_entry<'a>(header: &mut Vec<(String, &'a [u8])>, key: &str) -> Option<&'a [u8]> {
+    let index = header.iter().position(|(name, _)| name == key)?;
+    Some(header.remove(index).1)
+}
+
+/// An Avro schema, with named types (records, enums and fixed) referred to by
+/// their index in the list of named types so that records can contain themselves.
+#[derive(Debug, Clone)]
+enum Schema {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Array(Box<Schema>),
+    Map(Box<Schema>),
+    Union(Vec<Schema>),
+    Record(Vec<(String, Schema)>),
+    Enum(Vec<String>),
+    Fixed(usize),
+    Named(usize),
+    Logical(LogicalType, Box<Schema>),
+}
+
+/// Logical types that are shown differently from the type that stores them.
+#[derive(Debug, Clone, Copy)]
+enum LogicalType {
+    Date,
+    TimeMillis,
+    TimeMicros,
+    TimestampMillis { utc: bool },
+    TimestampMicros { utc: bool },
+    TimestampNanos { utc: bool },
+    Decimal { precision: u8, scale: i8 },
+}
+
+#[derive(Default)]
+struct SchemaParser {
+    named: Vec<Schema>,
+    names: HashMap<String, usize>,
+}
+
+impl SchemaParser {
+    /// Parses a schema, resolving unqualified names against `namespace`.
+    fn parse(&mut self, json: &Value, namespace: &str) -> Result<Schema, String> {
+        match json {
+            Value::String(name) => self.primitive_or_reference(name, namespace),
+            Value::Array(branches) => Ok(Schema::Union(
+                branches
+                    .iter()
+                    .map(|branch| self.parse(branch, namespace))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Value::Object(object) => {
+                let type_name = object
+                    .get("type")
+                    .ok_or_else(|| "a schema object has no type".to_string())?;
+                let schema = match type_name.as_str() {
+                    Some("record" | "error" | "enum" | "fixed") => {
+                        self.named_type(object, namespace)?
+                    }
+                    Some("array") => Schema::Array(Box::new(
+                        self.parse(object.get("items").unwrap_or(&Value::Null), namespace)?,
+                    )),
+                    Some("map") => Schema::Map(Box::new(
+                        self.parse(object.get("values").unwrap_or(&Value::Null), namespace)?,
+                    )),
+                    _ => self.parse(type_name, namespace)?,
+                };
+                Ok(self.with_logical_type(object, schema))
+            }
+            other => Err(format!("unexpected {} in schema", other)),
+        }
+    }
+
+    fn primitive_or_reference(&self, name: &str, namespace: &str) -> Result<Schema, String> {
+        Ok(match name {
+            "null" => Schema::Null,
+            "boolean" => Schema::Boolean,
+            "int" => Schema::Int,
+            "long" => Schema::Long,
+            "float" => Schema::Float,
+            "double" => Schema::Double,
+            "bytes" => Schema::Bytes,
+            "string" => Schema::String,
+            _ => {
+                let index = self
+                    .names
+                    .get(&full_name(name, namespace))
+                    .or_else(|| self.names.get(name))
+                    .ok_or_else(|| format!("unknown type '{}'", name))?;
+                Schema::Named(*index)
+            }
+        })
+    }
+
+    /// Parses a record, enum or fixed type and registers it under its name.
+    fn named_type(
+        &mut self,
+        object: &Map<String, Value>,
+        namespace: &str,
+    ) -> Result<Schema, String> {
+        let name = object
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "a named type has no name".to_string())?;
+        let namespace = match object.get("namespace").and_then(Value::as_str) {
+            Some(namespace) => namespace.to_string(),
+            // A dotted name carries its own namespace
+            None => match name.rsplit_once('.') {
+                Some((namespace, _)) => namespace.to_string(),
+                None => namespace.to_string(),
+            },
+        };
+
+        // Registered before the fields are parsed, which may refer to it
+        let index = self.named.len();
+        self.named.push(Schema::Null);
+        self.names.insert(full_name(name, &namespace), index);
+
+        let schema = match object.get("type").and_then(Value::as_str) {
+            Some("enum") => Schema::Enum(
+                object
+                    .get("symbols")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| format!("enum '{}' has no symbols", name))?
+                    .iter()
+                    .map(|symbol| symbol.as_str().unwrap_or_default().to_string())
+                    .collect(),
+            ),
+            Some("fixed") => Schema::Fixed(
+                object
+                    .get("size")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| format!("fixed '{}' has no size", name))?
+                    as usize,
+            ),
+            _ => {
+                let fields = object
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| format!("record '{}' has no fields", name))?;
+                let mut parsed = Vec::with_capacity(fields.len());
+                for field in fields {
+                    let field_name = field
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| format!("a field of record '{}' has no name", name))?;
+                    let field_type = field.get("type").unwrap_or(&Value::Null);
+                    parsed.push((field_name.to_string(), self.parse(field_type, &namespace)?));
+                }
+                Schema::Record(parsed)
+            }
+        };
+        self.named[index] = schema;
+        Ok(Schema::Named(index))
+    }
+
+    /// Wraps `schema` in the object's logical type, if it has one that applies
+    /// to its type. Unknown logical types are ignored, as the specification says.
+    fn with_logical_type(&self, object: &Map<String, Value>, schema: Schema) -> Schema {
+        let Some(name) = object.get("logicalType").and_then(Value::as_str) else {
+            return schema;
+        };
+        let underlying = match &schema {
+            Schema::Named(index) => &self.named[*index],
+            other => other,
+        };
+        let logical = match (name, underlying) {
+            ("date", Schema::Int) => LogicalType::Date,
+            ("time-millis", Schema::Int) => LogicalType::TimeMillis,
+            ("time-micros", Schema::Long) => LogicalType::TimeMicros,
+            ("timestamp-millis", Schema::Long) => LogicalType::TimestampMillis { utc: true },
+            ("timestamp-micros", Schema::Long) => LogicalType::TimestampMicros { utc: true },
+            ("timestamp-nanos", Schema::Long) => LogicalType::TimestampNanos { utc: true },
+            ("local-timestamp-millis", Schema::Long) => LogicalType::TimestampMillis { utc: false },
+            ("local-timestamp-micros", Schema::Long) => LogicalType::TimestampMicros { utc: false },
+            ("local-timestamp-nanos", Schema::Long) => LogicalType::TimestampNanos { utc: false },
+            ("decimal", Schema::Bytes | Schema::Fixed(_)) => {
+                let precision = object.get("precision").and_then(Value::as_u64).unwrap_or(0);
+                let scale = object.get("scale").and_then(Value::as_u64).unwrap_or(0);
+                // Wider decimals than Arrow's 128-bit ones are shown as bytes
+                if precision == 0 || precision > 38 || scale > precision {
+                    return schema;
+                }
+                LogicalType::Decimal {
+                    precision: precision as u8,
+                    scale: scale as i8,
+                }
+            }
+            _ => return schema,
+        };
+        Schema::Logical(logical, Box::new(schema))
+    }
+}
+
+fn full_name(name: &str, namespace: &str) -> String {
+    if name.contains('.') || namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", namespace, name)
+    }
+}
+
+/// Reads Avro's binary encoding, tracking the offset for error messages.
+struct Decoder<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+type DecodeResult<T> = Result<T, (usize, String)>;
+
+impl<'a> Decoder<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> DecodeResult<T> {
+        Err((self.offset, message.into()))
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'a [u8]> {
+        if self.data.len() - self.offset < len {
+            return self.error(format!("expected {} more bytes", len));
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Reads a zigzag-encoded variable-length integer.
+    fn long(&mut self) -> DecodeResult<i64> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+        self.error("integer is too long")
+    }
+
+    fn int(&mut self) -> DecodeResult<i32> {
+        let value = self.long()?;
+        i32::try_from(value)
+            .or_else(|_| self.error(format!("{} is out of range for an int", value)))
+    }
+
+    /// Reads a count or size, which must not be negative.
+    fn length(&mut self) -> DecodeResult<usize> {
+        let value = self.long()?;
+        usize::try_from(value).or_else(|_| self.error(format!("invalid length {}", value)))
+    }
+
+    fn bytes(&mut self) -> DecodeResult<&'a [u8]> {
+        let len = self.length()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> DecodeResult<String> {
+        let bytes = self.bytes()?;
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Ok(text.to_string()),
+            Err(_) => self.error("invalid UTF-8 in string"),
+        }
+    }
+
+    /// Reads the blocks of an array or map, calling `item` for each item.
+    ///
+    /// A block may not hold more items than there are bytes left, so that a
+    /// corrupt count can't loop for ages over items that take no bytes (nulls).
+    fn blocks(&mut self, mut item: impl FnMut(&mut Self) -> DecodeResult<()>) -> DecodeResult<()> {
+        loop {
+            let count = self.long()?;
+            if count == 0 {
+                return Ok(());
+            }
+            if count < 0 {
+                // A negative count is followed by the block's size in bytes
+                self.long()?;
+            }
+            let remaining = self.data.len() - self.offset;
+            if count.unsigned_abs() > remaining as u64 {
+                return self.error(format!(
+                    "{} items but only {} bytes left",
+                    count.unsigned_abs(),
+                    remaining
+                ));
+            }
+            for _ in 0..count.unsigned_abs() {
+                item(self)?;
+            }
+        }
+    }
+
+    fn value(&mut self, schema: &Schema, named: &[Schema], depth: usize) -> DecodeResult<Value> {
+        if depth > MAX_DEPTH {
+            return self.error("values are nested too deeply");
+        }
+        Ok(match schema {
+            Schema::Null => Value::Null,
+            Schema::Boolean => Value::Bool(self.take(1)?[0] != 0),
+            Schema::Int => Value::from(self.int()?),
+            Schema::Long => Value::from(self.long()?),
+            Schema::Float => float_to_json(f32::from_le_bytes(self.array()?) as f64),
+            Schema::Double => float_to_json(f64::from_le_bytes(self.array()?)),
+            Schema::Bytes => bytes_to_json(self.bytes()?),
+            Schema::Fixed(size) => bytes_to_json(self.take(*size)?),
+            Schema::String => Value::String(self.string()?),
+            Schema::Array(items) => {
+                let mut values = Vec::new();
+                self.blocks(|decoder| {
+                    values.push(decoder.value(items, named, depth + 1)?);
+                    Ok(())
+                })?;
+                Value::Array(values)
+            }
+            Schema::Map(values) => {
+                let mut object = Map::new();
+                self.blocks(|decoder| {
+                    let key = decoder.string()?;
+                    object.insert(key, decoder.value(values, named, depth + 1)?);
+                    Ok(())
+                })?;
+                Value::Object(object)
+            }
+            Schema::Union(branches) => {
+                let index = self.long()?;
+                match usize::try_from(index)
+                    .ok()
+                    .and_then(|index| branches.get(index))
+                {
+                    Some(branch) => self.value(branch, named, depth + 1)?,
+                    None => return self.error(format!("union has no branch {}", index)),
+                }
+            }
+            Schema::Record(fields) => {
+                let mut object = Map::new();
+                for (name, field) in fields {
+                    object.insert(name.clone(), self.value(field, named, depth + 1)?);
+                }
+                Value::Object(object)
+            }
+            Schema::Enum(symbols) => {
+                let index = self.long()?;
+                match usize::try_from(index)
+                    .ok()
+                    .and_then(|index| symbols.get(index))
+                {
+                    Some(symbol) => Value::String(symbol.clone()),
+                    None => return self.error(format!("enum has no symbol {}", index)),
+                }
+            }
+            Schema::Named(index) => self.value(&named[*index], named, depth + 1)?,
+            Schema::Logical(logical, underlying) => self.logical(*logical, underlying, named)?,
+        })
+    }
+
+    fn array<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    /// Reads a value of a logical type, converted as the matching Arrow type is.
+    fn logical(
+        &mut self,
+        logical: LogicalType,
+        underlying: &Schema,
+        named: &[Schema],
+    ) -> DecodeResult<Value> {
+        Ok(match logical {
+            LogicalType::Date => date_to_json(self.int()?),
+            LogicalType::TimeMillis => time_to_json(self.int()?.into(), TimeUnit::Millisecond),
+            LogicalType::TimeMicros => time_to_json(self.long()?, TimeUnit::Microsecond),
+            LogicalType::TimestampMillis { utc } => {
+                timestamp_to_json(self.long()?, TimeUnit::Millisecond, utc)
+            }
+            LogicalType::TimestampMicros { utc } => {
+                timestamp_to_json(self.long()?, TimeUnit::Microsecond, utc)
+            }
+            LogicalType::TimestampNanos { utc } => {
+                timestamp_to_json(self.long()?, TimeUnit::Nanosecond, utc)
+            }
+            LogicalType::Decimal { precision, scale } => {
+                let size = match underlying {
+                    Schema::Named(index) => match &named[*index] {
+                        Schema::Fixed(size) => Some(*size),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let bytes = match size {
+                    Some(size) => self.take(size)?,
+                    None => self.bytes()?,
+                };
+                match big_endian_i128(bytes) {
+                    Some(unscaled) => decimal_to_json(unscaled, precision, scale),
+                    None => bytes_to_json(bytes),
+                }
+            }
+        })
+    }
+}
+
+/// Reads a big-endian two's complement integer of at most 16 bytes.
+fn big_endian_i128(bytes: &[u8]) -> Option<i128> {
+    if bytes.len() > 16 {
+        return None;
+    }
+    let fill = if bytes.first().is_some_and(|byte| byte & 0x80 != 0) {
+        0xff
+    } else {
+        0
+    };
+    let mut buffer = [fill; 16];
+    buffer[16 - bytes.len()..].copy_from_slice(bytes);
+    Some(i128::from_be_bytes(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a zigzag variable-length integer.
+    fn long(value: i64) -> Vec<u8> {
+        let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (zigzag & 0x7f) as u8;
+            zigzag >>= 7;
+            if zigzag == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    fn string(text: &str) -> Vec<u8> {
+        [long(text.len() as i64), text.as_bytes().to_vec()].concat()
+    }
+
+    /// Writes an Avro file with the given schema, codec and blocks of encoded rows.
+    fn avro_file(schema: &str, codec: &str, blocks: &[(i64, Vec<u8>)]) -> Vec<u8> {
+        let sync = [7u8; SYNC_MARKER_LEN];
+        let mut data = AVRO_MAGIC.to_vec();
+        data.extend(long(2));
+        data.extend(string("avro.schema"));
+        data.extend(string(schema));
+        data.extend(string("avro.codec"));
+        data.extend(string(codec));
+        data.extend(long(0));
+        data.extend(sync);
+        for (num_rows, rows) in blocks {
+            data.extend(long(*num_rows));
+            data.extend(long(rows.len() as i64));
+            data.extend(rows);
+            data.extend(sync);
+        }
+        data
+    }
+
+    const SCHEMA: &str = r#"{
+        "type": "record", "name": "Event", "namespace": "example",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "kind", "type": {"type": "enum", "name": "Kind", "symbols": ["CLICK", "VIEW"]}},
+            {"name": "at", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+            {"name": "price", "type": {"type": "bytes", "logicalType": "decimal", "precision": 6, "scale": 2}},
+            {"name": "tags", "type": {"type": "map", "values": "string"}},
+            {"name": "parent", "type": ["null", "Event"]}
+        ]
+    }"#;
+
+    /// Encodes an event, with a parent event if `parent` is given.
+    fn event(id: i64, parent: Option<i64>) -> Vec<u8> {
+        let mut row = [
+            long(id),
+            long(1),
+            long(1_700_000_000_000),
+            string("\x30\x39"),
+            long(1),
+            string("source"),
+            string("web"),
+            long(0),
+        ]
+        .concat();
+        match parent {
+            Some(parent) => {
+                row.extend(long(1));
+                row.extend(event(parent, None));
+            }
+            None => row.extend(long(0)),
+        }
+        row
+    }
+
+    #[test]
+    fn test_read_rows_across_blocks() {
+        let blocks = [
+            (2, [event(0, None), event(1, None)].concat()),
+            (1, event(2, Some(9))),
+        ];
+        let avro = AvroFile::from_bytes(avro_file(SCHEMA, "null", &blocks)).unwrap();
+        assert_eq!(avro.num_rows(), 3);
+        assert_eq!(avro.num_blocks(), 2);
+
+        let rows = avro.read_rows(1, 5).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0],
+            serde_json::json!({
+                "id": 1,
+                "kind": "VIEW",
+                "at": "2023-11-14T22:13:20Z",
+                "price": "123.45",
+                "tags": {"source": "web"},
+                "parent": null,
+            })
+        );
+        assert_eq!(rows[1]["parent"]["id"], 9);
+        assert_eq!(rows[1]["parent"]["parent"], Value::Null);
+
+        let metadata = avro.metadata_json();
+        assert_eq!(metadata["codec"], "null");
+        assert_eq!(metadata["avro_schema"]["name"], "Event");
+        assert_eq!(metadata["blocks"][1]["num_rows"], 1);
+    }
+
+    #[test]
+    fn test_compressed_blocks() {
+        use std::io::Write;
+        let rows = [event(0, None), event(1, None)].concat();
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&rows).unwrap();
+        let deflated = encoder.finish().unwrap();
+
+        let avro = AvroFile::from_bytes(avro_file(SCHEMA, "deflate", &[(2, deflated)])).unwrap();
+        let ids: Vec<i64> = avro
+            .read_rows(0, 10)
+            .unwrap()
+            .iter()
+            .map(|row| row["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_binary_metadata() {
+        let data = [
+            AVRO_MAGIC.to_vec(),
+            long(2),
+            string("avro.schema"),
+            string("\"long\""),
+            string("app.key"),
+            long(2),
+            vec![0xff, 0xfe],
+            long(0),
+            vec![7; SYNC_MARKER_LEN],
+        ]
+        .concat();
+        let avro = AvroFile::from_bytes(data).unwrap();
+        assert_eq!(
+            avro.metadata_json()["metadata"]["app.key"],
+            serde_json::json!({"$binary": "//4="})
+        );
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        assert!(matches!(
+            AvroFile::from_bytes(b"PAR1".to_vec()),
+            Err(ParseError::InvalidAvro(_))
+        ));
+
+        let mut data = avro_file(SCHEMA, "null", &[(1, event(0, None))]);
+        // Corrupt the final sync marker
+        *data.last_mut().unwrap() = 0;
+        match AvroFile::from_bytes(data) {
+            Err(ParseError::InvalidAvro(message)) => {
+                assert!(message.contains("sync marker"), "{}", message)
+            }
+            other => panic!("Expected InvalidAvro error, got {:?}", other),
+        }
+
+        // The block claims two rows but holds one
+        let avro = AvroFile::from_bytes(avro_file(SCHEMA, "null", &[(2, event(0, None))])).unwrap();
+        assert!(matches!(
+            avro.read_rows(0, 2),
+            Err(ParseError::InvalidAvro(_))
+        ));
+    }
+
+    #[test]
+    fn test_array_count_bounded_by_data() {
+        let schema = r#"{"type": "array", "items": "null"}"#;
+        let avro = AvroFile::from_bytes(avro_file(schema, "null", &[(1, long(1 << 40))])).unwrap();
+        match avro.read_rows(0, 1) {
+            Err(ParseError::InvalidAvro(message)) => {
+                assert!(message.contains("bytes left"), "{}", message)
+            }
+            other => panic!("Expected InvalidAvro error, got {:?}", other),
+        }
+
+        // Nulls take no bytes, but are fine while there are as many bytes left
+        let rows = [long(2), long(0), long(0)].concat();
+        let avro = AvroFile::from_bytes(avro_file(schema, "null", &[(1, rows)])).unwrap();
+        assert_eq!(
+            avro.read_rows(0, 1).unwrap(),
+            vec![serde_json::json!([null, null])]
+        );
+    }
+}
@@ -0,0 +1,613 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading MessagePack, CBOR and BSON into JSON values.
+//!
+//! These formats have types JSON lacks, which are kept as single-purpose
+//! objects whose keys start with `$`, in the style of MongoDB's Extended JSON:
+//!
+//! - byte strings: `{"$binary": "<base64>"}`, plus `"$subtype": N` for BSON
+//!   binary data with a subtype other than 0
+//! - MessagePack extension types: `{"$ext": N, "$binary": "<base64>"}`
+//! - CBOR tags: `{"$tag": N, "$value": ...}`
+//! - BSON ObjectIds, dates, timestamps, regular expressions, code, decimals and
+//!   the other BSON-only types: `{"$oid": "<hex>"}`, `{"$date": <ms>}` and so on
+//!
+//! NaN and infinite floats become `{"$float": ...}` objects, and map keys that
+//! aren't strings use their JSON text, as Arrow maps do.
+
+use crate::json_reader::{ParseControl, ParseError, ParseProgress, ParseResult};
+use crate::json_value::{float_to_json, BINARY_KEY};
+use base64::Engine;
+use serde_json::{json, Map, Value};
+
+/// Number of top-level values decoded between progress reports.
+const VALUES_PER_PROGRESS_REPORT: usize = 1000;
+
+/// Maximum nesting of BSON documents, so corrupt input can't overflow the stack.
+const MAX_BSON_DEPTH: usize = 512;
+
+/// Parses MessagePack content.
+///
+/// Content holding several values one after another is read as an array of them.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Single(_))` if content is valid MessagePack
+/// * `Err(ParseError::InvalidMsgPack(_))` with the offset of the value that failed
+pub fn parse_msgpack_content(
+    data: &[u8],
+    control: &ParseControl,
+) -> Result<ParseResult, ParseError> {
+    decode_values(data, control, |reader| {
+        let offset = data.len() - reader.len();
+        rmpv::decode::read_value(reader)
+            .map(msgpack_to_json)
+            .map_err(|e| ParseError::InvalidMsgPack(format!("value at byte {}: {}", offset, e)))
+    })
+}
+
+/// Parses CBOR content.
+///
+/// Content holding several values one after another (a CBOR sequence) is read
+/// as an array of them.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Single(_))` if content is valid CBOR
+/// * `Err(ParseError::InvalidCbor(_))` with the offset of the value that failed
+pub fn parse_cbor_content(data: &[u8], control: &ParseControl) -> Result<ParseResult, ParseError> {
+    decode_values(data, control, |reader| {
+        let offset = data.len() - reader.len();
+        ciborium::de::from_reader::<ciborium::Value, _>(reader)
+            .map(cbor_to_json)
+            .map_err(|e| ParseError::InvalidCbor(format!("value at byte {}: {}", offset, e)))
+    })
+}
+
+/// Parses BSON content.
+///
+/// Dumps such as those written by `mongodump` hold one document after another,
+/// and are read as an array of documents.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Single(_))` if content is valid BSON
+/// * `Err(ParseError::InvalidBson(_))` with the offset where decoding failed
+pub fn parse_bson_content(data: &[u8], control: &ParseControl) -> Result<ParseResult, ParseError> {
+    decode_values(data, control, |reader| {
+        let mut bson = BsonReader {
+            data,
+            offset: data.len() - reader.len(),
+        };
+        let document = bson.document(0);
+        *reader = &data[bson.offset.min(data.len())..];
+        document.map_err(|(offset, message)| {
+            ParseError::InvalidBson(format!("at byte {}: {}", offset, message))
+        })
+    })
+}
+
+/// Decodes values from `data` until it is used up, with `decode` reading one
+/// value from the front of the slice and advancing it.
+fn decode_values<'a>(
+    data: &'a [u8],
+    control: &ParseControl,
+    mut decode: impl FnMut(&mut &'a [u8]) -> Result<Value, ParseError>,
+) -> Result<ParseResult, ParseError> {
+    let mut reader = data;
+    let mut values = Vec::new();
+    loop {
+        values.push(decode(&mut reader)?);
+        if reader.is_empty() {
+            break;
+        }
+        if values.len().is_multiple_of(VALUES_PER_PROGRESS_REPORT) {
+            control.check_cancelled()?;
+            control.report(ParseProgress {
+                bytes_processed: (data.len() - reader.len()) as u64,
+                total_bytes: Some(data.len() as u64),
+                rows_processed: values.len(),
+            });
+        }
+    }
+
+    let value = if values.len() == 1 {
+        values.pop().unwrap_or(Value::Null)
+    } else {
+        Value::Array(values)
+    };
+    Ok(ParseResult::Single(value))
+}
+
+/// Converts a byte string to an object holding it as base64 (`BINARY_KEY`).
+pub fn bytes_to_json(bytes: &[u8]) -> Value {
+    json!({ BINARY_KEY: base64::engine::general_purpose::STANDARD.encode(bytes) })
+}
+
+/// Converts map entries to an object, using the JSON text of keys that aren't strings.
+fn entries_to_object(entries: impl IntoIterator<Item = (Value, Value)>) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .map(|(key, value)| {
+                let key = match key {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (key, value)
+            })
+            .collect(),
+    )
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(b) => Value::Bool(b),
+        rmpv::Value::Integer(i) => match (i.as_i64(), i.as_u64()) {
+            (Some(i), _) => Value::from(i),
+            (None, Some(u)) => Value::from(u),
+            (None, None) => Value::Null,
+        },
+        rmpv::Value::F32(f) => float_to_json(f as f64),
+        rmpv::Value::F64(f) => float_to_json(f),
+        // Strings that aren't UTF-8 are kept as their bytes
+        rmpv::Value::String(s) if s.is_str() => Value::String(s.into_str().unwrap_or_default()),
+        rmpv::Value::String(s) => bytes_to_json(&s.into_bytes()),
+        rmpv::Value::Binary(bytes) => bytes_to_json(&bytes),
+        rmpv::Value::Array(items) => Value::Array(items.into_iter().map(msgpack_to_json).collect()),
+        rmpv::Value::Map(entries) => entries_to_object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (msgpack_to_json(key), msgpack_to_json(value))),
+        ),
+        rmpv::Value::Ext(type_id, bytes) => {
+            let mut value = bytes_to_json(&bytes);
+            value["$ext"] = Value::from(type_id);
+            value
+        }
+    }
+}
+
+fn cbor_to_json(value: ciborium::Value) -> Value {
+    match value {
+        ciborium::Value::Null => Value::Null,
+        ciborium::Value::Bool(b) => Value::Bool(b),
+        ciborium::Value::Integer(i) => Value::Number(i128::from(i).into()),
+        ciborium::Value::Float(f) => float_to_json(f),
+        ciborium::Value::Text(s) => Value::String(s),
+        ciborium::Value::Bytes(bytes) => bytes_to_json(&bytes),
+        ciborium::Value::Array(items) => {
+            Value::Array(items.into_iter().map(cbor_to_json).collect())
+        }
+        ciborium::Value::Map(entries) => entries_to_object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (cbor_to_json(key), cbor_to_json(value))),
+        ),
+        ciborium::Value::Tag(tag, value) => json!({ "$tag": tag, "$value": cbor_to_json(*value) }),
+        // ciborium::Value is non-exhaustive
+        _ => Value::Null,
+    }
+}
+
+/// Reads BSON documents, tracking the offset for error messages.
+struct BsonReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+type BsonResult<T> = Result<T, (usize, String)>;
+
+impl<'a> BsonReader<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> BsonResult<T> {
+        Err((self.offset, message.into()))
+    }
+
+    fn take(&mut self, len: usize) -> BsonResult<&'a [u8]> {
+        if self.data.len() - self.offset < len {
+            return self.error(format!("expected {} more bytes", len));
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> BsonResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> BsonResult<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> BsonResult<i32> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> BsonResult<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn length(&mut self, min: usize) -> BsonResult<usize> {
+        let len = self.i32()?;
+        match usize::try_from(len) {
+            Ok(len) if len >= min => Ok(len),
+            _ => self.error(format!("invalid length {}", len)),
+        }
+    }
+
+    /// Reads a NUL-terminated string, as used for element names and regular expressions.
+    fn cstring(&mut self) -> BsonResult<String> {
+        let rest = &self.data[self.offset..];
+        let Some(len) = rest.iter().position(|byte| *byte == 0) else {
+            return self.error("unterminated string");
+        };
+        let text = self.utf8(&rest[..len])?;
+        self.offset += len + 1;
+        Ok(text)
+    }
+
+    /// Reads a length-prefixed string.
+    fn string(&mut self) -> BsonResult<String> {
+        let len = self.length(1)?;
+        let bytes = self.take(len)?;
+        if bytes[len - 1] != 0 {
+            return Err((self.offset - 1, "string is not NUL-terminated".to_string()));
+        }
+        let bytes = &bytes[..len - 1];
+        self.utf8(bytes)
+    }
+
+    fn utf8(&self, bytes: &[u8]) -> BsonResult<String> {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Ok(text.to_string()),
+            Err(_) => self.error("invalid UTF-8 in string"),
+        }
+    }
+
+    fn object_id(&mut self) -> BsonResult<Value> {
+        let bytes = self.take(12)?;
+        Ok(json!({ "$oid": bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>() }))
+    }
+
+    /// Reads a document, as an object, with `depth` documents around it.
+    fn document(&mut self, depth: usize) -> BsonResult<Value> {
+        if depth > MAX_BSON_DEPTH {
+            return self.error("documents are nested too deeply");
+        }
+        let start = self.offset;
+        let len = self.length(5)?;
+        let end = start + len;
+        if end > self.data.len() {
+            return Err((start, format!("document of {} bytes is truncated", len)));
+        }
+
+        let mut object = Map::new();
+        loop {
+            let element_type = self.u8()?;
+            if element_type == 0 {
+                break;
+            }
+            let name = self.cstring()?;
+            let value = self.element(element_type, depth)?;
+            object.insert(name, value);
+            if self.offset >= end {
+                return self.error("document is missing its terminator");
+            }
+        }
+        if self.offset != end {
+            return Err((
+                start,
+                format!(
+                    "document is {} bytes but its length says {}",
+                    self.offset - start,
+                    len
+                ),
+            ));
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn element(&mut self, element_type: u8, depth: usize) -> BsonResult<Value> {
+        let value = match element_type {
+            0x01 => float_to_json(f64::from_le_bytes(self.array()?)),
+            0x02 => Value::String(self.string()?),
+            0x03 => self.document(depth + 1)?,
+            // Arrays are documents keyed "0", "1", ...
+            0x04 => match self.document(depth + 1)? {
+                Value::Object(items) => Value::Array(items.into_iter().map(|(_, v)| v).collect()),
+                other => other,
+            },
+            0x05 => {
+                let len = self.length(0)?;
+                let subtype = self.u8()?;
+                let mut value = bytes_to_json(self.take(len)?);
+                if subtype != 0 {
+                    value["$subtype"] = Value::from(subtype);
+                }
+                value
+            }
+            0x06 => json!({ "$undefined": true }),
+            0x07 => self.object_id()?,
+            0x08 => Value::Bool(self.u8()? != 0),
+            0x09 => json!({ "$date": self.i64()? }),
+            0x0A => Value::Null,
+            0x0B => {
+                let pattern = self.cstring()?;
+                let options = self.cstring()?;
+                json!({ "$regex": pattern, "$options": options })
+            }
+            0x0C => {
+                let namespace = self.string()?;
+                let id = self.object_id()?;
+                json!({ "$dbPointer": { "$ref": namespace, "$id": id } })
+            }
+            0x0D => json!({ "$code": self.string()? }),
+            0x0E => json!({ "$symbol": self.string()? }),
+            0x0F => {
+                let _total_len = self.length(14)?;
+                let code = self.string()?;
+                let scope = self.document(depth + 1)?;
+                json!({ "$code": code, "$scope": scope })
+            }
+            0x10 => Value::from(self.i32()?),
+            0x11 => {
+                let increment = u32::from_le_bytes(self.array()?);
+                let seconds = u32::from_le_bytes(self.array()?);
+                json!({ "$timestamp": { "t": seconds, "i": increment } })
+            }
+            0x12 => Value::from(self.i64()?),
+            0x13 => {
+                json!({ "$numberDecimal": decimal128_to_string(u128::from_le_bytes(self.array()?)) })
+            }
+            0xFF => json!({ "$minKey": 1 }),
+            0x7F => json!({ "$maxKey": 1 }),
+            _ => {
+                return Err((
+                    self.offset - 1,
+                    format!("unknown element type 0x{:02x}", element_type),
+                ))
+            }
+        };
+        Ok(value)
+    }
+}
+
+/// Formats an IEEE 754 decimal128 in BID encoding, as BSON stores it, in the
+/// scientific notation of the BSON specification, e.g. `1.23E+5`.
+fn decimal128_to_string(bits: u128) -> String {
+    const EXPONENT_BIAS: i64 = 6176;
+    let sign = if bits >> 127 == 1 { "-" } else { "" };
+    let combination = (bits >> 122) & 0x1f;
+    if combination == 0x1f {
+        return "NaN".to_string();
+    }
+    if combination == 0x1e {
+        return format!("{}Infinity", sign);
+    }
+
+    let (exponent, significand) = if (bits >> 125) & 0b11 == 0b11 {
+        // The implied leading bits make the significand too large to be canonical
+        (((bits >> 111) & 0x3fff) as i64, 0)
+    } else {
+        (((bits >> 113) & 0x3fff) as i64, bits & ((1 << 113) - 1))
+    };
+    let exponent = exponent - EXPONENT_BIAS;
+    let digits = significand.to_string();
+    let adjusted = exponent + digits.len() as i64 - 1;
+
+    if exponent <= 0 && adjusted >= -6 {
+        if exponent == 0 {
+            return format!("{}{}", sign, digits);
+        }
+        let point = digits.len() as i64 + exponent;
+        if point > 0 {
+            let (whole, fraction) = digits.split_at(point as usize);
+            format!("{}{}.{}", sign, whole, fraction)
+        } else {
+            format!("{}0.{}{}", sign, "0".repeat(-point as usize), digits)
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let point = if rest.is_empty() { "" } else { "." };
+        format!("{}{}{}{}E{:+}", sign, first, point, rest, adjusted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(result: Result<ParseResult, ParseError>) -> Value {
+        match result {
+            Ok(ParseResult::Single(value)) => value,
+            other => panic!("Expected Single result, got {:?}", other),
+        }
+    }
+
+    /// Encodes BSON elements, given as (type, name, payload), into a document.
+    fn bson_document(elements: &[(u8, &str, Vec<u8>)]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (element_type, name, payload) in elements {
+            body.push(*element_type);
+            body.extend_from_slice(name.as_bytes());
+            body.push(0);
+            body.extend_from_slice(payload);
+        }
+        body.push(0);
+        let mut document = ((body.len() + 4) as i32).to_le_bytes().to_vec();
+        document.extend(body);
+        document
+    }
+
+    fn bson_string(text: &str) -> Vec<u8> {
+        let mut bytes = ((text.len() + 1) as i32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(text.as_bytes());
+        bytes.push(0);
+        bytes
+    }
+
+    #[test]
+    fn test_msgpack_round_trip() {
+        let value = rmpv::Value::Map(vec![
+            ("name".into(), "slopjson".into()),
+            ("count".into(), rmpv::Value::from(u64::MAX)),
+            ("ratio".into(), rmpv::Value::F64(0.5)),
+            (
+                "tags".into(),
+                rmpv::Value::Array(vec![true.into(), rmpv::Value::Nil]),
+            ),
+            ("blob".into(), rmpv::Value::Binary(vec![0, 1, 2, 255])),
+            (1.into(), rmpv::Value::Ext(-1, vec![0, 0, 0, 1])),
+        ]);
+        let mut data = Vec::new();
+        rmpv::encode::write_value(&mut data, &value).unwrap();
+
+        let parsed = single(parse_msgpack_content(&data, &ParseControl::default()));
+        assert_eq!(
+            parsed,
+            json!({
+                "name": "slopjson",
+                "count": u64::MAX,
+                "ratio": 0.5,
+                "tags": [true, null],
+                "blob": {"$binary": "AAEC/w=="},
+                "1": {"$ext": -1, "$binary": "AAAAAQ=="},
+            })
+        );
+    }
+
+    #[test]
+    fn test_msgpack_stream_and_errors() {
+        let mut data = Vec::new();
+        rmpv::encode::write_value(&mut data, &rmpv::Value::from(1)).unwrap();
+        rmpv::encode::write_value(&mut data, &rmpv::Value::from("two")).unwrap();
+        let parsed = single(parse_msgpack_content(&data, &ParseControl::default()));
+        assert_eq!(parsed, json!([1, "two"]));
+
+        // A string header claiming more bytes than there are
+        data.extend_from_slice(&[0xa5, b'a']);
+        match parse_msgpack_content(&data, &ParseControl::default()) {
+            Err(ParseError::InvalidMsgPack(message)) => {
+                assert!(message.starts_with("value at byte 5:"), "{}", message)
+            }
+            other => panic!("Expected InvalidMsgPack error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let value = ciborium::Value::Map(vec![
+            ("id".into(), ciborium::Value::Integer((-7).into())),
+            ("bytes".into(), ciborium::Value::Bytes(b"hi".to_vec())),
+            (
+                "when".into(),
+                ciborium::Value::Tag(1, Box::new(ciborium::Value::Integer(1_700_000_000.into()))),
+            ),
+            (ciborium::Value::Bool(true), ciborium::Value::Float(1.5)),
+        ]);
+        let mut data = Vec::new();
+        ciborium::ser::into_writer(&value, &mut data).unwrap();
+
+        let parsed = single(parse_cbor_content(&data, &ParseControl::default()));
+        assert_eq!(
+            parsed,
+            json!({
+                "id": -7,
+                "bytes": {"$binary": "aGk="},
+                "when": {"$tag": 1, "$value": 1_700_000_000},
+                "true": 1.5,
+            })
+        );
+
+        assert!(matches!(
+            parse_cbor_content(&data[..data.len() - 1], &ParseControl::default()),
+            Err(ParseError::InvalidCbor(_))
+        ));
+    }
+
+    #[test]
+    fn test_bson_round_trip() {
+        let inner = bson_document(&[(0x10, "0", 1i32.to_le_bytes().to_vec())]);
+        let mut binary_payload = 3i32.to_le_bytes().to_vec();
+        binary_payload.push(4);
+        binary_payload.extend_from_slice(&[1, 2, 3]);
+        let first = bson_document(&[
+            (0x07, "_id", (0..12).collect()),
+            (0x02, "name", bson_string("Ada")),
+            (0x12, "big", i64::MIN.to_le_bytes().to_vec()),
+            (0x01, "score", 2.5f64.to_le_bytes().to_vec()),
+            (0x09, "at", 1_700_000_000_000i64.to_le_bytes().to_vec()),
+            (0x04, "list", inner),
+            (0x05, "uuid", binary_payload),
+            (0x0A, "none", Vec::new()),
+        ]);
+        let second = bson_document(&[(0x08, "ok", vec![1])]);
+        let data = [first, second].concat();
+
+        let parsed = single(parse_bson_content(&data, &ParseControl::default()));
+        assert_eq!(
+            parsed,
+            json!([
+                {
+                    "_id": {"$oid": "000102030405060708090a0b"},
+                    "name": "Ada",
+                    "big": i64::MIN,
+                    "score": 2.5,
+                    "at": {"$date": 1_700_000_000_000i64},
+                    "list": [1],
+                    "uuid": {"$binary": "AQID", "$subtype": 4},
+                    "none": null,
+                },
+                {"ok": true},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_bson_errors() {
+        let document = bson_document(&[(0x02, "name", bson_string("Ada"))]);
+        match parse_bson_content(&document[..document.len() - 2], &ParseControl::default()) {
+            Err(ParseError::InvalidBson(message)) => {
+                assert!(message.contains("truncated"), "{}", message)
+            }
+            other => panic!("Expected InvalidBson error, got {:?}", other),
+        }
+
+        let document = bson_document(&[(0x42, "odd", Vec::new())]);
+        match parse_bson_content(&document, &ParseControl::default()) {
+            Err(ParseError::InvalidBson(message)) => {
+                assert!(message.contains("unknown element type 0x42"), "{}", message)
+            }
+            other => panic!("Expected InvalidBson error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decimal128_to_string() {
+        let decimal =
+            |significand: u128, exponent: i64| (((exponent + 6176) as u128) << 113) | significand;
+        assert_eq!(decimal128_to_string(decimal(12345, -2)), "123.45");
+        assert_eq!(decimal128_to_string(decimal(5, -3)), "0.005");
+        assert_eq!(decimal128_to_string(decimal(123, 3)), "1.23E+5");
+        assert_eq!(decimal128_to_string(decimal(0, 0)), "0");
+        assert_eq!(decimal128_to_string(decimal(1, 0) | 1 << 127), "-1");
+        assert_eq!(decimal128_to_string(0x1e << 122), "Infinity");
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::archive::ArchiveKind;
+use crate::compression::Compression;
+use crate::csv_reader::CsvOptions;
+use crate::json_reader::{InputFormat, InputSource};
+use std::path::{Path, PathBuf};
+
+/// Shown when the command line can't be parsed.
+pub const USAGE: &str = "usage: slopjson \
+                          [--format json|jsonl|json5|yaml|toml|csv|tsv|parquet|arrow|avro|msgpack|cbor|bson|ipynb] \
+                          [--delimiter CHAR] [--quote CHAR|none] [FILE|-]...";
+
+/// The files to open and how to read them, from the command line.
+#[derive(Debug, Default, PartialEq)]
+pub struct CommandLine {
+    /// Files to open, in order; `-` stands for standard input
+    pub inputs: Vec<InputSource>,
+    /// Format given with `--format`, applied to every input
+    pub format: Option<InputFormat>,
+    /// Delimiter given with `--delimiter`, for CSV and TSV inputs
+    pub delimiter: Option<u8>,
+    /// Quote character given with `--quote`, `Some(None)` for `--quote none`
+    pub quote: Option<Option<u8>>,
+}
+
+impl CommandLine {
+    /// Parses the arguments after the program name.
+    ///
+    /// Accepts `--format FORMAT` or `--format=FORMAT` anywhere, `-` for standard
+    /// input, and `--` to treat every following argument as a file name.
+    /// `--delimiter` and `--quote` set the delimiter or quote character of the
+    /// inputs read as CSV or TSV (see `format_for`); `--quote none` turns
+    /// quoting off. They are an error if no input can be read as CSV.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut command_line = CommandLine::default();
+        let mut args = args.into_iter();
+        let mut options_done = false;
+
+        while let Some(arg) = args.next() {
+            if options_done {
+                command_line
+                    .inputs
+                    .push(InputSource::File(PathBuf::from(arg)));
+            } else if arg == "--" {
+                options_done = true;
+            } else if arg == "-" {
+                command_line.inputs.push(InputSource::Stdin);
+            } else if arg.starts_with("--") {
+                let (option, value) = match arg.split_once('=') {
+                    Some((option, value)) => (option, Some(value.to_string())),
+                    None => (arg.as_str(), None),
+                };
+                if !matches!(option, "--format" | "--delimiter" | "--quote") {
+                    return Err(format!("unknown option '{}'", arg));
+                }
+                let value = match value {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .ok_or_else(|| format!("{} needs a value", option))?,
+                };
+                match option {
+                    "--format" => command_line.format = Some(value.parse()?),
+                    "--delimiter" => command_line.delimiter = Some(parse_csv_char(option, &value)?),
+                    _ if value == "none" => command_line.quote = Some(None),
+                    _ => command_line.quote = Some(Some(parse_csv_char(option, &value)?)),
+                }
+            } else {
+                command_line
+                    .inputs
+                    .push(InputSource::File(PathBuf::from(arg)));
+            }
+        }
+
+        if command_line.delimiter.is_some() || command_line.quote.is_some() {
+            if let Some(format) = command_line
+                .format
+                .filter(|format| !matches!(format, InputFormat::Csv(_)))
+            {
+                return Err(format!(
+                    "--delimiter and --quote can't be used with {}",
+                    format.label()
+                ));
+            }
+            // Standard input is added later if no inputs are given
+            if !command_line.inputs.is_empty()
+                && !command_line
+                    .inputs
+                    .iter()
+                    .any(|input| command_line.csv_format_for(input).is_some())
+            {
+                return Err("--delimiter and --quote need a CSV or TSV input".to_string());
+            }
+        }
+
+        Ok(command_line)
+    }
+
+    /// Returns the format to read `input` in: the one given with `--format`,
+    /// or CSV with the options of `--delimiter` and `--quote` for an input
+    /// that is CSV or TSV by its extension or has no known format.
+    ///
+    /// Other inputs are left for their extension or content to decide.
+    pub fn format_for(&self, input: &InputSource) -> Option<InputFormat> {
+        self.csv_format_for(input).or(self.format)
+    }
+
+    /// Returns the CSV format with the `--delimiter` and `--quote` options
+    /// applied, if they were given and can apply to `input`.
+    fn csv_format_for(&self, input: &InputSource) -> Option<InputFormat> {
+        if self.delimiter.is_none() && self.quote.is_none() {
+            return None;
+        }
+        let format = match (self.format, input) {
+            (Some(format), _) => Some(format),
+            (None, InputSource::Stdin) => None,
+            (None, InputSource::File(path)) if ArchiveKind::from_path(path).is_some() => {
+                return None
+            }
+            (None, InputSource::File(path)) => format_from_path(path),
+        };
+        let mut options = match format {
+            Some(InputFormat::Csv(options)) => options,
+            None => CsvOptions::CSV,
+            Some(_) => return None,
+        };
+        options.delimiter = self.delimiter.unwrap_or(options.delimiter);
+        options.quote = self.quote.unwrap_or(options.quote);
+        Some(InputFormat::Csv(options))
+    }
+}
+
+/// Returns the format named by a file's extension, looking past a
+/// compression extension as in `data.csv.gz`.
+fn format_from_path(path: &Path) -> Option<InputFormat> {
+    match Compression::from_path(path) {
+        Some((_, inner_path)) => InputFormat::from_path(&inner_path),
+        None => InputFormat::from_path(path),
+    }
+}
+
+/// Parses the character given to `--delimiter` or `--quote`, which must be a
+/// single ASCII character; `tab` and `\t` stand for a tab.
+fn parse_csv_char(option: &str, value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "{} needs a single character, not '{}'",
+            option, value
+        )),
+    }
+}
+
+/// Returns true if standard input is a pipe or a redirected file.
+///
+/// Used to read standard input when no files are given, as in
+/// `curl ... | slopjson`, without blocking on a terminal or `/dev/null`.
+#[cfg(unix)]
+pub fn stdin_is_redirected() -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    std::fs::metadata("/dev/stdin").is_ok_and(|metadata| {
+        let file_type = metadata.file_type();
+        file_type.is_fifo() || file_type.is_file()
+    })
+}
+
+/// Returns true if standard input is not a terminal.
+#[cfg(not(unix))]
+pub fn stdin_is_redirected() -> bool {
+    use std::io::IsTerminal;
+
+    !std::io::stdin().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CommandLine, String> {
+        CommandLine::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_files_and_stdin() {
+        let command_line = parse(&["a.json", "-", "b.yaml"]).unwrap();
+        assert_eq!(
+            command_line.inputs,
+            vec![
+                InputSource::File(PathBuf::from("a.json")),
+                InputSource::Stdin,
+                InputSource::File(PathBuf::from("b.yaml")),
+            ]
+        );
+        assert_eq!(command_line.format, None);
+    }
+
+    #[test]
+    fn test_parse_format() {
+        let command_line = parse(&["--format", "jsonl", "data"]).unwrap();
+        assert_eq!(command_line.format, Some(InputFormat::JsonL));
+        assert_eq!(
+            command_line.inputs,
+            vec![InputSource::File(PathBuf::from("data"))]
+        );
+
+        let command_line = parse(&["-", "--format=PARQUET"]).unwrap();
+        assert_eq!(command_line.format, Some(InputFormat::Parquet));
+
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["--format"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_options() {
+        let command_line = parse(&["--delimiter", ";", "export.txt"]).unwrap();
+        assert_eq!(
+            command_line.format_for(&command_line.inputs[0]),
+            Some(InputFormat::Csv(CsvOptions {
+                delimiter: b';',
+                quote: Some(b'"'),
+            }))
+        );
+
+        let command_line = parse(&["--quote=none", "--format", "tsv"]).unwrap();
+        assert_eq!(
+            command_line.format_for(&InputSource::Stdin),
+            Some(InputFormat::Csv(CsvOptions {
+                delimiter: b'\t',
+                quote: None,
+            }))
+        );
+
+        assert!(parse(&["--delimiter", "::"]).is_err());
+        assert!(parse(&["--format", "json", "--delimiter", "tab"]).is_err());
+        assert!(parse(&["--delimiter", ";", "a.json", "b.parquet"]).is_err());
+    }
+
+    #[test]
+    fn test_csv_options_apply_to_csv_inputs_only() {
+        let command_line = parse(&[
+            "--quote", "none", "a.json", "b.tsv", "c.csv.gz", "d.zip", "-",
+        ])
+        .unwrap();
+        let formats: Vec<Option<InputFormat>> = command_line
+            .inputs
+            .iter()
+            .map(|input| command_line.format_for(input))
+            .collect();
+        let csv = |delimiter| {
+            Some(InputFormat::Csv(CsvOptions {
+                delimiter,
+                quote: None,
+            }))
+        };
+        assert_eq!(formats, vec![None, csv(b'\t'), csv(b','), None, csv(b',')]);
+    }
+
+    #[test]
+    fn test_parse_after_double_dash() {
+        let command_line = parse(&["--", "--format", "-"]).unwrap();
+        assert_eq!(
+            command_line.inputs,
+            vec![
+                InputSource::File(PathBuf::from("--format")),
+                InputSource::File(PathBuf::from("-")),
+            ]
+        );
+    }
+}
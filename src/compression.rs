@@ -0,0 +1,150 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::json_reader::ParseError;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A compression format that is decompressed transparently when a file is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Recognizes a compressed file by its extension.
+    ///
+    /// Returns the compression and the path without the compression extension,
+    /// whose own extension names the format inside (`data.jsonl.gz` gives
+    /// `data.jsonl`).
+    pub fn from_path(path: &Path) -> Option<(Self, PathBuf)> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        let compression = match extension.as_str() {
+            "gz" | "gzip" => Compression::Gzip,
+            "zst" | "zstd" => Compression::Zstd,
+            "bz2" => Compression::Bzip2,
+            "xz" => Compression::Xz,
+            _ => return None,
+        };
+        Some((compression, path.with_extension("")))
+    }
+
+    /// Recognizes compressed content by its first bytes.
+    pub fn from_magic(head: &[u8]) -> Option<Self> {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if head.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if head.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the codec as shown on root nodes.
+    pub fn label(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+        }
+    }
+
+    /// Wraps `reader` in a streaming decoder for this codec.
+    ///
+    /// Concatenated streams, as written by `cat a.gz b.gz`, are decoded as one.
+    pub fn decoder<'a>(self, reader: impl Read + 'a) -> Result<Box<dyn Read + 'a>, ParseError> {
+        Ok(match self {
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Compression::Zstd => Box::new(
+                zstd::stream::read::Decoder::new(reader)
+                    .map_err(|e| ParseError::IoError(e.to_string()))?,
+            ),
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+            Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn compress(compression: Compression, data: &[u8]) -> Vec<u8> {
+        match compression {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => zstd::encode_all(data, 0).unwrap(),
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_magic() {
+        for compression in [
+            Compression::Gzip,
+            Compression::Zstd,
+            Compression::Bzip2,
+            Compression::Xz,
+        ] {
+            let compressed = compress(compression, b"{\"a\": 1}\n");
+            assert_eq!(Compression::from_magic(&compressed), Some(compression));
+
+            let mut decompressed = String::new();
+            compression
+                .decoder(compressed.as_slice())
+                .unwrap()
+                .read_to_string(&mut decompressed)
+                .unwrap();
+            assert_eq!(decompressed, "{\"a\": 1}\n");
+        }
+        assert_eq!(Compression::from_magic(b"{}"), None);
+    }
+
+    #[test]
+    fn test_from_path_strips_extension() {
+        assert_eq!(
+            Compression::from_path(Path::new("logs/data.jsonl.GZ")),
+            Some((Compression::Gzip, PathBuf::from("logs/data.jsonl")))
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("dump.zst")),
+            Some((Compression::Zstd, PathBuf::from("dump")))
+        );
+        assert_eq!(Compression::from_path(Path::new("data.json")), None);
+    }
+}
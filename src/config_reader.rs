@@ -0,0 +1,328 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading YAML, TOML and JSON5, formats mostly written by hand for
+//! configuration, into JSON values.
+
+use crate::json_reader::{ParseError, ParseResult, SyntaxError};
+use crate::json_value::{deserialize_value, float_to_json, TOML_DATETIME_KEY};
+use serde_json::Value;
+
+/// Parses YAML content and converts it to JSON format.
+///
+/// A stream of `---`-separated documents is read document by document. A
+/// trailing `---` with nothing but comments after it does not start another
+/// document; other empty documents, like explicit `null` or `~` ones, are kept
+/// as nulls.
+///
+/// # Arguments
+///
+/// * `content` - The YAML string content to parse
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Single(_))` if content is a single valid YAML document
+/// * `Ok(ParseResult::YamlStream(_))` if content has more than one document
+/// * `Err(ParseError::InvalidYaml(_))` if content is not valid YAML
+pub fn parse_yaml_content(content: &str) -> Result<ParseResult, ParseError> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = deserialize_value(document)
+            .map_err(|e| ParseError::InvalidYaml(Box::new(SyntaxError::from_yaml(&e, content))))?;
+        documents.push(value);
+    }
+    if documents.len() > 1
+        && documents.last().is_some_and(Value::is_null)
+        && ends_with_empty_document(content)
+    {
+        documents.pop();
+    }
+    if documents.len() > 1 {
+        Ok(ParseResult::YamlStream(documents))
+    } else {
+        Ok(ParseResult::Single(documents.pop().unwrap_or(Value::Null)))
+    }
+}
+
+/// Whether only blank lines and comments follow the last `---` marker of a
+/// YAML stream, so that the last document is empty rather than an explicit null.
+fn ends_with_empty_document(content: &str) -> bool {
+    let is_blank = |text: &str| {
+        let text = text.trim();
+        text.is_empty() || text.starts_with('#')
+    };
+    let mut empty = false;
+    for line in content.lines() {
+        let marker = line
+            .strip_prefix("---")
+            .filter(|rest| rest.is_empty() || rest.starts_with([' ', '\t']));
+        match marker {
+            Some(rest) => empty = is_blank(rest),
+            None if is_blank(line) || line.trim_end() == "..." => {}
+            None => empty = false,
+        }
+    }
+    empty
+}
+
+/// Parses TOML content and converts it to JSON format.
+///
+/// Datetimes become `TOML_DATETIME_KEY` objects, and NaN or infinite floats
+/// `FLOAT_KEY` objects.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Single(_))` if content is valid TOML
+/// * `Err(ParseError::InvalidToml(_))` if content is not valid TOML
+pub fn parse_toml_content(content: &str) -> Result<ParseResult, ParseError> {
+    match content.parse::<toml::Table>() {
+        Ok(table) => Ok(ParseResult::Single(toml_to_json(toml::Value::Table(table)))),
+        Err(e) => Err(ParseError::InvalidToml(Box::new(SyntaxError::from_toml(
+            &e, content,
+        )))),
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => float_to_json(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => {
+            serde_json::json!({ TOML_DATETIME_KEY: datetime.to_string() })
+        }
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Parses JSON5 content, which also covers JSONC.
+///
+/// Accepts `//` and `/* */` comments, trailing commas, single-quoted strings,
+/// unquoted keys, hexadecimal numbers and `NaN`/`Infinity`. As JSON has no
+/// way to write the last two, they become `FLOAT_KEY` objects.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Single(_))` if content is valid JSON5
+/// * `Err(ParseError::InvalidJson5(_))` if content is not valid JSON5
+pub fn parse_json5_content(content: &str) -> Result<ParseResult, ParseError> {
+    let mut deserializer = json5::Deserializer::from_str(content)
+        .map_err(|e| ParseError::InvalidJson5(Box::new(SyntaxError::from_json5(&e, content))))?;
+    match deserialize_value(&mut deserializer) {
+        Ok(value) => Ok(ParseResult::Single(value)),
+        Err(e) => Err(ParseError::InvalidJson5(Box::new(SyntaxError::from_json5(
+            &e, content,
+        )))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_syntax_error_location() {
+        let error = match parse_yaml_content("key: value\nlist: [1, 2\nother: 3\n") {
+            Err(ParseError::InvalidYaml(error)) => error,
+            other => panic!("Expected InvalidYaml error, got {:?}", other),
+        };
+        assert!(error.location.is_some());
+        assert!(error.source_preview().contains("> "));
+    }
+
+    #[test]
+    fn test_parse_yaml_object() {
+        let content = r#"
+name: test
+value: 42
+nested:
+  key: value
+"#;
+        let result = parse_yaml_content(content).unwrap();
+
+        match result {
+            ParseResult::Single(value) => {
+                assert_eq!(value["name"], "test");
+                assert_eq!(value["value"], 42);
+                assert_eq!(value["nested"]["key"], "value");
+            }
+            _ => panic!("Expected Single result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_yaml_stream() {
+        let content = "---\nkind: Service\n---\n# only a comment\n---\nkind: Deployment\n---\n";
+        match parse_yaml_content(content).unwrap() {
+            ParseResult::YamlStream(documents) => {
+                assert_eq!(documents.len(), 3);
+                assert_eq!(documents[0]["kind"], "Service");
+                assert_eq!(documents[1], Value::Null);
+                assert_eq!(documents[2]["kind"], "Deployment");
+            }
+            other => panic!("Expected YamlStream result, got {:?}", other),
+        }
+
+        // Explicit null documents are kept, even at the end
+        match parse_yaml_content("a: 1\n---\n~\n---\nnull\n").unwrap() {
+            ParseResult::YamlStream(documents) => {
+                assert_eq!(
+                    documents,
+                    vec![serde_json::json!({"a": 1}), Value::Null, Value::Null]
+                );
+            }
+            other => panic!("Expected YamlStream result, got {:?}", other),
+        }
+        match parse_yaml_content("a: 1\n--- # end\n").unwrap() {
+            ParseResult::Single(value) => assert_eq!(value, serde_json::json!({"a": 1})),
+            other => panic!("Expected Single result, got {:?}", other),
+        }
+
+        // A lone document marker still gives a single document
+        assert!(matches!(
+            parse_yaml_content("---\nname: test\n").unwrap(),
+            ParseResult::Single(_)
+        ));
+
+        let error = match parse_yaml_content("a: 1\n---\nb: [1, 2\n") {
+            Err(ParseError::InvalidYaml(error)) => error,
+            other => panic!("Expected InvalidYaml error, got {:?}", other),
+        };
+        assert!(error.location.unwrap().line >= 3);
+    }
+
+    #[test]
+    fn test_parse_yaml_array() {
+        let content = r#"
+- name: first
+  value: 1
+- name: second
+  value: 2
+"#;
+        let result = parse_yaml_content(content).unwrap();
+
+        match result {
+            ParseResult::Single(value) => {
+                assert!(value.is_array());
+                let arr = value.as_array().unwrap();
+                assert_eq!(arr.len(), 2);
+                assert_eq!(arr[0]["name"], "first");
+                assert_eq!(arr[1]["value"], 2);
+            }
+            _ => panic!("Expected Single result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_yaml() {
+        let content = r#"
+name: test
+  invalid: indentation
+"#;
+        let result = parse_yaml_content(content);
+
+        assert!(result.is_err());
+        match result {
+            Err(ParseError::InvalidYaml(_)) => {}
+            _ => panic!("Expected InvalidYaml error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let content = r#"
+[package]
+name = "slopjson"
+version = "0.1.0"
+released = 1979-05-27T07:32:00Z
+birthday = 1979-05-27
+ratio = 0.5
+
+[[bin]]
+name = "slopjson"
+"#;
+        let result = parse_toml_content(content).unwrap();
+
+        match result {
+            ParseResult::Single(value) => {
+                assert_eq!(value["package"]["name"], "slopjson");
+                assert_eq!(value["package"]["ratio"], 0.5);
+                assert_eq!(
+                    value["package"]["released"],
+                    serde_json::json!({ "$datetime": "1979-05-27T07:32:00Z" })
+                );
+                assert_eq!(
+                    value["package"]["birthday"][TOML_DATETIME_KEY],
+                    "1979-05-27"
+                );
+                assert_eq!(value["bin"][0]["name"], "slopjson");
+            }
+            _ => panic!("Expected Single result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_toml() {
+        let content = "[package]\nname = \"slopjson\"\nversion = \n";
+        match parse_toml_content(content) {
+            Err(ParseError::InvalidToml(error)) => {
+                assert_eq!(error.location.map(|l| l.line), Some(3));
+            }
+            other => panic!("Expected InvalidToml error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_json5() {
+        let content = r#"{
+    // Editor settings
+    name: 'test',
+    "mask": 0xFF,
+    "ratio": Infinity,
+    /* trailing commas */
+    "list": [1, 2,],
+}"#;
+        let result = parse_json5_content(content).unwrap();
+
+        match result {
+            ParseResult::Single(value) => {
+                assert_eq!(value["name"], "test");
+                assert_eq!(value["mask"], 255);
+                assert_eq!(value["ratio"], serde_json::json!({ "$float": "Infinity" }));
+                assert_eq!(value["list"], serde_json::json!([1, 2]));
+            }
+            _ => panic!("Expected Single result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_json5() {
+        let content = "{\n  // comment\n  \"a\": 1\n  \"b\": 2\n}";
+        match parse_json5_content(content) {
+            Err(ParseError::InvalidJson5(error)) => {
+                // The missing comma is reported where the value ends
+                assert_eq!(error.location.map(|l| l.line), Some(3));
+            }
+            other => panic!("Expected InvalidJson5 error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Guessing the format of content whose file extension doesn't name one,
+//! from its magic bytes or its first line of text.
+
+use crate::arrow_file::ARROW_FILE_MAGIC;
+use crate::avro_file::AVRO_MAGIC;
+use crate::compression::Compression;
+use crate::encoding::{decode_text, detect_utf16};
+use crate::json_reader::ParseError;
+use crate::jsonl_reader::RECORD_SEPARATOR;
+use std::path::Path;
+
+/// Number of bytes from the start of a file looked at to guess its format.
+pub const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Number of bytes from the start of a file looked at to spot UTF-16 text.
+const UTF16_SNIFF_BYTES: usize = 128;
+
+/// Marks the start and end of a Parquet file.
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+/// What some content looks like, for choosing a parser when the file extension
+/// doesn't name a format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    /// Starts and ends with the Parquet magic bytes
+    Parquet,
+    /// Starts with the Arrow IPC file magic bytes
+    Arrow,
+    /// Starts with the Avro container file magic bytes
+    Avro,
+    /// Starts with the magic bytes of a compression format
+    Compressed(Compression),
+    /// Text starting with `{` or `[`
+    Json,
+    /// Text starting with a YAML document marker (`---` or `%YAML`)
+    Yaml,
+    /// Binary content in no known format
+    Binary,
+    /// Any other text, which may be JSON or YAML
+    Text,
+}
+
+/// Guesses what some content is from its first bytes and its last bytes.
+///
+/// `head` should hold the first few kilobytes; `tail` only needs the last few
+/// bytes, and may be the whole content.
+pub fn sniff_content(head: &[u8], tail: &[u8]) -> ContentKind {
+    if head.starts_with(PARQUET_MAGIC) && tail.ends_with(PARQUET_MAGIC) {
+        return ContentKind::Parquet;
+    }
+    if head.starts_with(ARROW_FILE_MAGIC) {
+        return ContentKind::Arrow;
+    }
+    if head.starts_with(AVRO_MAGIC) {
+        return ContentKind::Avro;
+    }
+    if let Some(compression) = Compression::from_magic(head) {
+        return ContentKind::Compressed(compression);
+    }
+
+    let text = if detect_utf16(head).is_some() {
+        // UTF-16 text is half NUL bytes when it is mostly ASCII
+        decode_text(head.to_vec()).text
+    } else {
+        // Text rarely has NUL bytes; binary formats usually have plenty
+        let nul_bytes = head.iter().filter(|byte| **byte == 0).count();
+        if nul_bytes > 0 && nul_bytes * 100 >= head.len() {
+            return ContentKind::Binary;
+        }
+        String::from_utf8_lossy(head).into_owned()
+    };
+    let text = text.trim_start_matches(['\u{feff}', RECORD_SEPARATOR]);
+    // Skip blank lines and YAML comments to the first line with content
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or("");
+    if first_line.starts_with('{') || first_line.starts_with('[') {
+        ContentKind::Json
+    } else if first_line.starts_with("---") || first_line.starts_with("%YAML") {
+        ContentKind::Yaml
+    } else {
+        ContentKind::Text
+    }
+}
+
+/// Whether a file starts with UTF-16 text, see `detect_utf16`.
+pub fn is_utf16_file(path: &Path) -> Result<bool, ParseError> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+    let mut head = Vec::with_capacity(UTF16_SNIFF_BYTES);
+    file.take(UTF16_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| ParseError::IoError(e.to_string()))?;
+    Ok(detect_utf16(&head).is_some())
+}
+
+/// Reads the start and end of a regular file and guesses what it holds.
+pub fn sniff_file(path: &Path) -> Result<ContentKind, ParseError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let io_error = |e: std::io::Error| ParseError::IoError(e.to_string());
+    let mut file = std::fs::File::open(path).map_err(io_error)?;
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    file.by_ref()
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .map_err(io_error)?;
+
+    let mut tail = [0; PARQUET_MAGIC.len()];
+    let tail: &[u8] = if head.len() < SNIFF_BYTES {
+        &head
+    } else {
+        file.seek(SeekFrom::End(-(tail.len() as i64)))
+            .map_err(io_error)?;
+        file.read_exact(&mut tail).map_err(io_error)?;
+        &tail
+    };
+    Ok(sniff_content(&head, tail))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_content() {
+        assert_eq!(
+            sniff_content(b"PAR1....PAR1", b"PAR1....PAR1"),
+            ContentKind::Parquet
+        );
+        assert_eq!(
+            sniff_content(b"PAR1 but text", b"PAR1 but text"),
+            ContentKind::Text
+        );
+        assert_eq!(
+            sniff_content(&[0x1f, 0x8b, 8, 0], &[]),
+            ContentKind::Compressed(Compression::Gzip)
+        );
+        assert_eq!(
+            sniff_content(&[0x28, 0xb5, 0x2f, 0xfd, 0], &[]),
+            ContentKind::Compressed(Compression::Zstd)
+        );
+        assert_eq!(
+            sniff_content(b"\x00\x01\x02binary", &[]),
+            ContentKind::Binary
+        );
+        assert_eq!(
+            sniff_content(b"ARROW1\x00\x00\xff\xff", &[]),
+            ContentKind::Arrow
+        );
+        assert_eq!(sniff_content(b"Obj\x01\x04", &[]), ContentKind::Avro);
+        assert_eq!(sniff_content(b"\n  [1, 2]", &[]), ContentKind::Json);
+        assert_eq!(sniff_content(b"\xef\xbb\xbf{}", &[]), ContentKind::Json);
+        assert_eq!(
+            sniff_content(b"# comment\n---\na: 1\n", &[]),
+            ContentKind::Yaml
+        );
+        assert_eq!(sniff_content(b"a: 1\n", &[]), ContentKind::Text);
+    }
+}
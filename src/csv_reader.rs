@@ -0,0 +1,183 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::json_reader::{ParseControl, ParseError, ParseProgress, ParseResult, SyntaxError};
+use serde_json::{Map, Value};
+
+/// Number of records converted between progress reports.
+const RECORDS_PER_PROGRESS_REPORT: usize = 1000;
+
+/// How the fields of a CSV file are separated and quoted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Byte between fields
+    pub delimiter: u8,
+    /// Byte around fields that contain the delimiter or a line break, or
+    /// `None` if quotes are ordinary characters
+    pub quote: Option<u8>,
+}
+
+impl CsvOptions {
+    /// Comma-separated values, as written by spreadsheets.
+    pub const CSV: CsvOptions = CsvOptions {
+        delimiter: b',',
+        quote: Some(b'"'),
+    };
+
+    /// Tab-separated values.
+    pub const TSV: CsvOptions = CsvOptions {
+        delimiter: b'\t',
+        quote: Some(b'"'),
+    };
+}
+
+/// Parses CSV content into an array with one object per record, keyed by the
+/// header.
+///
+/// Fields that are JSON numbers or booleans are converted, and empty fields
+/// become null; everything else stays a string, so `007` or `1e` are kept as
+/// written. Headers that are empty are named `column N`.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Single(_))` with an array of row objects
+/// * `Err(ParseError::InvalidCsv(_))` naming the record that could not be read,
+///   counting the first record after the header as record 1
+pub fn parse_csv_content(
+    content: &str,
+    options: CsvOptions,
+    control: &ParseControl,
+) -> Result<ParseResult, ParseError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .quoting(options.quote.is_some())
+        .quote(options.quote.unwrap_or(b'"'))
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| ParseError::InvalidCsv(Box::new(SyntaxError::from_csv(&e, content))))?
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            if name.is_empty() {
+                format!("column {}", idx + 1)
+            } else {
+                name.to_string()
+            }
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record
+            .map_err(|e| ParseError::InvalidCsv(Box::new(SyntaxError::from_csv(&e, content))))?;
+        let row: Map<String, Value> = headers
+            .iter()
+            .cloned()
+            .zip(record.iter().map(infer_value))
+            .collect();
+        rows.push(Value::Object(row));
+
+        if rows.len().is_multiple_of(RECORDS_PER_PROGRESS_REPORT) {
+            control.check_cancelled()?;
+            control.report(ParseProgress {
+                bytes_processed: record.position().map_or(0, |position| position.byte()),
+                total_bytes: Some(content.len() as u64),
+                rows_processed: rows.len(),
+            });
+        }
+    }
+
+    Ok(ParseResult::Single(Value::Array(rows)))
+}
+
+/// Converts a field to the JSON value it spells, if any.
+fn infer_value(field: &str) -> Value {
+    match field {
+        "" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        // serde_json skips surrounding whitespace, which would lose it here
+        _ if field.trim() != field => Value::String(field.to_string()),
+        _ => serde_json::from_str::<serde_json::Number>(field)
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::String(field.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str, options: CsvOptions) -> Result<Value, ParseError> {
+        match parse_csv_content(content, options, &ParseControl::default())? {
+            ParseResult::Single(value) => Ok(value),
+            other => panic!("Expected Single result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rows_keyed_by_header() {
+        let content = "id,name,score,active,zip,note\n\
+                       1,Ada,9.5,true,02139,\n\
+                       2,\"Lovelace, Ada\",-3,FALSE,10001,\" padded \"\n";
+        let rows = parse(content, CsvOptions::CSV).unwrap();
+
+        assert_eq!(
+            rows,
+            serde_json::json!([
+                {"id": 1, "name": "Ada", "score": 9.5, "active": true, "zip": "02139", "note": null},
+                {"id": 2, "name": "Lovelace, Ada", "score": -3, "active": false, "zip": 10001, "note": " padded "},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_delimiter_and_quote() {
+        // Single quotes are ordinary characters unless chosen as the quote
+        let error = parse("a\t\tc\n1\t'x\ty'\t3\n", CsvOptions::TSV).unwrap_err();
+        assert!(matches!(error, ParseError::InvalidCsv(_)));
+
+        let options = CsvOptions {
+            delimiter: b'\t',
+            quote: Some(b'\''),
+        };
+        let rows = parse("a\t\tc\n1\t'x\ty'\t3\n", options).unwrap();
+        assert_eq!(
+            rows,
+            serde_json::json!([{"a": 1, "column 2": "x\ty", "c": 3}])
+        );
+
+        let options = CsvOptions {
+            delimiter: b';',
+            quote: None,
+        };
+        let rows = parse("a;b\n\"1;2\n", options).unwrap();
+        assert_eq!(rows, serde_json::json!([{"a": "\"1", "b": 2}]));
+    }
+
+    #[test]
+    fn test_error_names_record() {
+        let content = "a,b\n1,2\n3,4\n5\n";
+        match parse(content, CsvOptions::CSV) {
+            Err(ParseError::InvalidCsv(error)) => {
+                assert!(error.message.starts_with("record 3:"), "{}", error.message);
+                assert_eq!(error.location.map(|l| l.line), Some(4));
+            }
+            other => panic!("Expected InvalidCsv error, got {:?}", other),
+        }
+    }
+}
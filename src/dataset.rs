@@ -0,0 +1,323 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::compression::Compression;
+use crate::json_reader::{InputFormat, ParseControl, ParseError};
+use crate::json_value::ValueOrigin;
+use crate::jsonl_index::JsonLIndex;
+use crate::parquet_file::ParquetFile;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Where a shard's rows are read from.
+#[derive(Debug)]
+enum ShardSource {
+    Parquet(ParquetFile),
+    JsonL(JsonLIndex),
+}
+
+/// One file of a dataset.
+#[derive(Debug)]
+pub struct Shard {
+    /// Path of the file relative to the dataset directory
+    pub name: String,
+    source: ShardSource,
+}
+
+impl Shard {
+    pub fn num_rows(&self) -> usize {
+        match &self.source {
+            ShardSource::Parquet(parquet) => parquet.num_rows(),
+            ShardSource::JsonL(index) => index.line_count(),
+        }
+    }
+
+    /// Reads `len` rows starting at `start`, which must be within the shard.
+    ///
+    /// A JSONL line that is not valid JSON is returned as an object with an
+    /// `error` member, as in the other JSONL views.
+    fn read_rows(&self, start: usize, len: usize) -> Result<Vec<Value>, ParseError> {
+        match &self.source {
+            ShardSource::Parquet(parquet) => parquet.read_rows(start, len),
+            ShardSource::JsonL(index) => Ok((start..start + len)
+                .map(|line| {
+                    index
+                        .parse_line(line)
+                        .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }))
+                })
+                .collect()),
+        }
+    }
+}
+
+/// A directory of Parquet or JSONL shards, read as one logical array of rows.
+///
+/// This is the layout of Hugging Face style datasets, such as
+/// `train-00000-of-00042.parquet` or `part-00000.jsonl`. Shards are found in
+/// the directory and its subdirectories and ordered by path. Opening the
+/// dataset only reads Parquet footers and indexes JSONL line offsets; rows are
+/// decoded when they are read. Compressed JSONL shards, such as
+/// `part-00000.jsonl.gz`, are decompressed into memory when the dataset is opened.
+#[derive(Debug)]
+pub struct Dataset {
+    shards: Vec<Shard>,
+    /// Row number of the first row of each shard
+    shard_starts: Vec<usize>,
+    num_rows: usize,
+}
+
+impl Dataset {
+    /// Finds and opens the shards of a dataset directory.
+    pub fn open(dir: &Path, control: &ParseControl) -> Result<Self, ParseError> {
+        let mut paths = Vec::new();
+        find_shards(dir, &mut paths)?;
+        paths.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if paths.is_empty() {
+            return Err(ParseError::IoError(format!(
+                "no Parquet or JSONL files in {}",
+                dir.display()
+            )));
+        }
+
+        let mut shards = Vec::with_capacity(paths.len());
+        let mut shard_starts = Vec::with_capacity(paths.len());
+        let mut num_rows = 0;
+        for (path, format) in paths {
+            control.check_cancelled()?;
+            let source = match format {
+                InputFormat::Parquet => ShardSource::Parquet(ParquetFile::open(&path)?),
+                _ => ShardSource::JsonL(match Compression::from_path(&path) {
+                    Some((compression, _)) => {
+                        JsonLIndex::build_compressed(&path, compression, control)?
+                    }
+                    None => JsonLIndex::build(&path, control)?,
+                }),
+            };
+            let shard = Shard {
+                name: path
+                    .strip_prefix(dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .into_owned(),
+                source,
+            };
+            shard_starts.push(num_rows);
+            num_rows += shard.num_rows();
+            shards.push(shard);
+        }
+
+        Ok(Self {
+            shards,
+            shard_starts,
+            num_rows,
+        })
+    }
+
+    /// Total number of rows across all shards.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn shards(&self) -> &[Shard] {
+        &self.shards
+    }
+
+    /// Describes the shards, as shown under the "Shards" node.
+    pub fn shards_json(&self) -> Value {
+        Value::Array(
+            self.shards
+                .iter()
+                .zip(&self.shard_starts)
+                .map(|(shard, first_row)| {
+                    serde_json::json!({
+                        "name": shard.name,
+                        "format": match shard.source {
+                            ShardSource::Parquet(_) => "parquet",
+                            ShardSource::JsonL(_) => "jsonl",
+                        },
+                        "rows": shard.num_rows(),
+                        "first_row": first_row + 1,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether `row` was read from a JSONL shard or converted from Parquet.
+    pub fn value_origin(&self, row: usize) -> ValueOrigin {
+        // The last shard starting at or before `row`, as in `read_rows`
+        let shard = self
+            .shard_starts
+            .partition_point(|shard_start| *shard_start <= row)
+            .saturating_sub(1);
+        match self.shards.get(shard).map(|shard| &shard.source) {
+            Some(ShardSource::JsonL(_)) => ValueOrigin::Json,
+            _ => ValueOrigin::Converted,
+        }
+    }
+
+    /// Reads up to `len` rows starting at row `start`, across shard boundaries.
+    pub fn read_rows(&self, start: usize, len: usize) -> Result<Vec<Value>, ParseError> {
+        let end = start.saturating_add(len).min(self.num_rows);
+        let mut rows = Vec::with_capacity(end.saturating_sub(start));
+        // The last shard starting at or before `start`; empty shards are skipped below
+        let first_shard = self
+            .shard_starts
+            .partition_point(|shard_start| *shard_start <= start)
+            .saturating_sub(1);
+        for (shard, shard_start) in self.shards.iter().zip(&self.shard_starts).skip(first_shard) {
+            let row = start + rows.len();
+            if row >= end {
+                break;
+            }
+            let local_start = row - shard_start;
+            let local_len = (shard.num_rows().saturating_sub(local_start)).min(end - row);
+            if local_len > 0 {
+                rows.extend(shard.read_rows(local_start, local_len)?);
+            }
+        }
+        Ok(rows)
+    }
+}
+
+/// Collects the Parquet and JSONL files under `dir`, including compressed
+/// JSONL files, skipping hidden entries such as `.cache` or `.git`.
+fn find_shards(dir: &Path, paths: &mut Vec<(PathBuf, InputFormat)>) -> Result<(), ParseError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| ParseError::IoError(e.to_string()))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| ParseError::IoError(e.to_string()))?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            find_shards(&path, paths)?;
+        } else if let Some(format) = shard_format(&path) {
+            paths.push((path, format));
+        }
+    }
+    Ok(())
+}
+
+/// The format of a shard: Parquet, or JSONL, which may be compressed as in
+/// `part-00000.jsonl.zst`.
+fn shard_format(path: &Path) -> Option<InputFormat> {
+    match (InputFormat::from_path(path), Compression::from_path(path)) {
+        (Some(format @ (InputFormat::Parquet | InputFormat::JsonL)), _) => Some(format),
+        (_, Some((_, inner_path))) => {
+            InputFormat::from_path(&inner_path).filter(|format| *format == InputFormat::JsonL)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_parquet(path: &Path, ids: std::ops::Range<i64>) {
+        use arrow::array::Int64Array;
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::arrow_writer::ArrowWriter;
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from_iter_values(ids))],
+        )
+        .unwrap();
+        let mut writer =
+            ArrowWriter::try_new(std::fs::File::create(path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_rows_across_shards() {
+        let dir = std::env::temp_dir().join(format!("slopjson-dataset-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("data")).unwrap();
+        std::fs::create_dir_all(dir.join(".cache")).unwrap();
+        write_parquet(&dir.join("data/train-00000-of-00002.parquet"), 0..3);
+        write_parquet(&dir.join("data/train-00001-of-00002.parquet"), 3..5);
+        std::fs::write(
+            dir.join("data/validation.jsonl"),
+            "{\"id\": 5}\n{\"id\": 6}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("README.md"), "# dataset").unwrap();
+        write_parquet(&dir.join(".cache/stale.parquet"), 0..100);
+
+        let dataset = Dataset::open(&dir, &ParseControl::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let dataset = dataset.unwrap();
+
+        assert_eq!(dataset.num_rows(), 7);
+        let names: Vec<&str> = dataset.shards().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "data/train-00000-of-00002.parquet",
+                "data/train-00001-of-00002.parquet",
+                "data/validation.jsonl"
+            ]
+        );
+        assert_eq!(dataset.shards_json()[2]["first_row"], 6);
+
+        let ids = |rows: Vec<Value>| -> Vec<i64> {
+            rows.iter().map(|row| row["id"].as_i64().unwrap()).collect()
+        };
+        assert_eq!(ids(dataset.read_rows(2, 4).unwrap()), [2, 3, 4, 5]);
+        assert_eq!(
+            ids(dataset.read_rows(0, 100).unwrap()),
+            (0..7).collect::<Vec<_>>()
+        );
+        assert!(dataset.read_rows(7, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compressed_jsonl_shards() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("slopjson-dataset-gz-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"{\"id\": 0}\n\n{\"id\": 1}\n").unwrap();
+        std::fs::write(dir.join("part-0.jsonl.gz"), encoder.finish().unwrap()).unwrap();
+        std::fs::write(dir.join("part-1.jsonl"), "{\"id\": 2}\n").unwrap();
+        std::fs::write(dir.join("notes.txt.gz"), b"").unwrap();
+
+        let dataset = Dataset::open(&dir, &ParseControl::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let dataset = dataset.unwrap();
+
+        let names: Vec<&str> = dataset.shards().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["part-0.jsonl.gz", "part-1.jsonl"]);
+        let rows = dataset.read_rows(0, 3).unwrap();
+        let ids: Vec<i64> = rows.iter().map(|row| row["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_empty_directory() {
+        let dir =
+            std::env::temp_dir().join(format!("slopjson-dataset-empty-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let result = Dataset::open(&dir, &ParseControl::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result, Err(ParseError::IoError(_))));
+    }
+}
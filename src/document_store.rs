@@ -13,42 +13,1256 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::value_lookup::{lookup_value, lookup_value_in_jsonl};
+use crate::archive::Archive;
+use crate::arrow_file::ArrowFile;
+use crate::avro_file::AvroFile;
+use crate::dataset::Dataset;
+use crate::json_reader::{
+    parse_member, JsonSeparator, LineError, ParseControl, ParseError, ParseResult,
+};
+use crate::json_value::ValueOrigin;
+use crate::jsonl_index::JsonLIndex;
+use crate::notebook::Notebook;
+use crate::parquet_file::ParquetFile;
+use crate::value_lookup::{
+    lookup_in_value, lookup_value, lookup_value_in_jsonl, lookup_value_in_lines, parse_json_path,
+    PathSegment,
+};
 use serde_json::Value;
+use std::cell::{Cell, OnceCell};
+use std::sync::Arc;
 
+/// A child of a tree node, as it should appear in the tree view.
 #[derive(Debug)]
+pub struct ChildNode<'a> {
+    /// Display name for the row (key, `[index]` or `Line N`)
+    pub name: String,
+    /// Path segment appended to the parent's paths to address this child
+    pub segment: PathSegment,
+    /// The child's value, or `None` if it has not been parsed yet
+    pub value: Option<&'a Value>,
+    /// Preview to show instead of the one derived from the value
+    pub preview: Option<String>,
+}
+
+/// How the members of an object are ordered in the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyOrder {
+    /// The order the keys appear in the source file
+    #[default]
+    File,
+    /// Sorted by key
+    Sorted,
+}
+
+/// Lists the direct children of a JSON value (object members or array elements).
+fn value_child_nodes(value: &Value) -> Vec<ChildNode<'_>> {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, val)| ChildNode {
+                name: key.clone(),
+                segment: PathSegment::Key(key.clone()),
+                value: Some(val),
+                preview: None,
+            })
+            .collect(),
+        Value::Array(arr) => arr
+            .iter()
+            .enumerate()
+            .map(|(idx, val)| ChildNode {
+                name: format!("[{}]", idx),
+                segment: PathSegment::Index(idx),
+                value: Some(val),
+                preview: None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A parsed JSONL file, or the documents of a multi-document YAML stream.
+///
+/// Lines that failed to parse stay in their place among the lines, as objects
+/// describing the error, and are listed as error nodes in the tree. Lines and
+/// errors are both named by the line of the content they start on.
+#[derive(Debug, Clone)]
 pub struct JsonLDocument {
-    values: Vec<Value>,
+    values: Arc<Vec<Value>>,
+    /// 1-based line of the content that each of `values` starts on
+    lines: Arc<Vec<usize>>,
+    /// Indices into `values` of the bad lines, in ascending order
+    error_indices: Vec<usize>,
     summary: Value,
+    /// True if the values are YAML documents, shown as `Doc N` rather than `Line N`
+    yaml_documents: bool,
+    /// How the values were separated; those not one per line are shown as `Value N`
+    separator: JsonSeparator,
 }
 
 impl JsonLDocument {
-    pub fn new(values: Vec<Value>) -> Self {
-        let summary = serde_json::json!({ "lines": values.len() });
-        Self { values, summary }
+    /// Creates a document from the valid lines, the line of the content each
+    /// starts on, and the lines that failed to parse.
+    pub fn new(values: Vec<Value>, value_lines: Vec<usize>, errors: Vec<LineError>) -> Self {
+        let summary = serde_json::json!({
+            "lines": values.len(),
+            "errors": errors.len(),
+        });
+
+        let mut entries = Vec::with_capacity(values.len() + errors.len());
+        let mut lines = Vec::with_capacity(values.len() + errors.len());
+        let mut error_indices = Vec::with_capacity(errors.len());
+        let mut errors = errors.into_iter().peekable();
+        for (idx, (value, line)) in values.into_iter().zip(value_lines).enumerate() {
+            while let Some(error) = errors.next_if(|error| error.index <= idx) {
+                error_indices.push(entries.len());
+                entries.push(line_error_to_json(&error));
+                lines.push(error.line);
+            }
+            entries.push(value);
+            lines.push(line);
+        }
+        for error in errors {
+            error_indices.push(entries.len());
+            entries.push(line_error_to_json(&error));
+            lines.push(error.line);
+        }
+
+        Self {
+            values: Arc::new(entries),
+            lines: Arc::new(lines),
+            error_indices,
+            summary,
+            yaml_documents: false,
+            separator: JsonSeparator::Newline,
+        }
+    }
+
+    /// Records how the values were separated in the content, see `JsonSeparator`.
+    pub fn with_separator(mut self, separator: JsonSeparator) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Creates a document from the documents of a multi-document YAML stream.
+    pub fn yaml_stream(documents: Vec<Value>) -> Self {
+        Self {
+            summary: serde_json::json!({ "documents": documents.len() }),
+            values: Arc::new(documents),
+            lines: Arc::new(Vec::new()),
+            error_indices: Vec::new(),
+            yaml_documents: true,
+            separator: JsonSeparator::Newline,
+        }
+    }
+
+    /// What the values are called in the root's preview, such as "3 objects".
+    pub fn item_noun(&self) -> &'static str {
+        if self.yaml_documents {
+            "documents"
+        } else {
+            "objects"
+        }
+    }
+
+    /// Number of lines that parsed successfully.
+    pub fn line_count(&self) -> usize {
+        self.values.len() - self.error_indices.len()
+    }
+
+    /// Number of lines that failed to parse.
+    pub fn error_count(&self) -> usize {
+        self.error_indices.len()
+    }
+
+    fn child_nodes(&self) -> Vec<ChildNode<'_>> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                let (name, preview) = if self.yaml_documents {
+                    (format!("Doc {}", idx + 1), yaml_document_preview(value))
+                } else if self.error_indices.binary_search(&idx).is_ok() {
+                    (format!("Error at line {}", self.lines[idx]), None)
+                } else if self.separator == JsonSeparator::Newline {
+                    (format!("Line {}", self.lines[idx]), None)
+                } else {
+                    (format!("Value {}", idx + 1), None)
+                };
+                ChildNode {
+                    name,
+                    segment: PathSegment::Index(idx),
+                    value: Some(value),
+                    preview,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Names a YAML document by its `kind` and `metadata.name`, as in "Deployment/web".
+///
+/// Kubernetes-style manifests are easier to tell apart this way than by their
+/// member counts. Returns `None` if the document has neither.
+fn yaml_document_preview(value: &Value) -> Option<String> {
+    let kind = value.get("kind").and_then(Value::as_str);
+    let name = value
+        .get("metadata")
+        .and_then(|metadata| metadata.get("name"))
+        .and_then(Value::as_str);
+    match (kind, name) {
+        (Some(kind), Some(name)) => Some(format!("{}/{}", kind, name)),
+        (Some(text), None) | (None, Some(text)) => Some(text.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// Describes a bad JSONL line as shown in the tree.
+fn line_error_to_json(error: &LineError) -> Value {
+    serde_json::json!({
+        "error": error.message,
+        "line": error.line,
+        "offset": error.offset,
+    })
+}
+
+/// Most pages of lines or rows, or archive members, that a document keeps
+/// decoded. `StoredDocument::trim_cache` releases the least recently used ones
+/// beyond this, whether or not their rows are still in the tree.
+pub const MAX_CACHED_PAGES: usize = 8;
+
+/// Records when each page (or archive member) of a document was last used, to
+/// find the least recently used ones when trimming its cache.
+#[derive(Debug)]
+struct RecentUses {
+    clock: Cell<u64>,
+    last_used: Box<[Cell<u64>]>,
+}
+
+impl RecentUses {
+    fn new(pages: usize) -> Self {
+        Self {
+            clock: Cell::new(0),
+            last_used: (0..pages).map(|_| Cell::new(0)).collect(),
+        }
+    }
+
+    fn touch(&self, page: usize) {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+        if let Some(last_used) = self.last_used.get(page) {
+            last_used.set(now);
+        }
+    }
+
+    /// Of the pages that are decoded, returns those to release so that only
+    /// the `MAX_CACHED_PAGES` most recently used remain.
+    fn pages_to_release(&self, decoded: impl Iterator<Item = usize>) -> Vec<usize> {
+        let mut decoded: Vec<usize> = decoded.collect();
+        if decoded.len() <= MAX_CACHED_PAGES {
+            return Vec::new();
+        }
+        decoded.sort_by_key(|page| std::cmp::Reverse(self.last_used[*page].get()));
+        decoded.split_off(MAX_CACHED_PAGES)
+    }
+}
+
+/// Returns the indices of the cells that hold a value.
+fn filled_cells<T>(cells: &[OnceCell<T>]) -> impl Iterator<Item = usize> + '_ {
+    cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.get().is_some())
+        .map(|(idx, _)| idx)
+}
+
+/// A JSONL file whose lines are parsed the first time they are looked up.
+///
+/// Only the line offsets are read up front (see `JsonLIndex`). As in a
+/// `PagedDocument`, a file with more lines than fit in a page has one root
+/// child per page, named like `Lines 1-1000`, and a line's path is
+/// `$[page][line within page]`; otherwise the lines are the root's children.
+/// Blank lines are not listed, but lines and pages are named by their line
+/// numbers in the file.
+/// Parsed lines are cached per page, in a cache that is allocated on first
+/// use, so an untouched file costs a few bytes per line, and at most
+/// `MAX_CACHED_PAGES` pages are kept once the cache is trimmed. A line that is
+/// not valid JSON is shown as an object with an `error` member; as lines are
+/// only parsed when they are looked up, how many there are is not known.
+#[derive(Debug)]
+pub struct IndexedJsonLDocument {
+    index: Arc<JsonLIndex>,
+    lines_per_page: usize,
+    summary: Value,
+    page_summaries: Arc<[Value]>,
+    pages: Vec<OnceCell<Box<[OnceCell<Value>]>>>,
+    uses: RecentUses,
+}
+
+impl IndexedJsonLDocument {
+    pub fn new(index: JsonLIndex, lines_per_page: usize) -> Self {
+        let lines_per_page = lines_per_page.max(1);
+        let line_count = index.line_count();
+        let summary = serde_json::json!({
+            "lines": line_count,
+            "lines_per_page": lines_per_page,
+        });
+        let page_summaries = (0..line_count.div_ceil(lines_per_page))
+            .map(|page| {
+                let first_line = page * lines_per_page;
+                let end = (first_line + lines_per_page).min(line_count);
+                serde_json::json!({
+                    "first_line": index.source_line(first_line),
+                    "last_line": index.source_line(end - 1),
+                    "lines": end - first_line,
+                })
+            })
+            .collect::<Arc<[Value]>>();
+        Self {
+            index: Arc::new(index),
+            lines_per_page,
+            summary,
+            pages: page_summaries.iter().map(|_| OnceCell::new()).collect(),
+            uses: RecentUses::new(page_summaries.len()),
+            page_summaries,
+        }
+    }
+
+    fn shared_copy(&self) -> Self {
+        Self {
+            index: self.index.clone(),
+            lines_per_page: self.lines_per_page,
+            summary: self.summary.clone(),
+            page_summaries: self.page_summaries.clone(),
+            pages: self.pages.iter().map(|_| OnceCell::new()).collect(),
+            uses: RecentUses::new(self.pages.len()),
+        }
+    }
+
+    /// Number of non-blank lines, whether or not they are valid JSON.
+    pub fn line_count(&self) -> usize {
+        self.index.line_count()
+    }
+
+    /// Whether lines are grouped under page nodes (the file spans more than one page).
+    fn is_paged(&self) -> bool {
+        self.pages.len() > 1
+    }
+
+    /// Returns a line's value, parsing it on first use.
+    fn line(&self, line: usize) -> Option<&Value> {
+        if line >= self.index.line_count() {
+            return None;
+        }
+        self.uses.touch(line / self.lines_per_page);
+        let page = self
+            .pages
+            .get(line / self.lines_per_page)?
+            .get_or_init(|| (0..self.lines_per_page).map(|_| OnceCell::new()).collect());
+        Some(page[line % self.lines_per_page].get_or_init(|| {
+            self.index
+                .parse_line(line)
+                .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }))
+        }))
+    }
+
+    /// Returns a line's value only if it has already been parsed.
+    fn parsed_line(&self, line: usize) -> Option<&Value> {
+        self.pages.get(line / self.lines_per_page)?.get()?[line % self.lines_per_page].get()
+    }
+
+    /// Lists the lines of a page, named by their line number in the file.
+    fn line_nodes(&self, page: usize) -> Vec<ChildNode<'_>> {
+        let first_line = page * self.lines_per_page;
+        let end = (first_line + self.lines_per_page).min(self.index.line_count());
+        (first_line..end)
+            .map(|line| ChildNode {
+                name: format!("Line {}", self.index.source_line(line)),
+                segment: PathSegment::Index(line - first_line),
+                value: self.parsed_line(line),
+                preview: None,
+            })
+            .collect()
+    }
+
+    fn lookup_value(&self, path: &str) -> Option<&Value> {
+        if path == "$" {
+            return Some(&self.summary);
+        }
+        if !self.is_paged() {
+            return lookup_value_in_lines(|line| self.line(line), path);
+        }
+        let segments = parse_json_path(path)?;
+        match segments.as_slice() {
+            [PathSegment::Index(page)] => self.page_summaries.get(*page),
+            [PathSegment::Index(page), PathSegment::Index(line), rest @ ..]
+                if *line < self.lines_per_page =>
+            {
+                lookup_in_value(self.line(page * self.lines_per_page + line)?, rest)
+            }
+            _ => None,
+        }
+    }
+
+    fn child_nodes(&self, path: &str) -> Vec<ChildNode<'_>> {
+        let Some(segments) = parse_json_path(path) else {
+            return Vec::new();
+        };
+        match segments.as_slice() {
+            [] if self.is_paged() => self
+                .page_summaries
+                .iter()
+                .enumerate()
+                .map(|(page, summary)| ChildNode {
+                    name: format!("Lines {}-{}", summary["first_line"], summary["last_line"]),
+                    segment: PathSegment::Index(page),
+                    value: Some(summary),
+                    preview: None,
+                })
+                .collect(),
+            [] => self.line_nodes(0),
+            [PathSegment::Index(page)] if self.is_paged() => self.line_nodes(*page),
+            _ => self
+                .lookup_value(path)
+                .map(value_child_nodes)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Forgets parsed lines: one line, every line of a page, or every line for the root.
+    fn release_rows(&mut self, path: &str) {
+        let Some(segments) = parse_json_path(path) else {
+            return;
+        };
+        let line = match segments.as_slice() {
+            [] => {
+                self.pages.iter_mut().for_each(|page| {
+                    page.take();
+                });
+                return;
+            }
+            [PathSegment::Index(page)] if self.is_paged() => {
+                if let Some(page) = self.pages.get_mut(*page) {
+                    page.take();
+                }
+                return;
+            }
+            [PathSegment::Index(line)] if !self.is_paged() => *line,
+            [PathSegment::Index(page), PathSegment::Index(line)]
+                if self.is_paged() && *line < self.lines_per_page =>
+            {
+                page * self.lines_per_page + line
+            }
+            _ => return,
+        };
+        if let Some(page) = self
+            .pages
+            .get_mut(line / self.lines_per_page)
+            .and_then(|page| page.get_mut())
+        {
+            page[line % self.lines_per_page].take();
+        }
+    }
+
+    /// Releases the least recently used pages of parsed lines beyond `MAX_CACHED_PAGES`.
+    fn trim_cache(&mut self) {
+        for page in self.uses.pages_to_release(filled_cells(&self.pages)) {
+            self.pages[page].take();
+        }
+    }
+}
+
+/// Name of the synthetic node listing the schema and row group (or record
+/// batch, or block) metadata of a Parquet, Arrow or Avro file.
+pub const METADATA_KEY: &str = "Metadata";
+
+/// Name of the synthetic node listing a dataset's shards.
+pub const DATASET_SHARDS_KEY: &str = "Shards";
+
+/// Default number of rows decoded together when paging through a Parquet file.
+///
+/// A Parquet page is never larger than the file's largest row group, see
+/// `PagedDocument::new`.
+pub const DEFAULT_PARQUET_ROWS_PER_PAGE: usize = 1000;
+
+/// Rows that are decoded on request: a Parquet, Arrow or Avro file, or a
+/// dataset of shards.
+#[derive(Debug)]
+pub enum RowSource {
+    Parquet(ParquetFile),
+    Arrow(ArrowFile),
+    /// Boxed, as it is much larger than the other sources
+    Avro(Box<AvroFile>),
+    Dataset(Dataset),
+}
+
+impl RowSource {
+    pub fn num_rows(&self) -> usize {
+        match self {
+            RowSource::Parquet(parquet) => parquet.num_rows(),
+            RowSource::Arrow(arrow) => arrow.num_rows(),
+            RowSource::Avro(avro) => avro.num_rows(),
+            RowSource::Dataset(dataset) => dataset.num_rows(),
+        }
     }
 
-    pub fn values(&self) -> &[Value] {
-        &self.values
+    fn read_rows(&self, start: usize, len: usize) -> Result<Vec<Value>, ParseError> {
+        match self {
+            RowSource::Parquet(parquet) => parquet.read_rows(start, len),
+            RowSource::Arrow(arrow) => arrow.read_rows(start, len),
+            RowSource::Avro(avro) => avro.read_rows(start, len),
+            RowSource::Dataset(dataset) => dataset.read_rows(start, len),
+        }
+    }
+}
+
+/// Decodes rows `start..start + len` of a source into a page value: an array of
+/// rows, or an object describing the error if they cannot be decoded.
+fn read_page(source: &RowSource, start: usize, len: usize) -> Value {
+    match source.read_rows(start, len) {
+        Ok(rows) => Value::Array(rows),
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    }
+}
+
+/// A page of rows that is not decoded yet, to be decoded on another thread
+/// with `decode` and handed back with `StoredDocument::store_page`.
+#[derive(Debug)]
+pub struct PageDecoder {
+    source: Arc<RowSource>,
+    page: usize,
+    rows_per_page: usize,
+}
+
+impl PageDecoder {
+    /// Decodes the page's rows, unless the control is already cancelled.
+    pub fn decode(&self, control: &ParseControl) -> Result<DecodedPage, ParseError> {
+        control.check_cancelled()?;
+        Ok(DecodedPage {
+            page: self.page,
+            rows: read_page(
+                &self.source,
+                self.page * self.rows_per_page,
+                self.rows_per_page,
+            ),
+        })
+    }
+}
+
+/// The rows of a page decoded by a `PageDecoder`.
+#[derive(Debug)]
+pub struct DecodedPage {
+    page: usize,
+    rows: Value,
+}
+
+/// Rows decoded one page (window of rows) at a time, from a Parquet, Arrow or
+/// Avro file or from the shards of a dataset as if they were one array.
+///
+/// The root's first child is a synthetic node: "Metadata", describing a
+/// file's schema and row groups, or "Shards", listing a dataset's
+/// files. If the rows fit in a single page, they follow it as the root's
+/// children. Otherwise the root has one child per page, named like
+/// `Rows 1-1000`, and a row's path is `$[page][row within page]`. A page is
+/// decoded the first time it is looked up and kept until released, or until
+/// the cache is trimmed to the `MAX_CACHED_PAGES` most recently used pages.
+///
+/// Lookups decode pages on the calling thread. To keep a page's decoding off
+/// the GTK thread, get a `page_decoder` for the node first, decode the page
+/// elsewhere and store it with `store_page` before listing the node's children.
+#[derive(Debug)]
+pub struct PagedDocument {
+    source: Arc<RowSource>,
+    rows_per_page: usize,
+    summary: Value,
+    info_key: &'static str,
+    info: Arc<Value>,
+    page_summaries: Arc<[Value]>,
+    pages: Vec<OnceCell<Value>>,
+    uses: RecentUses,
+}
+
+impl PagedDocument {
+    /// For Parquet, `rows_per_page` is capped at the rows of the largest row
+    /// group, so that decoding a page never reads more than two row groups.
+    pub fn new(source: RowSource, rows_per_page: usize) -> Self {
+        let rows_per_page = match &source {
+            RowSource::Parquet(parquet) => rows_per_page.min(parquet.max_row_group_rows()),
+            _ => rows_per_page,
+        }
+        .max(1);
+        let num_rows = source.num_rows();
+        let page_count = num_rows.div_ceil(rows_per_page);
+        let (summary, info_key, info) = match &source {
+            RowSource::Parquet(parquet) => (
+                serde_json::json!({
+                    "rows": num_rows,
+                    "row_groups": parquet.num_row_groups(),
+                    "rows_per_page": rows_per_page,
+                }),
+                METADATA_KEY,
+                parquet.metadata_json(),
+            ),
+            RowSource::Arrow(arrow) => (
+                serde_json::json!({
+                    "rows": num_rows,
+                    "record_batches": arrow.num_batches(),
+                    "rows_per_page": rows_per_page,
+                }),
+                METADATA_KEY,
+                arrow.metadata_json(),
+            ),
+            RowSource::Avro(avro) => (
+                serde_json::json!({
+                    "rows": num_rows,
+                    "blocks": avro.num_blocks(),
+                    "rows_per_page": rows_per_page,
+                }),
+                METADATA_KEY,
+                avro.metadata_json(),
+            ),
+            RowSource::Dataset(dataset) => (
+                serde_json::json!({
+                    "rows": num_rows,
+                    "shards": dataset.shards().len(),
+                    "rows_per_page": rows_per_page,
+                }),
+                DATASET_SHARDS_KEY,
+                dataset.shards_json(),
+            ),
+        };
+        let page_summaries = (0..page_count)
+            .map(|page| {
+                let first_row = page * rows_per_page;
+                let last_row = (first_row + rows_per_page).min(num_rows);
+                serde_json::json!({
+                    "first_row": first_row + 1,
+                    "last_row": last_row,
+                    "rows": last_row - first_row,
+                })
+            })
+            .collect();
+        Self {
+            source: Arc::new(source),
+            rows_per_page,
+            summary,
+            info_key,
+            info: Arc::new(info),
+            page_summaries,
+            pages: (0..page_count).map(|_| OnceCell::new()).collect(),
+            uses: RecentUses::new(page_count),
+        }
+    }
+
+    fn shared_copy(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            rows_per_page: self.rows_per_page,
+            summary: self.summary.clone(),
+            info_key: self.info_key,
+            info: self.info.clone(),
+            page_summaries: self.page_summaries.clone(),
+            pages: self.pages.iter().map(|_| OnceCell::new()).collect(),
+            uses: RecentUses::new(self.pages.len()),
+        }
+    }
+
+    /// Number of shards, for a dataset.
+    pub fn shard_count(&self) -> Option<usize> {
+        match self.source.as_ref() {
+            RowSource::Parquet(_) | RowSource::Arrow(_) | RowSource::Avro(_) => None,
+            RowSource::Dataset(dataset) => Some(dataset.shards().len()),
+        }
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.source.num_rows()
+    }
+
+    /// Whether rows are grouped under page nodes (the file spans more than one page).
+    fn is_paged(&self) -> bool {
+        self.pages.len() > 1
+    }
+
+    /// Returns a page's rows as an array, decoding them on first use.
+    ///
+    /// Decoding blocks until the whole page is read. If the rows cannot be
+    /// decoded, the page holds an object describing the error.
+    fn page(&self, page: usize) -> Option<&Value> {
+        let cell = self.pages.get(page)?;
+        self.uses.touch(page);
+        Some(
+            cell.get_or_init(|| {
+                read_page(&self.source, page * self.rows_per_page, self.rows_per_page)
+            }),
+        )
+    }
+
+    /// The page that listing the children of `path` reads rows from, if any.
+    fn page_of(&self, path: &str) -> Option<usize> {
+        let segments = parse_json_path(path)?;
+        match segments.split_first() {
+            Some((PathSegment::Key(key), _)) if key == self.info_key => None,
+            Some((PathSegment::Index(page), _)) if self.is_paged() => Some(*page),
+            _ if self.is_paged() => None,
+            _ => Some(0),
+        }
+    }
+
+    /// Returns a decoder for the page under `path`, if it is not decoded yet.
+    fn page_decoder(&self, path: &str) -> Option<PageDecoder> {
+        let page = self.page_of(path)?;
+        if self.pages.get(page)?.get().is_some() {
+            return None;
+        }
+        Some(PageDecoder {
+            source: self.source.clone(),
+            page,
+            rows_per_page: self.rows_per_page,
+        })
+    }
+
+    /// Rows of a dataset are converted only if they come from a Parquet shard;
+    /// the rows and metadata of a single file are always converted.
+    fn value_origin(&self, path: &str) -> ValueOrigin {
+        let RowSource::Dataset(dataset) = self.source.as_ref() else {
+            return ValueOrigin::Converted;
+        };
+        let row = match parse_json_path(path).as_deref() {
+            Some([PathSegment::Index(page), PathSegment::Index(row), ..]) if self.is_paged() => {
+                page * self.rows_per_page + row
+            }
+            Some([PathSegment::Index(row), ..]) if !self.is_paged() => *row,
+            _ => return ValueOrigin::Json,
+        };
+        dataset.value_origin(row)
+    }
+
+    /// Keeps a page decoded by a `PageDecoder`, unless it was decoded meanwhile.
+    fn store_page(&mut self, decoded: DecodedPage) {
+        if let Some(cell) = self.pages.get(decoded.page) {
+            self.uses.touch(decoded.page);
+            let _ = cell.set(decoded.rows);
+        }
+    }
+
+    fn lookup_value(&self, path: &str) -> Option<&Value> {
+        let segments = parse_json_path(path)?;
+        match segments.split_first() {
+            None => Some(&self.summary),
+            Some((PathSegment::Key(key), rest)) if key == self.info_key => {
+                lookup_in_value(&self.info, rest)
+            }
+            Some((PathSegment::Index(page), rest)) if self.is_paged() => {
+                if rest.is_empty() {
+                    self.page_summaries.get(*page)
+                } else {
+                    lookup_in_value(self.page(*page)?, rest)
+                }
+            }
+            _ => lookup_in_value(self.page(0)?, &segments),
+        }
+    }
+
+    fn child_nodes(&self, path: &str) -> Vec<ChildNode<'_>> {
+        let Some(segments) = parse_json_path(path) else {
+            return Vec::new();
+        };
+        match segments.as_slice() {
+            [] => {
+                let mut children = vec![ChildNode {
+                    name: self.info_key.to_string(),
+                    segment: PathSegment::Key(self.info_key.to_string()),
+                    value: Some(self.info.as_ref()),
+                    preview: None,
+                }];
+                if self.is_paged() {
+                    children.extend(self.page_summaries.iter().enumerate().map(
+                        |(page, summary)| ChildNode {
+                            name: format!("Rows {}-{}", summary["first_row"], summary["last_row"]),
+                            segment: PathSegment::Index(page),
+                            value: Some(summary),
+                            preview: None,
+                        },
+                    ));
+                } else if let Some(rows) = self.page(0) {
+                    children.extend(value_child_nodes(rows));
+                }
+                children
+            }
+            [PathSegment::Index(page)] if self.is_paged() => {
+                self.page(*page).map(value_child_nodes).unwrap_or_default()
+            }
+            _ => self
+                .lookup_value(path)
+                .map(value_child_nodes)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Frees the decoded rows under `path`: one page, or every page for the root.
+    fn release_rows(&mut self, path: &str) {
+        let Some(segments) = parse_json_path(path) else {
+            return;
+        };
+        match segments.as_slice() {
+            [] => self.pages.iter_mut().for_each(|page| {
+                page.take();
+            }),
+            [PathSegment::Index(page)] if self.is_paged() => {
+                if let Some(page) = self.pages.get_mut(*page) {
+                    page.take();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Releases the least recently used decoded pages beyond `MAX_CACHED_PAGES`.
+    fn trim_cache(&mut self) {
+        for page in self.uses.pages_to_release(filled_cells(&self.pages)) {
+            self.pages[page].take();
+        }
+    }
+}
+
+/// A zip or tar archive whose members are parsed the first time they are looked up.
+///
+/// The root has one child per member that slopjson can parse, named by its
+/// path in the archive. A member's nodes have paths starting with `$[member]`,
+/// and the rest of the path is looked up in the member's own document. A
+/// member that fails to parse is shown as an object with an `error` member.
+/// At most `MAX_CACHED_PAGES` parsed members are kept once the cache is trimmed.
+#[derive(Debug)]
+pub struct ArchiveDocument {
+    archive: Arc<Archive>,
+    rows_per_page: usize,
+    summary: Value,
+    members: Vec<OnceCell<StoredDocument>>,
+    uses: RecentUses,
+}
+
+impl ArchiveDocument {
+    pub fn new(archive: Archive, rows_per_page: usize) -> Self {
+        let summary = serde_json::json!({
+            "archive": archive.kind().label(),
+            "members": archive.members().len(),
+        });
+        let members = archive.members().iter().map(|_| OnceCell::new()).collect();
+        Self {
+            uses: RecentUses::new(archive.members().len()),
+            archive: Arc::new(archive),
+            rows_per_page,
+            summary,
+            members,
+        }
+    }
+
+    fn shared_copy(&self) -> Self {
+        Self {
+            archive: self.archive.clone(),
+            rows_per_page: self.rows_per_page,
+            summary: self.summary.clone(),
+            members: self.members.iter().map(|_| OnceCell::new()).collect(),
+            uses: RecentUses::new(self.members.len()),
+        }
+    }
+
+    pub fn member_count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns a member's document, reading and parsing it on first use.
+    fn member(&self, member: usize) -> Option<&StoredDocument> {
+        let cell = self.members.get(member)?;
+        self.uses.touch(member);
+        Some(cell.get_or_init(|| {
+            let name = &self.archive.members()[member].name;
+            match self
+                .archive
+                .read_member(member)
+                .and_then(|data| parse_member(name, data))
+            {
+                Ok(parsed) => {
+                    let origin = parsed.value_origin();
+                    StoredDocument::from_parse_result(parsed.content, origin, self.rows_per_page)
+                }
+                Err(e) => StoredDocument::Single(
+                    Arc::new(serde_json::json!({ "error": e.to_string() })),
+                    ValueOrigin::Json,
+                ),
+            }
+        }))
+    }
+
+    /// The origin of a member's values, parsing the member if needed.
+    fn value_origin(&self, path: &str) -> ValueOrigin {
+        match Self::split_member_path(path) {
+            Some((member, member_path)) => self
+                .member(member)
+                .map_or(ValueOrigin::Json, |doc| doc.value_origin(&member_path)),
+            None => ValueOrigin::Json,
+        }
+    }
+
+    /// Returns a decoder for the page under `path`, in a member that is already parsed.
+    fn page_decoder(&self, path: &str) -> Option<PageDecoder> {
+        let (member, member_path) = Self::split_member_path(path)?;
+        self.members.get(member)?.get()?.page_decoder(&member_path)
+    }
+
+    fn store_page(&mut self, path: &str, decoded: DecodedPage) {
+        let Some((member, member_path)) = Self::split_member_path(path) else {
+            return;
+        };
+        if let Some(doc) = self.members.get_mut(member).and_then(OnceCell::get_mut) {
+            doc.store_page(&member_path, decoded);
+        }
+    }
+
+    /// Splits a path below a member into the member and the path within it.
+    fn split_member_path(path: &str) -> Option<(usize, String)> {
+        match parse_json_path(path)?.first()? {
+            PathSegment::Index(member) => {
+                let prefix = format!("$[{}]", member);
+                Some((*member, format!("${}", path.strip_prefix(&prefix)?)))
+            }
+            PathSegment::Key(_) => None,
+        }
+    }
+
+    fn lookup_value(&self, path: &str) -> Option<&Value> {
+        if path == "$" {
+            return Some(&self.summary);
+        }
+        let (member, member_path) = Self::split_member_path(path)?;
+        self.member(member)?.lookup_value(&member_path)
+    }
+
+    fn child_nodes(&self, path: &str) -> Vec<ChildNode<'_>> {
+        if path == "$" {
+            return self
+                .archive
+                .members()
+                .iter()
+                .zip(&self.members)
+                .enumerate()
+                .map(|(idx, (member, cell))| ChildNode {
+                    name: member.name.clone(),
+                    segment: PathSegment::Index(idx),
+                    value: cell.get().and_then(|doc| doc.lookup_value("$")),
+                    preview: None,
+                })
+                .collect();
+        }
+        let Some((member, member_path)) = Self::split_member_path(path) else {
+            return Vec::new();
+        };
+        self.member(member)
+            .map(|doc| doc.child_nodes(&member_path))
+            .unwrap_or_default()
+    }
+
+    /// Forgets parsed members: one member, or every member for the root. Below
+    /// a member, the member's own document releases its rows.
+    fn release_rows(&mut self, path: &str) {
+        if path == "$" {
+            self.members.iter_mut().for_each(|member| {
+                member.take();
+            });
+            return;
+        }
+        let Some((member, member_path)) = Self::split_member_path(path) else {
+            return;
+        };
+        let Some(cell) = self.members.get_mut(member) else {
+            return;
+        };
+        if member_path == "$" {
+            cell.take();
+        } else if let Some(doc) = cell.get_mut() {
+            doc.release_rows(&member_path);
+        }
+    }
+
+    /// Releases the least recently used parsed members beyond `MAX_CACHED_PAGES`,
+    /// then trims the caches of the members that remain.
+    fn trim_cache(&mut self) {
+        for member in self.uses.pages_to_release(filled_cells(&self.members)) {
+            self.members[member].take();
+        }
+        for member in self.members.iter_mut().filter_map(OnceCell::get_mut) {
+            member.trim_cache();
+        }
+    }
+}
+
+/// A Jupyter notebook, listed cell by cell.
+///
+/// The root's first child is the "Metadata" node, holding the notebook's
+/// kernel and language metadata, followed by one child per cell, named like
+/// `Cell 3 [code]` and previewed by the first line of its source. A cell's
+/// path is `$[cell]`, and the metadata's `$.Metadata`.
+#[derive(Debug, Clone)]
+pub struct NotebookDocument {
+    notebook: Arc<Notebook>,
+    summary: Value,
+}
+
+impl NotebookDocument {
+    pub fn new(notebook: Notebook) -> Self {
+        let summary = notebook.summary();
+        Self {
+            notebook: Arc::new(notebook),
+            summary,
+        }
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.notebook.cells.len()
+    }
+
+    pub fn kernel_name(&self) -> Option<&str> {
+        self.notebook.kernel_name()
+    }
+
+    fn lookup_value(&self, path: &str) -> Option<&Value> {
+        let segments = parse_json_path(path)?;
+        match segments.split_first() {
+            None => Some(&self.summary),
+            Some((PathSegment::Key(key), rest)) if key == METADATA_KEY => {
+                lookup_in_value(&self.notebook.metadata, rest)
+            }
+            Some((PathSegment::Index(cell), rest)) => {
+                lookup_in_value(&self.notebook.cells.get(*cell)?.value, rest)
+            }
+            Some((PathSegment::Key(_), _)) => None,
+        }
+    }
+
+    fn child_nodes(&self, path: &str) -> Vec<ChildNode<'_>> {
+        if path != "$" {
+            return self
+                .lookup_value(path)
+                .map(value_child_nodes)
+                .unwrap_or_default();
+        }
+        let metadata = ChildNode {
+            name: METADATA_KEY.to_string(),
+            segment: PathSegment::Key(METADATA_KEY.to_string()),
+            value: Some(&self.notebook.metadata),
+            preview: None,
+        };
+        let cells = self
+            .notebook
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| ChildNode {
+                name: format!("Cell {} [{}]", idx + 1, cell.cell_type),
+                segment: PathSegment::Index(idx),
+                value: Some(&cell.value),
+                preview: cell.first_line().map(str::to_string),
+            });
+        std::iter::once(metadata).chain(cells).collect()
     }
 }
 
 #[derive(Debug)]
 pub enum StoredDocument {
-    Single(Value),
+    Single(Arc<Value>, ValueOrigin),
     JsonL(JsonLDocument),
+    IndexedJsonL(IndexedJsonLDocument),
+    Paged(PagedDocument),
+    Archive(ArchiveDocument),
+    Notebook(NotebookDocument),
 }
 
 impl StoredDocument {
+    /// Wraps a parse result in the document type that serves it to the tree.
+    ///
+    /// `rows_per_page` sets the page size of Parquet, Arrow and Avro files and of
+    /// JSONL files whose lines are parsed on demand, including those inside archives.
+    /// `origin` says whether a single value was read as JSON or converted from
+    /// another format; the other results know where their values come from.
+    pub fn from_parse_result(
+        result: ParseResult,
+        origin: ValueOrigin,
+        rows_per_page: usize,
+    ) -> Self {
+        match result {
+            ParseResult::Single(value) => StoredDocument::Single(Arc::new(value), origin),
+            ParseResult::JsonL {
+                values,
+                lines,
+                errors,
+                separator,
+            } => StoredDocument::JsonL(
+                JsonLDocument::new(values, lines, errors).with_separator(separator),
+            ),
+            ParseResult::YamlStream(documents) => {
+                StoredDocument::JsonL(JsonLDocument::yaml_stream(documents))
+            }
+            ParseResult::IndexedJsonL(index) => {
+                StoredDocument::IndexedJsonL(IndexedJsonLDocument::new(index, rows_per_page))
+            }
+            ParseResult::PagedParquet(source) => StoredDocument::Paged(PagedDocument::new(
+                RowSource::Parquet(source),
+                rows_per_page,
+            )),
+            ParseResult::PagedArrow(source) => {
+                StoredDocument::Paged(PagedDocument::new(RowSource::Arrow(source), rows_per_page))
+            }
+            ParseResult::PagedAvro(source) => StoredDocument::Paged(PagedDocument::new(
+                RowSource::Avro(Box::new(source)),
+                rows_per_page,
+            )),
+            ParseResult::Dataset(dataset) => StoredDocument::Paged(PagedDocument::new(
+                RowSource::Dataset(dataset),
+                rows_per_page,
+            )),
+            ParseResult::Archive(archive) => {
+                StoredDocument::Archive(ArchiveDocument::new(archive, rows_per_page))
+            }
+            ParseResult::Notebook(notebook) => {
+                StoredDocument::Notebook(NotebookDocument::new(notebook))
+            }
+        }
+    }
+
+    /// Returns a document over the same data with nothing decoded yet, that can
+    /// be moved to another thread, as search does.
+    ///
+    /// The data is shared rather than copied. Lines, pages and members that the
+    /// copy decodes are cached in the copy, leaving this document's caches as
+    /// they are.
+    pub fn shared_copy(&self) -> Self {
+        match self {
+            StoredDocument::Single(value, origin) => StoredDocument::Single(value.clone(), *origin),
+            StoredDocument::JsonL(doc) => StoredDocument::JsonL(doc.clone()),
+            StoredDocument::IndexedJsonL(doc) => StoredDocument::IndexedJsonL(doc.shared_copy()),
+            StoredDocument::Paged(doc) => StoredDocument::Paged(doc.shared_copy()),
+            StoredDocument::Archive(doc) => StoredDocument::Archive(doc.shared_copy()),
+            StoredDocument::Notebook(doc) => StoredDocument::Notebook(doc.clone()),
+        }
+    }
+
+    /// Whether the value at `path` was read as JSON or converted from another
+    /// format, which decides whether objects such as `{"$float": "NaN"}` are
+    /// shown as the values they stand for.
+    pub fn value_origin(&self, path: &str) -> ValueOrigin {
+        match self {
+            StoredDocument::Single(_, origin) => *origin,
+            StoredDocument::JsonL(doc) if doc.yaml_documents => ValueOrigin::Converted,
+            StoredDocument::JsonL(_) | StoredDocument::IndexedJsonL(_) => ValueOrigin::Json,
+            StoredDocument::Paged(doc) => doc.value_origin(path),
+            StoredDocument::Archive(doc) => doc.value_origin(path),
+            StoredDocument::Notebook(_) => ValueOrigin::Converted,
+        }
+    }
+
     pub fn lookup_value(&self, path: &str) -> Option<&Value> {
         match self {
-            StoredDocument::Single(value) => lookup_value(value, path),
+            StoredDocument::Single(value, _) => lookup_value(value, path),
             StoredDocument::JsonL(doc) => {
                 if path == "$" {
                     return Some(&doc.summary);
                 }
                 lookup_value_in_jsonl(&doc.values, path)
             }
+            StoredDocument::IndexedJsonL(doc) => doc.lookup_value(path),
+            StoredDocument::Paged(doc) => doc.lookup_value(path),
+            StoredDocument::Archive(doc) => doc.lookup_value(path),
+            StoredDocument::Notebook(doc) => doc.lookup_value(path),
+        }
+    }
+
+    /// Lists the children of the node at `path`, in the order they appear in the tree.
+    ///
+    /// The root of a JSONL document has one child per line (or per page of
+    /// lines, for a large file with more lines than a page), the root of a
+    /// Parquet, Arrow or Avro file or dataset has the metadata or shards node followed by one
+    /// child per row (or per page of rows, for more rows than a page), and the
+    /// root of an archive has one child per member, and the root of a notebook has
+    /// the metadata node followed by one child per cell.
+    /// Every other node has one child per object member or array element;
+    /// leaves have none.
+    pub fn child_nodes(&self, path: &str) -> Vec<ChildNode<'_>> {
+        match self {
+            StoredDocument::JsonL(doc) if path == "$" => doc.child_nodes(),
+            StoredDocument::IndexedJsonL(doc) => doc.child_nodes(path),
+            StoredDocument::Paged(doc) => doc.child_nodes(path),
+            StoredDocument::Archive(doc) => doc.child_nodes(path),
+            StoredDocument::Notebook(doc) => doc.child_nodes(path),
+            _ => self
+                .lookup_value(path)
+                .map(value_child_nodes)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Lists the children of the node at `path`, with object members in `key_order`.
+    ///
+    /// Children are only sorted when they are all object members; array elements,
+    /// lines, pages and archive members keep their order.
+    pub fn ordered_child_nodes(&self, path: &str, key_order: KeyOrder) -> Vec<ChildNode<'_>> {
+        let mut children = self.child_nodes(path);
+        if key_order == KeyOrder::Sorted
+            && children
+                .iter()
+                .all(|child| matches!(child.segment, PathSegment::Key(_)))
+        {
+            children.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        children
+    }
+
+    /// Returns a decoder for the page of rows that listing the children of
+    /// `path` would decode, if there is one and it is not decoded yet.
+    ///
+    /// Only Parquet, Arrow and Avro files and datasets, on their own or in an
+    /// archive member that is already parsed, decode pages this way.
+    pub fn page_decoder(&self, path: &str) -> Option<PageDecoder> {
+        match self {
+            StoredDocument::Paged(doc) => doc.page_decoder(path),
+            StoredDocument::Archive(doc) => doc.page_decoder(path),
+            _ => None,
+        }
+    }
+
+    /// Keeps a page decoded from the `page_decoder` for `path`.
+    pub fn store_page(&mut self, path: &str, decoded: DecodedPage) {
+        match self {
+            StoredDocument::Paged(doc) => doc.store_page(decoded),
+            StoredDocument::Archive(doc) => doc.store_page(path, decoded),
+            _ => {}
+        }
+    }
+
+    /// Frees data that was decoded on demand under `path`, once its rows are unloaded.
+    ///
+    /// Only indexed, paged and archive documents decode data lazily; for the others
+    /// this does nothing.
+    pub fn release_rows(&mut self, path: &str) {
+        match self {
+            StoredDocument::IndexedJsonL(doc) => doc.release_rows(path),
+            StoredDocument::Paged(doc) => doc.release_rows(path),
+            StoredDocument::Archive(doc) => doc.release_rows(path),
+            _ => {}
+        }
+    }
+
+    /// Releases the least recently used pages and archive members decoded on
+    /// demand, keeping the `MAX_CACHED_PAGES` most recently used.
+    ///
+    /// Unlike `release_rows`, this does not depend on rows being unloaded: rows
+    /// whose data was released decode it again when they are next looked up.
+    pub fn trim_cache(&mut self) {
+        match self {
+            StoredDocument::IndexedJsonL(doc) => doc.trim_cache(),
+            StoredDocument::Paged(doc) => doc.trim_cache(),
+            StoredDocument::Archive(doc) => doc.trim_cache(),
+            _ => {}
         }
     }
 }
@@ -59,11 +1273,488 @@ mod tests {
 
     #[test]
     fn test_jsonl_summary_root() {
-        let doc = StoredDocument::JsonL(JsonLDocument::new(vec![
-            serde_json::json!({"name": "first"}),
-            serde_json::json!({"name": "second"}),
-        ]));
+        let doc = StoredDocument::JsonL(JsonLDocument::new(
+            vec![
+                serde_json::json!({"name": "first"}),
+                serde_json::json!({"name": "second"}),
+            ],
+            vec![1, 2],
+            Vec::new(),
+        ));
         let result = doc.lookup_value("$").unwrap();
         assert_eq!(result["lines"], 2);
     }
+
+    #[test]
+    fn test_jsonl_error_nodes() {
+        let doc = StoredDocument::JsonL(JsonLDocument::new(
+            vec![
+                serde_json::json!({"name": "first"}),
+                serde_json::json!({"name": "second"}),
+            ],
+            // Line 3 is blank
+            vec![1, 4],
+            vec![LineError {
+                line: 2,
+                offset: 18,
+                message: "expected value at line 1 column 10".to_string(),
+                index: 1,
+            }],
+        ));
+
+        let summary = doc.lookup_value("$").unwrap();
+        assert_eq!(summary["lines"], 2);
+        assert_eq!(summary["errors"], 1);
+
+        let lines = doc.child_nodes("$");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].name, "Line 1");
+        assert_eq!(lines[1].name, "Error at line 2");
+        assert_eq!(lines[2].name, "Line 4");
+        assert_eq!(doc.lookup_value("$[1].offset").unwrap(), 18);
+        assert_eq!(doc.lookup_value("$[2].name").unwrap(), "second");
+    }
+
+    #[test]
+    fn test_yaml_stream_nodes() {
+        let doc = StoredDocument::from_parse_result(
+            ParseResult::YamlStream(vec![
+                serde_json::json!({"kind": "Deployment", "metadata": {"name": "web"}}),
+                serde_json::json!({"kind": "Service"}),
+                serde_json::json!([1, 2]),
+            ]),
+            ValueOrigin::Converted,
+            1000,
+        );
+        assert_eq!(doc.lookup_value("$").unwrap()["documents"], 3);
+        assert_eq!(doc.value_origin("$[0]"), ValueOrigin::Converted);
+
+        let documents = doc.child_nodes("$");
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0].name, "Doc 1");
+        assert_eq!(documents[0].preview.as_deref(), Some("Deployment/web"));
+        assert_eq!(documents[1].preview.as_deref(), Some("Service"));
+        assert_eq!(documents[2].preview, None);
+        assert_eq!(doc.lookup_value("$[0].metadata.name").unwrap(), "web");
+    }
+
+    #[test]
+    fn test_concatenated_value_nodes() {
+        let doc = StoredDocument::from_parse_result(
+            ParseResult::JsonL {
+                values: vec![serde_json::json!({"a": 1}), serde_json::json!({"a": 2})],
+                lines: vec![1, 1],
+                errors: Vec::new(),
+                separator: JsonSeparator::Concatenated,
+            },
+            ValueOrigin::Json,
+            1000,
+        );
+        assert_eq!(doc.value_origin("$[1]"), ValueOrigin::Json);
+        let values = doc.child_nodes("$");
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1].name, "Value 2");
+        assert_eq!(doc.lookup_value("$[1].a").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_notebook_nodes() {
+        let notebook = serde_json::json!({
+            "cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["# Results\n", "text"]},
+                {
+                    "cell_type": "code",
+                    "execution_count": 2,
+                    "metadata": {},
+                    "outputs": [{"name": "stdout", "output_type": "stream", "text": ["4\n"]}],
+                    "source": ["print(2 + 2)"]
+                }
+            ],
+            "metadata": {"kernelspec": {"display_name": "Python 3", "name": "python3"}},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        });
+        let parsed = parse_member("analysis.ipynb", notebook.to_string().into_bytes()).unwrap();
+        let doc = StoredDocument::from_parse_result(parsed.content, ValueOrigin::Json, 1000);
+        let StoredDocument::Notebook(notebook) = &doc else {
+            panic!("Expected a notebook document");
+        };
+        assert_eq!(notebook.kernel_name(), Some("Python 3"));
+        assert_eq!(doc.lookup_value("$").unwrap()["code_cells"], 1);
+
+        let children = doc.child_nodes("$");
+        let names: Vec<&str> = children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, ["Metadata", "Cell 1 [markdown]", "Cell 2 [code]"]);
+        assert_eq!(children[1].preview.as_deref(), Some("# Results"));
+        assert_eq!(doc.lookup_value("$.Metadata.nbformat").unwrap(), "4.5");
+        assert_eq!(doc.lookup_value("$[1].outputs[0].text").unwrap(), "4\n");
+        assert_eq!(doc.child_nodes("$[1]")[0].name, "source");
+    }
+
+    #[test]
+    fn test_ordered_child_nodes() {
+        let doc = StoredDocument::Single(
+            Arc::new(serde_json::json!({
+                "zeta": 1,
+                "alpha": {"b": 2, "a": 3},
+                "mid": [3, 1, 2],
+            })),
+            ValueOrigin::Json,
+        );
+        let names = |path, key_order| {
+            doc.ordered_child_nodes(path, key_order)
+                .into_iter()
+                .map(|child| child.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("$", KeyOrder::File), ["zeta", "alpha", "mid"]);
+        assert_eq!(names("$", KeyOrder::Sorted), ["alpha", "mid", "zeta"]);
+        assert_eq!(names("$.alpha", KeyOrder::Sorted), ["a", "b"]);
+        assert_eq!(names("$.mid", KeyOrder::Sorted), ["[0]", "[1]", "[2]"]);
+    }
+
+    #[test]
+    fn test_child_nodes_single() {
+        let doc = StoredDocument::Single(
+            Arc::new(serde_json::json!({"items": [10, 20], "name": "x"})),
+            ValueOrigin::Json,
+        );
+        let root_children = doc.child_nodes("$");
+        assert_eq!(root_children.len(), 2);
+        assert_eq!(root_children[0].name, "items");
+        assert_eq!(
+            root_children[0].segment,
+            PathSegment::Key("items".to_string())
+        );
+
+        let items = doc.child_nodes("$.items");
+        assert_eq!(items[1].name, "[1]");
+        assert_eq!(items[1].segment, PathSegment::Index(1));
+        assert_eq!(items[1].value, Some(&serde_json::json!(20)));
+
+        assert!(doc.child_nodes("$.name").is_empty());
+    }
+
+    #[test]
+    fn test_child_nodes_jsonl_root() {
+        let doc = StoredDocument::JsonL(JsonLDocument::new(
+            vec![
+                serde_json::json!({"name": "first"}),
+                serde_json::json!({"name": "second"}),
+            ],
+            vec![1, 2],
+            Vec::new(),
+        ));
+        let lines = doc.child_nodes("$");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].name, "Line 2");
+        assert_eq!(lines[1].segment, PathSegment::Index(1));
+
+        let fields = doc.child_nodes("$[1]");
+        assert_eq!(fields[0].name, "name");
+        assert_eq!(fields[0].value, Some(&serde_json::json!("second")));
+    }
+
+    /// Writes `0..num_rows` to a Parquet file, in row groups of `row_group_size`
+    /// rows, and opens it for paging.
+    fn open_paged_parquet(
+        name: &str,
+        num_rows: i64,
+        row_group_size: usize,
+        rows_per_page: usize,
+    ) -> StoredDocument {
+        use arrow::array::Int64Array;
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::arrow_writer::ArrowWriter;
+        use parquet::file::properties::WriterProperties;
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from_iter_values(0..num_rows))],
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("{}-{}.parquet", name, std::process::id()));
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(row_group_size)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(std::fs::File::create(&path).unwrap(), schema, Some(props))
+                .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let source = ParquetFile::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        StoredDocument::Paged(PagedDocument::new(
+            RowSource::Parquet(source),
+            rows_per_page,
+        ))
+    }
+
+    #[test]
+    fn test_paged_parquet_pages() {
+        let mut doc = open_paged_parquet("slopjson-pages", 25, 25, 10);
+
+        assert_eq!(doc.lookup_value("$").unwrap()["rows"], 25);
+        let root_children = doc.child_nodes("$");
+        assert_eq!(root_children.len(), 4);
+        assert_eq!(root_children[0].name, "Metadata");
+        assert_eq!(root_children[1].name, "Rows 1-10");
+        assert_eq!(root_children[3].name, "Rows 21-25");
+        assert_eq!(root_children[3].segment, PathSegment::Index(2));
+
+        let last_page = doc.child_nodes("$[2]");
+        assert_eq!(last_page.len(), 5);
+        assert_eq!(last_page[0].value.unwrap()["id"], 20);
+        assert_eq!(doc.lookup_value("$[1][3].id").unwrap(), 13);
+        assert_eq!(doc.lookup_value("$[1]").unwrap()["first_row"], 11);
+        assert_eq!(doc.lookup_value("$.Metadata.num_rows").unwrap(), 25);
+
+        doc.release_rows("$[1]");
+        assert_eq!(doc.lookup_value("$[1][3].id").unwrap(), 13);
+    }
+
+    #[test]
+    fn test_paged_parquet_page_size_capped_at_row_group() {
+        let doc = open_paged_parquet("slopjson-capped-pages", 25, 8, 1000);
+
+        assert_eq!(doc.lookup_value("$").unwrap()["rows_per_page"], 8);
+        let root_children = doc.child_nodes("$");
+        assert_eq!(root_children.len(), 5);
+        assert_eq!(root_children[1].name, "Rows 1-8");
+        assert_eq!(root_children[4].name, "Rows 25-25");
+        assert_eq!(doc.lookup_value("$[1][2].id").unwrap(), 10);
+    }
+
+    #[test]
+    fn test_paged_parquet_single_page() {
+        let doc = open_paged_parquet("slopjson-single-page", 3, 3, 10);
+
+        let root_children = doc.child_nodes("$");
+        assert_eq!(root_children.len(), 4);
+        assert_eq!(root_children[0].name, "Metadata");
+        assert_eq!(root_children[1].name, "[0]");
+        assert_eq!(root_children[3].segment, PathSegment::Index(2));
+        assert_eq!(doc.lookup_value("$[2].id").unwrap(), 2);
+        assert_eq!(doc.value_origin("$[2]"), ValueOrigin::Converted);
+        assert_eq!(
+            doc.lookup_value("$.Metadata.row_groups[0].num_rows")
+                .unwrap(),
+            3
+        );
+        assert!(doc
+            .child_nodes("$.Metadata")
+            .iter()
+            .any(|child| child.name == "row_groups"));
+    }
+
+    #[test]
+    fn test_paged_parquet_decodes_pages_elsewhere() {
+        use std::sync::atomic::AtomicBool;
+
+        let mut doc = open_paged_parquet("slopjson-decoded-pages", 25, 25, 10);
+        assert!(doc.page_decoder("$").is_none());
+        assert!(doc.page_decoder("$.Metadata").is_none());
+
+        let decoder = doc.page_decoder("$[1]").unwrap();
+        let decoded = std::thread::spawn(move || decoder.decode(&ParseControl::default()))
+            .join()
+            .unwrap()
+            .unwrap();
+        doc.store_page("$[1]", decoded);
+        assert!(doc.page_decoder("$[1]").is_none());
+        assert!(doc.page_decoder("$[1][3]").is_none());
+        assert_eq!(doc.child_nodes("$[1]")[3].value.unwrap()["id"], 13);
+
+        let cancelled = ParseControl::new(Arc::new(AtomicBool::new(true)), |_| {});
+        assert!(matches!(
+            doc.page_decoder("$[2]").unwrap().decode(&cancelled),
+            Err(ParseError::Cancelled)
+        ));
+
+        let single = open_paged_parquet("slopjson-decoded-single-page", 3, 3, 10);
+        assert!(single.page_decoder("$").is_some());
+        assert!(single.page_decoder("$.Metadata").is_none());
+    }
+
+    #[test]
+    fn test_indexed_jsonl_parses_lines_on_demand() {
+        let path =
+            std::env::temp_dir().join(format!("slopjson-indexed-doc-{}.jsonl", std::process::id()));
+        std::fs::write(
+            &path,
+            "{\"name\": \"first\"}\n{\"name\": \"second\"}\nnot json\n",
+        )
+        .unwrap();
+        let index = JsonLIndex::build(&path, &crate::json_reader::ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut doc = StoredDocument::IndexedJsonL(IndexedJsonLDocument::new(index, 1000));
+
+        assert_eq!(doc.lookup_value("$").unwrap()["lines"], 3);
+        let lines = doc.child_nodes("$");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].name, "Line 2");
+        assert_eq!(lines[1].value, None);
+
+        assert_eq!(doc.lookup_value("$[1].name").unwrap(), "second");
+        assert_eq!(
+            doc.child_nodes("$")[1].value,
+            Some(&serde_json::json!({"name": "second"}))
+        );
+        assert!(doc.lookup_value("$[2].error").is_some());
+
+        doc.release_rows("$[1]");
+        assert_eq!(doc.child_nodes("$")[1].value, None);
+        assert_eq!(doc.child_nodes("$[1]")[0].name, "name");
+    }
+
+    #[test]
+    fn test_indexed_jsonl_pages_of_lines() {
+        let path = std::env::temp_dir().join(format!(
+            "slopjson-indexed-pages-{}.jsonl",
+            std::process::id()
+        ));
+        std::fs::write(&path, "{\"id\": 1}\n\n{\"id\": 2}\n{\"id\": 3}\n").unwrap();
+        let index = JsonLIndex::build(&path, &crate::json_reader::ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut doc = StoredDocument::IndexedJsonL(IndexedJsonLDocument::new(index, 2));
+
+        // Pages and lines are named by line number in the file, counting the blank line
+        let pages = doc.child_nodes("$");
+        let names: Vec<&str> = pages.iter().map(|page| page.name.as_str()).collect();
+        assert_eq!(names, ["Lines 1-3", "Lines 4-4"]);
+        assert_eq!(doc.lookup_value("$[1]").unwrap()["lines"], 1);
+        assert_eq!(doc.child_nodes("$[0]")[1].name, "Line 3");
+
+        let lines = doc.child_nodes("$[1]");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].name, "Line 4");
+        assert_eq!(lines[0].value, None);
+        assert_eq!(doc.lookup_value("$[1][0].id").unwrap(), 3);
+        assert!(doc.lookup_value("$[0][2]").is_none());
+        assert!(doc.child_nodes("$[1]")[0].value.is_some());
+
+        doc.release_rows("$[1]");
+        assert_eq!(doc.child_nodes("$[1]")[0].value, None);
+    }
+
+    #[test]
+    fn test_trim_cache_keeps_recent_pages() {
+        let path = std::env::temp_dir().join(format!(
+            "slopjson-indexed-trim-{}.jsonl",
+            std::process::id()
+        ));
+        let lines: String = (0..12).map(|id| format!("{{\"id\": {}}}\n", id)).collect();
+        std::fs::write(&path, lines).unwrap();
+        let index = JsonLIndex::build(&path, &crate::json_reader::ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut doc = StoredDocument::IndexedJsonL(IndexedJsonLDocument::new(index, 1));
+
+        for page in 0..12 {
+            assert_eq!(
+                doc.lookup_value(&format!("$[{}][0].id", page)).unwrap(),
+                page
+            );
+        }
+        // Looking up a page again makes it recent
+        doc.lookup_value("$[0][0]");
+        doc.trim_cache();
+
+        let decoded: Vec<usize> = (0..12)
+            .filter(|page| doc.child_nodes(&format!("$[{}]", page))[0].value.is_some())
+            .collect();
+        assert_eq!(decoded, [0, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(decoded.len(), MAX_CACHED_PAGES);
+        assert_eq!(doc.lookup_value("$[1][0].id").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_archive_members_parse_on_demand() {
+        use crate::archive::ArchiveKind;
+        use crate::json_reader::ParseControl;
+
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in [
+            ("a.json", &b"{\"a\": {\"b\": 1}}"[..]),
+            ("rows.jsonl", &b"{\"id\": 1}\n{\"id\": 2}\n"[..]),
+            ("broken.yaml", &b"key: [1, 2\n"[..]),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        let path = std::env::temp_dir().join(format!("slopjson-doc-{}.tar", std::process::id()));
+        std::fs::write(&path, builder.into_inner().unwrap()).unwrap();
+        let archive =
+            Archive::open(&path, ArchiveKind::Tar, None, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut doc = StoredDocument::from_parse_result(
+            ParseResult::Archive(archive),
+            ValueOrigin::Json,
+            1000,
+        );
+
+        assert_eq!(doc.lookup_value("$").unwrap()["members"], 3);
+        let members = doc.child_nodes("$");
+        let names: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["a.json", "rows.jsonl", "broken.yaml"]);
+        assert!(members.iter().all(|member| member.value.is_none()));
+
+        // Looking inside a member parses it, with paths relative to the member
+        assert_eq!(doc.lookup_value("$[0].a.b").unwrap(), 1);
+        assert_eq!(doc.value_origin("$[0].a"), ValueOrigin::Json);
+        let lines = doc.child_nodes("$[1]");
+        assert_eq!(lines[1].name, "Line 2");
+        assert_eq!(doc.lookup_value("$[1][1].id").unwrap(), 2);
+        assert!(doc.lookup_value("$[2]").unwrap()["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid YAML"));
+        assert!(doc.child_nodes("$")[0].value.is_some());
+
+        doc.release_rows("$[0]");
+        assert!(doc.child_nodes("$")[0].value.is_none());
+        assert!(doc.child_nodes("$")[1].value.is_some());
+    }
+
+    #[test]
+    fn test_dataset_pages_cross_shards() {
+        use crate::json_reader::ParseControl;
+
+        let dir = std::env::temp_dir().join(format!("slopjson-doc-dataset-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("part-0.jsonl"),
+            "{\"id\": 0}\n{\"id\": 1}\n{\"id\": 2}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("part-1.jsonl"), "{\"id\": 3}\n").unwrap();
+        let dataset = Dataset::open(&dir, &ParseControl::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let doc = StoredDocument::from_parse_result(
+            ParseResult::Dataset(dataset.unwrap()),
+            ValueOrigin::Json,
+            2,
+        );
+
+        assert_eq!(doc.lookup_value("$").unwrap()["rows"], 4);
+        assert_eq!(doc.lookup_value("$").unwrap()["shards"], 2);
+        let root_children = doc.child_nodes("$");
+        let names: Vec<&str> = root_children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, [DATASET_SHARDS_KEY, "Rows 1-2", "Rows 3-4"]);
+        assert_eq!(
+            doc.lookup_value("$.Shards[1].name").unwrap(),
+            "part-1.jsonl"
+        );
+
+        // The second page holds the last row of the first shard and the second shard
+        assert_eq!(doc.lookup_value("$[1][0].id").unwrap(), 2);
+        assert_eq!(doc.lookup_value("$[1][1].id").unwrap(), 3);
+        assert_eq!(doc.value_origin("$[1][1]"), ValueOrigin::Json);
+    }
 }
@@ -0,0 +1,209 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding text files that are not plain UTF-8.
+//!
+//! Files exported by Windows tools are often UTF-16 or start with a UTF-8
+//! byte order mark, and older files may be Latin-1. The encoding is taken from
+//! the byte order mark if there is one, then from the pattern of NUL bytes
+//! that UTF-16 gives ASCII text, and otherwise the text is read as UTF-8, or
+//! as Latin-1 if it is not valid UTF-8.
+
+/// Starts UTF-8 text written with a byte order mark.
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+const UTF16LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// The character encoding text content was read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 starting with a byte order mark, which is dropped
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// Content that is not valid UTF-8, read one byte per character
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Name shown next to the file name for encodings other than plain UTF-8.
+    pub fn label(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 BOM",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::Latin1 => "Latin-1",
+        }
+    }
+}
+
+/// Returns the UTF-16 byte order of content that starts with a UTF-16 byte
+/// order mark, or whose first characters are ASCII written as UTF-16.
+pub fn detect_utf16(head: &[u8]) -> Option<TextEncoding> {
+    if head.starts_with(UTF16LE_BOM) {
+        return Some(TextEncoding::Utf16Le);
+    }
+    if head.starts_with(UTF16BE_BOM) {
+        return Some(TextEncoding::Utf16Be);
+    }
+    // ASCII characters are a NUL byte and a nonzero byte in UTF-16
+    let pairs: Vec<&[u8]> = head.chunks_exact(2).take(64).collect();
+    if pairs.len() < 2 {
+        return None;
+    }
+    if pairs.iter().all(|pair| pair[0] != 0 && pair[1] == 0) {
+        Some(TextEncoding::Utf16Le)
+    } else if pairs.iter().all(|pair| pair[0] == 0 && pair[1] != 0) {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+/// Text decoded from bytes, with how it was decoded.
+#[derive(Debug)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: TextEncoding,
+    /// Set if some bytes could not be decoded exactly, saying how they were read
+    pub warning: Option<String>,
+}
+
+/// Decodes text content, detecting its encoding.
+///
+/// Byte order marks are dropped. UTF-16 with unpaired surrogates has them
+/// replaced with U+FFFD, and content that is not valid UTF-8 is read as
+/// Latin-1; both come with a warning, as some characters may be wrong.
+pub fn decode_text(data: Vec<u8>) -> DecodedText {
+    if let Some(encoding) = detect_utf16(&data) {
+        let bytes = data
+            .strip_prefix(UTF16LE_BOM)
+            .or_else(|| data.strip_prefix(UTF16BE_BOM))
+            .unwrap_or(&data);
+        let units = bytes.chunks_exact(2).map(|pair| match encoding {
+            TextEncoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+            _ => u16::from_le_bytes([pair[0], pair[1]]),
+        });
+        let mut replaced = 0;
+        let text: String = char::decode_utf16(units)
+            .map(|c| {
+                c.unwrap_or_else(|_| {
+                    replaced += 1;
+                    char::REPLACEMENT_CHARACTER
+                })
+            })
+            .collect();
+        let warning = (replaced > 0 || !bytes.len().is_multiple_of(2)).then(|| {
+            format!(
+                "The content is not valid {}; characters that could not be decoded are shown as \u{FFFD}",
+                encoding.label()
+            )
+        });
+        return DecodedText {
+            text,
+            encoding,
+            warning,
+        };
+    }
+
+    let (data, encoding) = match data.strip_prefix(UTF8_BOM) {
+        Some(rest) => (rest.to_vec(), TextEncoding::Utf8Bom),
+        None => (data, TextEncoding::Utf8),
+    };
+    match String::from_utf8(data) {
+        Ok(text) => DecodedText {
+            text,
+            encoding,
+            warning: None,
+        },
+        Err(e) => {
+            let offset = e.utf8_error().valid_up_to();
+            DecodedText {
+                text: e
+                    .into_bytes()
+                    .iter()
+                    .map(|byte| char::from(*byte))
+                    .collect(),
+                encoding: TextEncoding::Latin1,
+                warning: Some(format!(
+                    "The content is not valid UTF-8 (at byte {}) and was read as Latin-1; \
+                     some characters may be wrong",
+                    offset
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, big_endian: bool, bom: bool) -> Vec<u8> {
+        let bom = bom.then_some('\u{feff}');
+        bom.into_iter()
+            .chain(text.chars())
+            .collect::<String>()
+            .encode_utf16()
+            .flat_map(|unit| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        for (big_endian, bom) in [(false, true), (true, true), (false, false), (true, false)] {
+            let decoded = decode_text(utf16("{\"name\": \"café\"}", big_endian, bom));
+            assert_eq!(decoded.text, "{\"name\": \"café\"}");
+            let expected = if big_endian {
+                TextEncoding::Utf16Be
+            } else {
+                TextEncoding::Utf16Le
+            };
+            assert_eq!(decoded.encoding, expected);
+            assert!(decoded.warning.is_none());
+        }
+    }
+
+    #[test]
+    fn test_decode_utf8_and_bom() {
+        let decoded = decode_text("a: é".as_bytes().to_vec());
+        assert_eq!(
+            (decoded.text.as_str(), decoded.encoding),
+            ("a: é", TextEncoding::Utf8)
+        );
+
+        let decoded = decode_text(b"\xEF\xBB\xBF[1]".to_vec());
+        assert_eq!(decoded.text, "[1]");
+        assert_eq!(decoded.encoding, TextEncoding::Utf8Bom);
+        assert!(decoded.warning.is_none());
+    }
+
+    #[test]
+    fn test_decode_latin1_fallback() {
+        let decoded = decode_text(b"name: Jos\xE9\n".to_vec());
+        assert_eq!(decoded.text, "name: José\n");
+        assert_eq!(decoded.encoding, TextEncoding::Latin1);
+        assert!(decoded.warning.unwrap().contains("at byte 9"));
+    }
+}
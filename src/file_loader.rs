@@ -0,0 +1,298 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::document_store::{DecodedPage, PageDecoder};
+use crate::json_reader::{
+    parse_input, InputFormat, InputSource, ParseControl, ParseError, ParseProgress, ParsedInput,
+};
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Label, ProgressBar};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Messages sent from the worker thread back to the GTK main thread.
+enum LoadMessage {
+    Progress(ParseProgress),
+    Finished(Result<ParsedInput, ParseError>),
+    Decoded(Result<DecodedPage, ParseError>),
+}
+
+/// Callback invoked on the main thread with a decoded page, or `ParseError::Cancelled`.
+type PageDecoded = Box<dyn FnOnce(Result<DecodedPage, ParseError>)>;
+
+/// Work waiting for the worker thread.
+enum QueuedLoad {
+    /// A file or standard input to parse
+    File {
+        source: InputSource,
+        format: Option<InputFormat>,
+        name: String,
+    },
+    /// A page of rows of an open document to decode
+    Page {
+        decoder: PageDecoder,
+        name: String,
+        on_decoded: PageDecoded,
+    },
+}
+
+/// Callback invoked on the main thread with the result of a load and the file's display name.
+type LoadFinished = Box<dyn Fn(Result<ParsedInput, ParseError>, &str)>;
+
+/// Parses files, and decodes pages of rows of open files, on a worker thread,
+/// one at a time, showing progress in the window.
+///
+/// Work is queued, so files open in the order they were requested. Only the
+/// finished result is handed back to the main thread; a cancelled load never
+/// reaches the `on_finished` callback.
+pub struct FileLoader {
+    progress_toolbar: GtkBox,
+    progress_label: Label,
+    progress_bar: ProgressBar,
+    queue: RefCell<VecDeque<QueuedLoad>>,
+    current_cancel: RefCell<Option<Arc<AtomicBool>>>,
+    on_finished: LoadFinished,
+}
+
+impl FileLoader {
+    /// Creates a loader that reports progress in the given widgets.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress_toolbar` - Container shown while a load is in progress
+    /// * `progress_label` - Label showing the name of the file being loaded
+    /// * `progress_bar` - Progress bar showing how much of the file has been processed
+    /// * `on_finished` - Called with each parse result (including errors) and the display name
+    pub fn new(
+        progress_toolbar: GtkBox,
+        progress_label: Label,
+        progress_bar: ProgressBar,
+        on_finished: impl Fn(Result<ParsedInput, ParseError>, &str) + 'static,
+    ) -> Rc<Self> {
+        Rc::new(Self {
+            progress_toolbar,
+            progress_label,
+            progress_bar,
+            queue: RefCell::new(VecDeque::new()),
+            current_cancel: RefCell::new(None),
+            on_finished: Box::new(on_finished),
+        })
+    }
+
+    /// Queues a file or standard input to be parsed in the background.
+    ///
+    /// `format` overrides the format guessed from the file's extension.
+    pub fn load(self: &Rc<Self>, source: InputSource, format: Option<InputFormat>, name: String) {
+        self.queue.borrow_mut().push_back(QueuedLoad::File {
+            source,
+            format,
+            name,
+        });
+        if self.current_cancel.borrow().is_none() {
+            self.start_next();
+        }
+    }
+
+    /// Queues a page of rows of an open document to be decoded in the background.
+    ///
+    /// Unlike `on_finished` for a load, `on_decoded` is also called if the user
+    /// cancels, with `ParseError::Cancelled`, so that the caller can put back
+    /// whatever it showed while waiting for the page.
+    pub fn decode_page(
+        self: &Rc<Self>,
+        decoder: PageDecoder,
+        name: String,
+        on_decoded: impl FnOnce(Result<DecodedPage, ParseError>) + 'static,
+    ) {
+        self.queue.borrow_mut().push_back(QueuedLoad::Page {
+            decoder,
+            name,
+            on_decoded: Box::new(on_decoded),
+        });
+        if self.current_cancel.borrow().is_none() {
+            self.start_next();
+        }
+    }
+
+    /// Cancels the load or decode in progress. Queued work still runs.
+    pub fn cancel(&self) {
+        if let Some(cancelled) = self.current_cancel.borrow().as_ref() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn start_next(self: &Rc<Self>) {
+        let next = self.queue.borrow_mut().pop_front();
+        let Some(next) = next else {
+            *self.current_cancel.borrow_mut() = None;
+            self.progress_toolbar.set_visible(false);
+            return;
+        };
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        *self.current_cancel.borrow_mut() = Some(cancelled.clone());
+
+        self.progress_label.set_text(&match &next {
+            QueuedLoad::File { name, .. } => format!("Loading {}", name),
+            QueuedLoad::Page { name, .. } => format!("Decoding {}", name),
+        });
+        self.progress_bar.set_fraction(0.0);
+        self.progress_bar.set_text(Some(""));
+        self.progress_toolbar.set_no_show_all(false);
+        self.progress_toolbar.show_all();
+
+        let (sender, receiver) = async_channel::unbounded();
+
+        // Parse or decode on a worker thread; only plain data crosses back to the main thread
+        let cancelled_for_worker = cancelled.clone();
+        let (name, mut on_decoded) = match next {
+            QueuedLoad::File {
+                source,
+                format,
+                name,
+            } => {
+                std::thread::spawn(move || {
+                    let progress_sender = sender.clone();
+                    let control = ParseControl::new(cancelled_for_worker, move |progress| {
+                        let _ =
+                            progress_sender.send_blocking(LoadMessage::Progress(progress.clone()));
+                    });
+                    let result = parse_input(&source, format, &control);
+                    let _ = sender.send_blocking(LoadMessage::Finished(result));
+                });
+                (name, None)
+            }
+            QueuedLoad::Page {
+                decoder,
+                name,
+                on_decoded,
+            } => {
+                std::thread::spawn(move || {
+                    let control = ParseControl::new(cancelled_for_worker, |_| {});
+                    let _ = sender.send_blocking(LoadMessage::Decoded(decoder.decode(&control)));
+                });
+                (name, Some(on_decoded))
+            }
+        };
+
+        let loader = self.clone();
+        glib::spawn_future_local(async move {
+            while let Ok(message) = receiver.recv().await {
+                match message {
+                    LoadMessage::Progress(progress) => {
+                        if let Some(fraction) = progress_fraction(&progress) {
+                            loader.progress_bar.set_fraction(fraction);
+                        }
+                        loader
+                            .progress_bar
+                            .set_text(Some(&format_progress_text(&progress)));
+                    }
+                    LoadMessage::Finished(result) => {
+                        // Drop the result if the user cancelled after parsing finished
+                        if !cancelled.load(Ordering::Relaxed) {
+                            (loader.on_finished)(result, &name);
+                        }
+                        break;
+                    }
+                    LoadMessage::Decoded(result) => {
+                        let result = if cancelled.load(Ordering::Relaxed) {
+                            Err(ParseError::Cancelled)
+                        } else {
+                            result
+                        };
+                        if let Some(on_decoded) = on_decoded.take() {
+                            on_decoded(result);
+                        }
+                        break;
+                    }
+                }
+            }
+            loader.start_next();
+        });
+    }
+}
+
+/// Returns the fraction of the input processed, if the input size is known.
+fn progress_fraction(progress: &ParseProgress) -> Option<f64> {
+    match progress.total_bytes {
+        Some(total) if total > 0 => Some((progress.bytes_processed as f64 / total as f64).min(1.0)),
+        _ => None,
+    }
+}
+
+/// Formats a byte count using binary units (e.g. `1.5 MB`).
+fn format_byte_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Formats the text shown on the progress bar, e.g. `12.0 MB of 300.0 MB, 5000 rows`.
+fn format_progress_text(progress: &ParseProgress) -> String {
+    let mut text = match progress.total_bytes {
+        Some(total) => format!(
+            "{} of {}",
+            format_byte_size(progress.bytes_processed),
+            format_byte_size(total)
+        ),
+        None => format_byte_size(progress.bytes_processed),
+    };
+    if progress.rows_processed > 0 {
+        text.push_str(&format!(", {} rows", progress.rows_processed));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_progress_text() {
+        let progress = ParseProgress {
+            bytes_processed: 3 * 1024 * 1024,
+            total_bytes: Some(12 * 1024 * 1024),
+            rows_processed: 5000,
+        };
+        assert_eq!(
+            format_progress_text(&progress),
+            "3.0 MB of 12.0 MB, 5000 rows"
+        );
+        assert_eq!(progress_fraction(&progress), Some(0.25));
+    }
+
+    #[test]
+    fn test_format_progress_text_unknown_size() {
+        let progress = ParseProgress {
+            bytes_processed: 512,
+            total_bytes: None,
+            rows_processed: 0,
+        };
+        assert_eq!(format_progress_text(&progress), "512 B");
+        assert_eq!(progress_fraction(&progress), None);
+    }
+}
@@ -13,110 +13,620 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::archive::{Archive, ArchiveKind};
+use crate::arrow_file::ArrowFile;
+use crate::avro_file::AvroFile;
+use crate::binary_reader::{parse_bson_content, parse_cbor_content, parse_msgpack_content};
+use crate::compression::Compression;
+use crate::config_reader::{parse_json5_content, parse_toml_content, parse_yaml_content};
+use crate::content_sniffing::{is_utf16_file, sniff_content, sniff_file, ContentKind, SNIFF_BYTES};
+use crate::csv_reader::{parse_csv_content, CsvOptions};
+use crate::dataset::Dataset;
+use crate::encoding::{decode_text, TextEncoding};
+use crate::json_value::ValueOrigin;
+use crate::jsonl_index::JsonLIndex;
+use crate::jsonl_reader::{parse_json_collecting_warnings, parse_jsonl_content_with_control};
+use crate::notebook::{parse_notebook_content, Notebook};
+use crate::parquet_file::ParquetFile;
 use serde_json::Value;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Size of the chunks files are read in, between progress reports.
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// JSONL files at least this large are indexed and parsed line by line on demand.
+const INDEXED_JSONL_MIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Number of source lines shown before and after the line of a syntax error.
+const ERROR_CONTEXT_LINES: usize = 3;
 
 /// Result of parsing JSON/JSONL content
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub enum ParseResult {
     /// Single JSON object/array/value
     Single(Value),
-    /// JSONL format - multiple JSON objects, one per line
-    JsonL(Vec<Value>),
+    /// JSONL format - multiple JSON objects, one per line, plus the lines that failed to parse.
+    /// Also used for values written one after another without newlines between them.
+    JsonL {
+        values: Vec<Value>,
+        /// 1-based line of the content that each value starts on
+        lines: Vec<usize>,
+        errors: Vec<LineError>,
+        separator: JsonSeparator,
+    },
+    /// YAML stream with more than one `---`-separated document, in order
+    YamlStream(Vec<Value>),
+    /// Large JSONL file - only line offsets are read, lines are parsed when viewed
+    IndexedJsonL(JsonLIndex),
+    /// Parquet file - rows are decoded when they are viewed
+    PagedParquet(ParquetFile),
+    /// Arrow IPC or Feather file - rows are converted when they are viewed
+    PagedArrow(ArrowFile),
+    /// Avro container file - rows are decoded when they are viewed
+    PagedAvro(AvroFile),
+    /// Zip or tar archive - members are parsed when they are viewed
+    Archive(Archive),
+    /// Directory of Parquet or JSONL shards - rows are decoded when they are viewed
+    Dataset(Dataset),
+    /// Jupyter notebook, with its cells converted for viewing
+    Notebook(Notebook),
+}
+
+/// How the values of a `ParseResult::JsonL` were separated in the content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonSeparator {
+    /// One value per line (JSON Lines or NDJSON)
+    #[default]
+    Newline,
+    /// Values written one after another, such as `{...}{...}` or a series of
+    /// pretty-printed objects
+    Concatenated,
+    /// An RFC 7464 JSON text sequence, with each value preceded by an ASCII
+    /// record separator (0x1E)
+    RecordSeparator,
+}
+
+impl JsonSeparator {
+    /// Name shown next to the file name, as for a format.
+    pub fn label(self) -> &'static str {
+        match self {
+            JsonSeparator::Newline => InputFormat::JsonL.label(),
+            JsonSeparator::Concatenated => "concatenated JSON",
+            JsonSeparator::RecordSeparator => "JSON text sequence",
+        }
+    }
+}
+
+/// Parsed content from a file or standard input, with how it was read.
+#[derive(Debug)]
+pub struct ParsedInput {
+    pub content: ParseResult,
+    /// The format the content was parsed as, if known
+    pub format: Option<InputFormat>,
+    /// True if the format was guessed from the content rather than given or
+    /// taken from the file extension
+    pub format_detected: bool,
+    /// The codec the content was decompressed from, if it was compressed
+    pub compression: Option<Compression>,
+    /// The character encoding of text content, if it was decoded from bytes
+    pub encoding: Option<TextEncoding>,
+    /// Problems that did not stop the content from loading, such as duplicate keys
+    pub warnings: Vec<String>,
+}
+
+impl ParsedInput {
+    /// Content with nothing known about how it was read, such as pasted text.
+    /// The other fields are filled in with struct update syntax where known.
+    pub fn from_content(content: ParseResult) -> Self {
+        Self {
+            content,
+            format: None,
+            format_detected: false,
+            compression: None,
+            encoding: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Whether the values were read as JSON or converted from another format.
+    ///
+    /// Archives and datasets, which have no format of their own, say `Json`;
+    /// their members and shards are looked up one by one.
+    pub fn value_origin(&self) -> ValueOrigin {
+        match self.format {
+            Some(InputFormat::Json | InputFormat::JsonL) | None => ValueOrigin::Json,
+            Some(_) => ValueOrigin::Converted,
+        }
+    }
+
+    /// Name for the root node: `name` followed by the format for JSONL and for
+    /// formats guessed from the content, by the compression codec, and by the
+    /// text encoding unless it is plain UTF-8, as in "dump (YAML)",
+    /// "logs.jsonl.gz (JSONL, gzip)" or "export.json (UTF-16LE)". Values that
+    /// are not one per line are named by their separator instead, as in
+    /// "events.json (concatenated JSON)".
+    pub fn root_name(&self, name: &str) -> String {
+        let format = match &self.content {
+            ParseResult::JsonL { separator, .. } => Some(separator.label()),
+            ParseResult::IndexedJsonL(_) => Some(InputFormat::JsonL.label()),
+            _ if self.format_detected => self.format.map(InputFormat::label),
+            _ => None,
+        };
+        let labels: Vec<&str> = format
+            .into_iter()
+            .chain(self.compression.map(Compression::label))
+            .chain(
+                self.encoding
+                    .filter(|encoding| *encoding != TextEncoding::Utf8)
+                    .map(TextEncoding::label),
+            )
+            .collect();
+        if labels.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} ({})", name, labels.join(", "))
+        }
+    }
+}
+
+/// A file format that can be chosen with `--format` instead of guessed from the
+/// file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// A single JSON document, or JSONL if it has one value per line
+    Json,
+    /// JSON Lines - one value per line, never read as a single document
+    JsonL,
+    /// JSON5 or JSONC - JSON with comments, trailing commas and the other JSON5 extensions
+    Json5,
+    Yaml,
+    Toml,
+    /// Comma- or tab-separated values, read as an array of row objects
+    Csv(CsvOptions),
+    Parquet,
+    /// Arrow IPC file or stream, including Feather version 2
+    Arrow,
+    Avro,
+    MsgPack,
+    Cbor,
+    Bson,
+    /// Jupyter notebook, shown cell by cell
+    Notebook,
+}
+
+impl InputFormat {
+    /// Guesses the format from a file's extension.
+    ///
+    /// Returns `None` for unknown extensions, whose content is tried as JSON, JSON5, then YAML.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        extension.parse().ok()
+    }
+
+    /// Name of the format as shown in the tree.
+    pub fn label(self) -> &'static str {
+        match self {
+            InputFormat::Json => "JSON",
+            InputFormat::JsonL => "JSONL",
+            InputFormat::Json5 => "JSON5",
+            InputFormat::Yaml => "YAML",
+            InputFormat::Toml => "TOML",
+            InputFormat::Csv(options) if options.delimiter == b'\t' => "TSV",
+            InputFormat::Csv(_) => "CSV",
+            InputFormat::Parquet => "Parquet",
+            InputFormat::Arrow => "Arrow",
+            InputFormat::Avro => "Avro",
+            InputFormat::MsgPack => "MessagePack",
+            InputFormat::Cbor => "CBOR",
+            InputFormat::Bson => "BSON",
+            InputFormat::Notebook => "Jupyter notebook",
+        }
+    }
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "json" | "json-seq" => Ok(InputFormat::Json),
+            "jsonl" | "ndjson" => Ok(InputFormat::JsonL),
+            "json5" | "jsonc" => Ok(InputFormat::Json5),
+            "yaml" | "yml" => Ok(InputFormat::Yaml),
+            "toml" => Ok(InputFormat::Toml),
+            "csv" => Ok(InputFormat::Csv(CsvOptions::CSV)),
+            "tsv" | "tab" => Ok(InputFormat::Csv(CsvOptions::TSV)),
+            "parquet" => Ok(InputFormat::Parquet),
+            "arrow" | "arrows" | "feather" | "ipc" => Ok(InputFormat::Arrow),
+            "avro" => Ok(InputFormat::Avro),
+            "msgpack" | "mpk" => Ok(InputFormat::MsgPack),
+            "cbor" => Ok(InputFormat::Cbor),
+            "bson" => Ok(InputFormat::Bson),
+            "ipynb" | "notebook" => Ok(InputFormat::Notebook),
+            _ => Err(format!(
+                "unknown format '{}' (expected json, jsonl, json5, yaml, toml, csv, tsv, parquet, \
+                 arrow, avro, msgpack, cbor, bson or ipynb)",
+                name
+            )),
+        }
+    }
+}
+
+/// Where content to be parsed comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// A file, which may be a named pipe
+    File(PathBuf),
+    /// The process's standard input, given as `-` on the command line
+    Stdin,
+}
+
+/// A JSONL line that could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    /// 1-based line number in the content
+    pub line: usize,
+    /// Byte offset of the start of the line
+    pub offset: usize,
+    /// The JSON parser's error message
+    pub message: String,
+    /// Number of valid lines before this one, i.e. where it falls among the parsed values
+    pub index: usize,
 }
 
 /// Errors that can occur during JSON/JSONL/YAML/Parquet parsing
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
     /// Failed to parse as JSON or JSONL
-    InvalidJson(String),
+    InvalidJson(Box<SyntaxError>),
+    /// Failed to parse as JSON5 or JSONC
+    InvalidJson5(Box<SyntaxError>),
     /// Failed to parse as YAML
-    InvalidYaml(String),
+    InvalidYaml(Box<SyntaxError>),
+    /// Failed to parse as TOML
+    InvalidToml(Box<SyntaxError>),
+    /// Failed to parse as CSV or TSV
+    InvalidCsv(Box<SyntaxError>),
     /// Failed to parse as Parquet
     InvalidParquet(String),
+    /// Failed to parse as Arrow IPC
+    InvalidArrow(String),
+    /// Failed to parse as Avro
+    InvalidAvro(String),
+    /// Failed to parse as MessagePack
+    InvalidMsgPack(String),
+    /// Failed to parse as CBOR
+    InvalidCbor(String),
+    /// Failed to parse as BSON
+    InvalidBson(String),
     /// File I/O error
     IoError(String),
+    /// Parsing was cancelled before it finished
+    Cancelled,
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ParseError::InvalidJson(msg) => write!(f, "Invalid JSON: {}", msg),
-            ParseError::InvalidYaml(msg) => write!(f, "Invalid YAML: {}", msg),
+            ParseError::InvalidJson(error) => write!(f, "Invalid JSON: {}", error),
+            ParseError::InvalidJson5(error) => write!(f, "Invalid JSON5: {}", error),
+            ParseError::InvalidYaml(error) => write!(f, "Invalid YAML: {}", error),
+            ParseError::InvalidToml(error) => write!(f, "Invalid TOML: {}", error),
+            ParseError::InvalidCsv(error) => write!(f, "Invalid CSV: {}", error),
             ParseError::InvalidParquet(msg) => write!(f, "Invalid Parquet: {}", msg),
+            ParseError::InvalidArrow(msg) => write!(f, "Invalid Arrow: {}", msg),
+            ParseError::InvalidAvro(msg) => write!(f, "Invalid Avro: {}", msg),
+            ParseError::InvalidMsgPack(msg) => write!(f, "Invalid MessagePack: {}", msg),
+            ParseError::InvalidCbor(msg) => write!(f, "Invalid CBOR: {}", msg),
+            ParseError::InvalidBson(msg) => write!(f, "Invalid BSON: {}", msg),
             ParseError::IoError(msg) => write!(f, "I/O error: {}", msg),
+            ParseError::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
 
-/// Parses content as JSON or JSONL format.
-///
-/// First attempts to parse as JSONL (multiple JSON objects, one per line).
-/// If that fails, falls back to parsing as a single JSON document.
-///
-/// # Arguments
-///
-/// * `content` - The string content to parse
-///
-/// # Returns
-///
-/// * `Ok(ParseResult::JsonL(_))` if content is valid JSONL
-/// * `Ok(ParseResult::Single(_))` if content is valid single JSON
-/// * `Err(ParseError::InvalidJson(_))` if content is neither valid JSON nor JSONL
-pub fn parse_json_content(content: &str) -> Result<ParseResult, ParseError> {
-    let lines: Vec<&str> = content.lines().collect();
-
-    // Try JSONL format if there are multiple lines
-    if lines.len() > 1 {
-        let mut json_values: Vec<Value> = Vec::new();
-
-        for line in lines.iter() {
-            if line.trim().is_empty() {
-                continue;
+impl ParseError {
+    /// Returns the syntax error, if this is a syntax error in a text format.
+    pub fn syntax_error(&self) -> Option<&SyntaxError> {
+        match self {
+            ParseError::InvalidJson(error)
+            | ParseError::InvalidJson5(error)
+            | ParseError::InvalidYaml(error)
+            | ParseError::InvalidToml(error)
+            | ParseError::InvalidCsv(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Records the file the error came from.
+    pub fn with_path(mut self, path: &Path) -> Self {
+        if let ParseError::InvalidJson(error)
+        | ParseError::InvalidJson5(error)
+        | ParseError::InvalidYaml(error)
+        | ParseError::InvalidToml(error)
+        | ParseError::InvalidCsv(error) = &mut self
+        {
+            error.path = Some(path.to_path_buf());
+            // The file can be read again when it is needed
+            error.source_text = None;
+        }
+        self
+    }
+
+    /// Keeps the text with the error, for input that cannot be read again.
+    pub fn with_source_text(mut self, text: String) -> Self {
+        if let ParseError::InvalidJson(error)
+        | ParseError::InvalidJson5(error)
+        | ParseError::InvalidYaml(error)
+        | ParseError::InvalidToml(error)
+        | ParseError::InvalidCsv(error) = &mut self
+        {
+            error.source_text = Some(text);
+        }
+        self
+    }
+
+    /// Records the codec the text with the error was decompressed from.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        if let ParseError::InvalidJson(error)
+        | ParseError::InvalidJson5(error)
+        | ParseError::InvalidYaml(error)
+        | ParseError::InvalidToml(error)
+        | ParseError::InvalidCsv(error) = &mut self
+        {
+            error.compression = Some(compression);
+        }
+        self
+    }
+}
+
+/// A position in parsed text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceLocation {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in bytes
+    pub column: usize,
+    /// Byte offset from the start of the text
+    pub offset: usize,
+}
+
+/// A JSON or YAML syntax error, with where it occurred and the source around it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    /// The parser's error message
+    pub message: String,
+    /// Where the error occurred, if the parser reported it
+    pub location: Option<SourceLocation>,
+    /// The file that was being parsed, if any
+    pub path: Option<PathBuf>,
+    /// The codec the file was decompressed from, if it was compressed
+    pub compression: Option<Compression>,
+    /// The whole text that was parsed, for input without a file such as
+    /// standard input
+    pub source_text: Option<String>,
+    /// Source lines around the error, the first being line `context_start_line`
+    pub context: Vec<String>,
+    pub context_start_line: usize,
+}
+
+impl SyntaxError {
+    /// Creates an error for `content`, keeping the lines around `line` and `column`.
+    ///
+    /// A line of 0 means the parser did not report a location.
+    fn new(message: String, content: &str, line: usize, column: usize) -> Self {
+        if line == 0 {
+            return Self {
+                message,
+                location: None,
+                path: None,
+                compression: None,
+                source_text: None,
+                context: Vec::new(),
+                context_start_line: 0,
+            };
+        }
+
+        let line_start: usize = content
+            .split_inclusive('\n')
+            .take(line - 1)
+            .map(str::len)
+            .sum();
+        let column = column.max(1);
+        let offset = (line_start + column - 1).min(content.len());
+        let context_start_line = line.saturating_sub(ERROR_CONTEXT_LINES).max(1);
+        let context = content
+            .lines()
+            .skip(context_start_line - 1)
+            .take(line + ERROR_CONTEXT_LINES + 1 - context_start_line)
+            .map(str::to_string)
+            .collect();
+
+        Self {
+            message,
+            location: Some(SourceLocation {
+                line,
+                column,
+                offset,
+            }),
+            path: None,
+            compression: None,
+            source_text: None,
+            context,
+            context_start_line,
+        }
+    }
+
+    /// Creates an error from a serde_json error raised while parsing `content`.
+    pub fn from_json(error: &serde_json::Error, content: &str) -> Self {
+        Self::new(error.to_string(), content, error.line(), error.column())
+    }
+
+    /// Creates an error from a json5 error raised while parsing `content`.
+    pub fn from_json5(error: &json5::Error, content: &str) -> Self {
+        let json5::Error::Message { msg, location } = error;
+        match location {
+            Some(location) => Self::new(msg.clone(), content, location.line, location.column),
+            None => Self::new(msg.clone(), content, 0, 0),
+        }
+    }
+
+    /// Creates an error from a serde_yaml error raised while parsing `content`.
+    pub fn from_yaml(error: &serde_yaml::Error, content: &str) -> Self {
+        match error.location() {
+            Some(location) => Self::new(
+                error.to_string(),
+                content,
+                location.line(),
+                location.column(),
+            ),
+            None => Self::new(error.to_string(), content, 0, 0),
+        }
+    }
+
+    /// Creates an error from a toml error raised while parsing `content`.
+    pub fn from_toml(error: &toml::de::Error, content: &str) -> Self {
+        let message = error.message().to_string();
+        match error.span() {
+            Some(span) => {
+                let offset = span.start.min(content.len());
+                let before = &content[..offset];
+                let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                let line = before.matches('\n').count() + 1;
+                Self::new(message, content, line, offset - line_start + 1)
             }
-            match serde_json::from_str::<Value>(line) {
-                Ok(value) => json_values.push(value),
-                Err(_) => {
-                    // If any line fails, try as single JSON instead
-                    json_values.clear();
-                    break;
-                }
+            None => Self::new(message, content, 0, 0),
+        }
+    }
+
+    /// Creates an error from a csv error raised while parsing `content`.
+    ///
+    /// The message starts with the record number, the header being record 0.
+    pub fn from_csv(error: &csv::Error, content: &str) -> Self {
+        let message = match error.kind() {
+            csv::ErrorKind::UnequalLengths {
+                expected_len, len, ..
+            } => format!("expected {} fields but found {}", expected_len, len),
+            csv::ErrorKind::Utf8 { err, .. } => {
+                format!("invalid UTF-8 in field {}", err.field() + 1)
             }
+            _ => error.to_string(),
+        };
+        match error.position() {
+            Some(position) => Self::new(
+                format!("record {}: {}", position.record(), message),
+                content,
+                position.line() as usize,
+                1,
+            ),
+            None => Self::new(message, content, 0, 0),
         }
+    }
 
-        if !json_values.is_empty() {
-            return Ok(ParseResult::JsonL(json_values));
+    /// Formats the source lines around the error, marking the error's position.
+    ///
+    /// ```text
+    ///    2 |   "a": 1,
+    /// >  3 |   "b": ,
+    ///      |        ^
+    ///    4 | }
+    /// ```
+    pub fn source_preview(&self) -> String {
+        let Some(location) = self.location else {
+            return String::new();
+        };
+        let last_line = self.context_start_line + self.context.len().saturating_sub(1);
+        let width = last_line.max(location.line).to_string().len();
+
+        let mut preview = String::new();
+        for (idx, text) in self.context.iter().enumerate() {
+            let line = self.context_start_line + idx;
+            let marker = if line == location.line { ">" } else { " " };
+            preview.push_str(&format!("{} {:>width$} | {}\n", marker, line, text));
+            if line == location.line {
+                // Columns are in bytes; pad by the characters before the error
+                let prefix = text.get(..location.column - 1).unwrap_or(text);
+                preview.push_str(&format!(
+                    "  {:>width$} | {}^\n",
+                    "",
+                    " ".repeat(prefix.chars().count())
+                ));
+            }
         }
+        preview
     }
+}
 
-    // Try to parse as single JSON
-    match serde_json::from_str::<Value>(content) {
-        Ok(value) => Ok(ParseResult::Single(value)),
-        Err(e) => Err(ParseError::InvalidJson(e.to_string())),
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(path) = &self.path {
+            write!(f, " in {}", path.display())?;
+        }
+        Ok(())
     }
 }
 
-/// Parses YAML content and converts it to JSON format.
-///
-/// # Arguments
-///
-/// * `content` - The YAML string content to parse
-///
-/// # Returns
+/// Progress of a parse, reported periodically while a file is read and converted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseProgress {
+    /// Bytes of input consumed so far
+    pub bytes_processed: u64,
+    /// Total input size in bytes, if known
+    pub total_bytes: Option<u64>,
+    /// Rows (JSONL lines or Parquet rows) converted so far
+    pub rows_processed: usize,
+}
+
+/// Lets the caller of a long-running parse observe its progress and cancel it.
 ///
-/// * `Ok(ParseResult::Single(_))` if content is valid YAML
-/// * `Err(ParseError::InvalidYaml(_))` if content is not valid YAML
-pub fn parse_yaml_content(content: &str) -> Result<ParseResult, ParseError> {
-    match serde_yaml::from_str::<Value>(content) {
-        Ok(value) => Ok(ParseResult::Single(value)),
-        Err(e) => Err(ParseError::InvalidYaml(e.to_string())),
+/// The parse checks for cancellation between chunks, lines and record batches,
+/// and returns `ParseError::Cancelled` as soon as it notices.
+pub struct ParseControl {
+    cancelled: Arc<AtomicBool>,
+    on_progress: Box<dyn Fn(&ParseProgress)>,
+}
+
+impl ParseControl {
+    /// Creates a control that reports progress to `on_progress` and stops once `cancelled` is set.
+    pub fn new(cancelled: Arc<AtomicBool>, on_progress: impl Fn(&ParseProgress) + 'static) -> Self {
+        Self {
+            cancelled,
+            on_progress: Box::new(on_progress),
+        }
+    }
+
+    pub fn check_cancelled(&self) -> Result<(), ParseError> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            Err(ParseError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn report(&self, progress: ParseProgress) {
+        (self.on_progress)(&progress);
     }
 }
 
-/// Parses Parquet file content and converts it to JSON format.
+impl Default for ParseControl {
+    /// A control that is never cancelled and ignores progress.
+    fn default() -> Self {
+        Self::new(Arc::new(AtomicBool::new(false)), |_| {})
+    }
+}
+
+/// Parses Parquet file content that is already in memory.
 ///
-/// Parquet files are converted to an array of objects, where each object represents a row.
+/// Only the footer is decoded here; rows are converted to JSON objects as they
+/// are read from the returned `ParquetFile`.
 ///
 /// # Arguments
 ///
@@ -124,161 +634,397 @@ pub fn parse_yaml_content(content: &str) -> Result<ParseResult, ParseError> {
 ///
 /// # Returns
 ///
-/// * `Ok(ParseResult::Single(_))` if content is valid Parquet (returns array of row objects)
+/// * `Ok(ParseResult::PagedParquet(_))` if content is valid Parquet
 /// * `Err(ParseError::InvalidParquet(_))` if content is not valid Parquet
-pub fn parse_parquet_content(data: &[u8]) -> Result<ParseResult, ParseError> {
-    use arrow::array::Array;
-    use bytes::Bytes;
-    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-
-    // Convert to Bytes which implements ChunkReader
-    let owned_data = data.to_vec();
-    let bytes_data = Bytes::from(owned_data);
-    let builder = ParquetRecordBatchReaderBuilder::try_new(bytes_data)
-        .map_err(|e: parquet::errors::ParquetError| ParseError::InvalidParquet(e.to_string()))?;
-
-    let reader = builder
-        .build()
-        .map_err(|e: parquet::errors::ParquetError| ParseError::InvalidParquet(e.to_string()))?;
-
-    let mut all_rows: Vec<Value> = Vec::new();
-
-    for batch_result in reader {
-        let batch = batch_result
-            .map_err(|e: arrow::error::ArrowError| ParseError::InvalidParquet(e.to_string()))?;
-
-        // Convert each row in the batch to a JSON object
-        let num_rows = batch.num_rows();
-        let num_cols = batch.num_columns();
-        let schema = batch.schema();
-
-        for row_idx in 0..num_rows {
-            let mut row_obj = serde_json::Map::new();
-
-            for col_idx in 0..num_cols {
-                let column = batch.column(col_idx);
-                let field_name = schema.field(col_idx).name().to_string();
-
-                // Extract value from the column at this row
-                let value = if column.is_null(row_idx) {
-                    Value::Null
-                } else {
-                    match column.data_type() {
-                        arrow::datatypes::DataType::Utf8 => {
-                            let array = column
-                                .as_any()
-                                .downcast_ref::<arrow::array::StringArray>()
-                                .ok_or_else(|| {
-                                    ParseError::InvalidParquet(
-                                        "Failed to cast string array".to_string(),
-                                    )
-                                })?;
-                            Value::String(array.value(row_idx).to_string())
-                        }
-                        arrow::datatypes::DataType::Int64 => {
-                            let array = column
-                                .as_any()
-                                .downcast_ref::<arrow::array::Int64Array>()
-                                .ok_or_else(|| {
-                                    ParseError::InvalidParquet(
-                                        "Failed to cast int64 array".to_string(),
-                                    )
-                                })?;
-                            Value::Number(array.value(row_idx).into())
-                        }
-                        arrow::datatypes::DataType::Float64 => {
-                            let array = column
-                                .as_any()
-                                .downcast_ref::<arrow::array::Float64Array>()
-                                .ok_or_else(|| {
-                                    ParseError::InvalidParquet(
-                                        "Failed to cast float64 array".to_string(),
-                                    )
-                                })?;
-                            Value::Number(
-                                serde_json::Number::from_f64(array.value(row_idx))
-                                    .unwrap_or_else(|| serde_json::Number::from(0)),
-                            )
-                        }
-                        arrow::datatypes::DataType::Boolean => {
-                            let array = column
-                                .as_any()
-                                .downcast_ref::<arrow::array::BooleanArray>()
-                                .ok_or_else(|| {
-                                    ParseError::InvalidParquet(
-                                        "Failed to cast boolean array".to_string(),
-                                    )
-                                })?;
-                            Value::Bool(array.value(row_idx))
-                        }
-                        _ => {
-                            // For other types, convert to string representation
-                            Value::String(format!("{:?}", column))
-                        }
-                    }
-                };
-
-                row_obj.insert(field_name, value);
-            }
+pub fn parse_parquet_content(data: Vec<u8>) -> Result<ParseResult, ParseError> {
+    ParquetFile::from_bytes(bytes::Bytes::from(data)).map(ParseResult::PagedParquet)
+}
 
-            all_rows.push(Value::Object(row_obj));
-        }
-    }
+/// Parses Arrow IPC file or stream content, which includes Feather version 2.
+///
+/// The record batches are read here; rows are converted to JSON objects, as
+/// Parquet rows are, as they are read from the returned `ArrowFile`.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::PagedArrow(_))` if content is valid Arrow IPC
+/// * `Err(ParseError::InvalidArrow(_))` if content is not valid Arrow IPC
+pub fn parse_arrow_content(data: Vec<u8>) -> Result<ParseResult, ParseError> {
+    ArrowFile::from_bytes(data).map(ParseResult::PagedArrow)
+}
 
-    Ok(ParseResult::Single(Value::Array(all_rows)))
+/// Parses Avro object container file content.
+///
+/// Only the header and block positions are read here; rows are decoded as
+/// they are read from the returned `AvroFile`.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::PagedAvro(_))` if content is a valid Avro file
+/// * `Err(ParseError::InvalidAvro(_))` if content is not a valid Avro file
+pub fn parse_avro_content(data: Vec<u8>) -> Result<ParseResult, ParseError> {
+    AvroFile::from_bytes(data).map(ParseResult::PagedAvro)
 }
 
-/// Parses a file based on its extension.
+/// Parses a file based on its extension, or in the given format.
 ///
 /// Supports:
-/// - `.json`, `.jsonl` - JSON/JSONL format
+/// - `.json` - JSON, or JSONL if the file has one value per line
+/// - `.jsonl`, `.ndjson` - JSONL format (large JSONL files are indexed, see `JsonLIndex`)
 /// - `.yaml`, `.yml` - YAML format
-/// - `.parquet` - Parquet format
+/// - `.parquet` - Parquet format (opened for paging, see `ParquetFile`)
+/// - `.arrow`, `.feather` - Arrow IPC format (see `ArrowFile`)
+/// - `.avro` - Avro container files (see `AvroFile`)
+/// - `.msgpack`, `.mpk`, `.cbor`, `.bson` - binary formats, see `binary_reader`
+/// - `.gz`, `.zst`, `.bz2`, `.xz` - decompressed, then parsed by the extension before it
+/// - `.zip`, `.tar`, `.tar.gz`, `.tgz` and other compressed tar files - members listed, see `Archive`
+///
+/// Files with other extensions are recognized from their content, see `sniff_content`.
+/// A directory is opened as a dataset of Parquet or JSONL shards, see `Dataset`.
+///
+/// This is meant to run on a worker thread: progress is reported while the file
+/// is read and again while it is converted.
 ///
 /// # Arguments
 ///
-/// * `path` - Path to the file
+/// * `path` - Path to the file, which may be a named pipe
+/// * `format` - Format to parse the file as, overriding its extension
+/// * `control` - Receives progress reports and signals cancellation
 ///
 /// # Returns
 ///
 /// * `Ok(ParseResult)` if file was successfully parsed
+/// * `Err(ParseError::Cancelled)` if the parse was cancelled
 /// * `Err(ParseError)` if parsing failed or file type is unsupported
-pub fn parse_file(path: &Path) -> Result<ParseResult, ParseError> {
-    use std::fs;
+pub fn parse_file(
+    path: &Path,
+    format: Option<InputFormat>,
+    control: &ParseControl,
+) -> Result<ParsedInput, ParseError> {
+    if path.is_dir() {
+        return Dataset::open(path, control)
+            .map(|dataset| ParsedInput::from_content(ParseResult::Dataset(dataset)))
+            .map_err(|e| e.with_path(path));
+    }
+    if let Some((kind, compression)) = ArchiveKind::from_path(path) {
+        return Archive::open(path, kind, compression, control)
+            .map(|archive| ParsedInput {
+                compression,
+                ..ParsedInput::from_content(ParseResult::Archive(archive))
+            })
+            .map_err(|e| e.with_path(path));
+    }
+
+    let result = match Compression::from_path(path) {
+        // The extension inside the compression one names the format
+        Some((compression, inner_path)) => parse_compressed_file(
+            path,
+            compression,
+            format.or_else(|| InputFormat::from_path(&inner_path)),
+            control,
+        ),
+        None => parse_file_as(
+            path,
+            format.or_else(|| InputFormat::from_path(path)),
+            control,
+        ),
+    };
+    result.map_err(|e| e.with_path(path))
+}
+
+/// Decompresses a file as it is read, then parses the decompressed content.
+fn parse_compressed_file(
+    path: &Path,
+    compression: Compression,
+    format: Option<InputFormat>,
+    control: &ParseControl,
+) -> Result<ParsedInput, ParseError> {
+    let file = std::fs::File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+    let decoder = compression.decoder(std::io::BufReader::new(file))?;
+    let data = read_all(decoder, None, control)?;
+    let mut parsed =
+        parse_bytes(data, format, control).map_err(|e| e.with_compression(compression))?;
+    parsed.compression = Some(compression);
+    Ok(parsed)
+}
+
+fn parse_file_as(
+    path: &Path,
+    format: Option<InputFormat>,
+    control: &ParseControl,
+) -> Result<ParsedInput, ParseError> {
+    let regular_file = std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file());
+
+    let paged_format = match format {
+        // Lines are found by scanning bytes, so UTF-16 files are decoded in memory
+        Some(InputFormat::JsonL)
+            if is_large_file(path, INDEXED_JSONL_MIN_BYTES) && !is_utf16_file(path)? =>
+        {
+            format
+        }
+        Some(InputFormat::Parquet) if regular_file => format,
+        // Only the start and end of an unknown file are read to spot Parquet
+        None if regular_file && sniff_file(path)? == ContentKind::Parquet => {
+            Some(InputFormat::Parquet)
+        }
+        _ => None,
+    };
+    match paged_format {
+        Some(InputFormat::JsonL) => {
+            let index = JsonLIndex::build(path, control)?;
+            return Ok(ParsedInput {
+                format: paged_format,
+                ..ParsedInput::from_content(ParseResult::IndexedJsonL(index))
+            });
+        }
+        Some(InputFormat::Parquet) => {
+            // Only the footer is read here; row groups are decoded as they are viewed
+            control.check_cancelled()?;
+            return Ok(ParsedInput {
+                format: paged_format,
+                format_detected: format.is_none(),
+                ..ParsedInput::from_content(ParseResult::PagedParquet(ParquetFile::open(path)?))
+            });
+        }
+        _ => {}
+    }
+
+    // Pipes can't be sized or read out of order, so they are read into memory
+    let file = std::fs::File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+    let total_bytes = regular_file
+        .then(|| file.metadata().ok().map(|metadata| metadata.len()))
+        .flatten();
+    let data = read_all(file, total_bytes, control)?;
+    parse_bytes(data, format, control)
+}
+
+/// Parses a file or standard input, see `parse_file` and `parse_reader`.
+pub fn parse_input(
+    source: &InputSource,
+    format: Option<InputFormat>,
+    control: &ParseControl,
+) -> Result<ParsedInput, ParseError> {
+    match source {
+        InputSource::File(path) => parse_file(path, format, control),
+        InputSource::Stdin => parse_reader(std::io::stdin().lock(), format, control),
+    }
+}
+
+/// Parses a file read from an archive, by the extensions of its `name`.
+///
+/// Compressed members are decompressed first; members with unknown extensions
+/// are recognized from their content.
+pub fn parse_member(name: &str, data: Vec<u8>) -> Result<ParsedInput, ParseError> {
+    let control = ParseControl::default();
+    let path = Path::new(name);
+    match Compression::from_path(path) {
+        Some((compression, inner_path)) => {
+            let data = read_all(compression.decoder(data.as_slice())?, None, &control)?;
+            let mut parsed = parse_bytes(data, InputFormat::from_path(&inner_path), &control)
+                .map_err(|e| e.with_compression(compression))?;
+            parsed.compression = Some(compression);
+            Ok(parsed)
+        }
+        None => parse_bytes(data, InputFormat::from_path(path), &control),
+    }
+}
+
+/// Reads and parses everything from `reader`, such as standard input.
+///
+/// Without a `format`, the format is guessed from the content.
+pub fn parse_reader(
+    reader: impl std::io::Read,
+    format: Option<InputFormat>,
+    control: &ParseControl,
+) -> Result<ParsedInput, ParseError> {
+    let data = read_all(reader, None, control)?;
+    parse_bytes(data, format, control)
+}
+
+/// Parses content that has been read into memory in the given format, or in
+/// the format guessed by `sniff_content`.
+///
+/// Compressed content is decompressed first whatever the format, since piped
+/// input such as `curl ... | slopjson --format json -` can still be gzipped.
+fn parse_bytes(
+    data: Vec<u8>,
+    format: Option<InputFormat>,
+    control: &ParseControl,
+) -> Result<ParsedInput, ParseError> {
+    let kind = match (Compression::from_magic(&data), format) {
+        (Some(compression), _) => ContentKind::Compressed(compression),
+        (None, Some(InputFormat::Parquet)) => ContentKind::Parquet,
+        (None, Some(InputFormat::Arrow)) => ContentKind::Arrow,
+        (None, Some(InputFormat::Avro)) => ContentKind::Avro,
+        (None, Some(_)) => ContentKind::Text,
+        (None, None) => sniff_content(&data[..data.len().min(SNIFF_BYTES)], &data),
+    };
+    let binary_content = match (kind, format) {
+        (ContentKind::Compressed(_), _) => None,
+        (_, Some(InputFormat::MsgPack)) => Some(parse_msgpack_content(&data, control)),
+        (_, Some(InputFormat::Cbor)) => Some(parse_cbor_content(&data, control)),
+        (_, Some(InputFormat::Bson)) => Some(parse_bson_content(&data, control)),
+        _ => None,
+    };
+    if let Some(content) = binary_content {
+        return Ok(ParsedInput {
+            format,
+            ..ParsedInput::from_content(content?)
+        });
+    }
+
+    match kind {
+        ContentKind::Parquet => {
+            control.check_cancelled()?;
+            return Ok(ParsedInput {
+                format: Some(InputFormat::Parquet),
+                format_detected: format.is_none(),
+                ..ParsedInput::from_content(parse_parquet_content(data)?)
+            });
+        }
+        ContentKind::Arrow | ContentKind::Avro => {
+            control.check_cancelled()?;
+            let (content, parsed_format) = if kind == ContentKind::Arrow {
+                (parse_arrow_content(data)?, InputFormat::Arrow)
+            } else {
+                (parse_avro_content(data)?, InputFormat::Avro)
+            };
+            return Ok(ParsedInput {
+                format: Some(parsed_format),
+                format_detected: format.is_none(),
+                ..ParsedInput::from_content(content)
+            });
+        }
+        ContentKind::Compressed(compression) => {
+            let decoder = compression.decoder(data.as_slice())?;
+            let data = read_all(decoder, None, control)?;
+            let mut parsed =
+                parse_bytes(data, format, control).map_err(|e| e.with_compression(compression))?;
+            parsed.compression = Some(compression);
+            return Ok(parsed);
+        }
+        ContentKind::Binary => {
+            return Err(ParseError::IoError(
+                "the content is binary and not in a supported format".to_string(),
+            ));
+        }
+        ContentKind::Json | ContentKind::Yaml | ContentKind::Text => {}
+    }
 
-    let extension = path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+    let decoded = decode_text(data);
+    let mut warnings: Vec<String> = decoded.warning.into_iter().collect();
+    let (content, parsed_format) =
+        match parse_text_as(&decoded.text, format, kind, control, &mut warnings) {
+            Ok(result) => result,
+            // Kept for Jump to Error, as standard input cannot be read again
+            Err(e) => return Err(e.with_source_text(decoded.text)),
+        };
+    Ok(ParsedInput {
+        format: Some(parsed_format),
+        format_detected: format != Some(parsed_format),
+        encoding: Some(decoded.encoding),
+        warnings,
+        ..ParsedInput::from_content(content)
+    })
+}
 
-    match extension.as_str() {
-        "json" | "jsonl" => {
-            let content =
-                fs::read_to_string(path).map_err(|e| ParseError::IoError(e.to_string()))?;
-            parse_json_content(&content)
+/// Parses decoded text in the given format, or in the format its content
+/// looks like, returning the format it was parsed as.
+fn parse_text_as(
+    content: &str,
+    format: Option<InputFormat>,
+    kind: ContentKind,
+    control: &ParseControl,
+    warnings: &mut Vec<String>,
+) -> Result<(ParseResult, InputFormat), ParseError> {
+    Ok(match (format, kind) {
+        // Files such as tsconfig.json often have comments, so JSON that
+        // fails to parse is retried as JSON5
+        (Some(InputFormat::Json), _) => {
+            match parse_json_collecting_warnings(content, control, warnings) {
+                Ok(result) => (result, InputFormat::Json),
+                Err(ParseError::InvalidJson(json_error)) => match parse_json5_content(content) {
+                    Ok(result) => (result, InputFormat::Json5),
+                    Err(_) => return Err(ParseError::InvalidJson(json_error)),
+                },
+                Err(e) => return Err(e),
+            }
         }
-        "yaml" | "yml" => {
-            let content =
-                fs::read_to_string(path).map_err(|e| ParseError::IoError(e.to_string()))?;
-            parse_yaml_content(&content)
+        (Some(InputFormat::JsonL), _) => (
+            parse_jsonl_content_with_control(content, control, warnings)?,
+            InputFormat::JsonL,
+        ),
+        (Some(InputFormat::Json5), _) => {
+            control.check_cancelled()?;
+            (parse_json5_content(content)?, InputFormat::Json5)
         }
-        "parquet" => {
-            let data = fs::read(path).map_err(|e| ParseError::IoError(e.to_string()))?;
-            parse_parquet_content(&data)
+        (Some(InputFormat::Csv(options)), _) => (
+            parse_csv_content(content, options, control)?,
+            InputFormat::Csv(options),
+        ),
+        (Some(InputFormat::Toml), _) => {
+            control.check_cancelled()?;
+            (parse_toml_content(content)?, InputFormat::Toml)
         }
-        _ => {
-            // Try to auto-detect: first try as JSON, then YAML
-            let content =
-                fs::read_to_string(path).map_err(|e| ParseError::IoError(e.to_string()))?;
-            parse_text_content(&content)
+        (Some(InputFormat::Notebook), _) => {
+            control.check_cancelled()?;
+            (
+                parse_notebook_content(content, warnings)?,
+                InputFormat::Notebook,
+            )
         }
+        (Some(InputFormat::Yaml), _) | (None, ContentKind::Yaml) => {
+            control.check_cancelled()?;
+            (parse_yaml_content(content)?, InputFormat::Yaml)
+        }
+        (_, kind) => {
+            parse_text_detecting_format(content, control, kind == ContentKind::Json, warnings)?
+        }
+    })
+}
+
+/// Returns true if `path` is a regular file of at least `min_bytes` bytes.
+fn is_large_file(path: &Path, min_bytes: u64) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() >= min_bytes)
+}
+
+/// Reads everything from `reader` in chunks, reporting progress after each one.
+///
+/// `total_bytes` is the expected size, if known, for the progress reports.
+pub fn read_all(
+    mut reader: impl std::io::Read,
+    total_bytes: Option<u64>,
+    control: &ParseControl,
+) -> Result<Vec<u8>, ParseError> {
+    let mut data = Vec::with_capacity(total_bytes.unwrap_or(0) as usize);
+    let mut chunk = vec![0; READ_CHUNK_SIZE];
+
+    loop {
+        control.check_cancelled()?;
+        let read = match reader.read(&mut chunk) {
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(ParseError::IoError(e.to_string())),
+        };
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&chunk[..read]);
+        control.report(ParseProgress {
+            bytes_processed: data.len() as u64,
+            total_bytes,
+            rows_processed: 0,
+        });
     }
+
+    Ok(data)
 }
 
-/// Parses text content, trying JSON/JSONL first, then YAML.
+/// Parses text content, trying JSON/JSONL first, then JSON5, then YAML.
 ///
 /// This is useful for clipboard content or other text sources where the format is unknown.
+/// JSON5 comes before YAML so that JSON with comments or trailing commas is
+/// not misread as YAML.
 ///
 /// # Arguments
 ///
@@ -289,153 +1035,454 @@ pub fn parse_file(path: &Path) -> Result<ParseResult, ParseError> {
 /// * `Ok(ParseResult)` if content was successfully parsed as JSON/JSONL or YAML
 /// * `Err(ParseError)` if parsing failed for all formats
 pub fn parse_text_content(content: &str) -> Result<ParseResult, ParseError> {
+    parse_text_input(content).map(|parsed| parsed.content)
+}
+
+/// Parses text content like `parse_text_content`, keeping the warnings for
+/// duplicate keys in JSON.
+pub fn parse_text_input(content: &str) -> Result<ParsedInput, ParseError> {
+    let mut warnings = Vec::new();
+    let (result, format) =
+        parse_text_detecting_format(content, &ParseControl::default(), false, &mut warnings)?;
+    Ok(ParsedInput {
+        format: Some(format),
+        warnings,
+        ..ParsedInput::from_content(result)
+    })
+}
+
+/// Parses text as JSON/JSONL, then as JSON5, then as YAML, returning the
+/// format that worked. Duplicate keys in JSON are added to `warnings`.
+///
+/// If none works, the JSON error is returned when the text looked like
+/// JSON, and the YAML error otherwise.
+fn parse_text_detecting_format(
+    content: &str,
+    control: &ParseControl,
+    looks_like_json: bool,
+    warnings: &mut Vec<String>,
+) -> Result<(ParseResult, InputFormat), ParseError> {
     // Try JSON/JSONL first
-    if let Ok(result) = parse_json_content(content) {
-        return Ok(result);
+    let json_error = match parse_json_collecting_warnings(content, control, warnings) {
+        Ok(result) => return Ok((result, InputFormat::Json)),
+        Err(ParseError::Cancelled) => return Err(ParseError::Cancelled),
+        Err(e) => e,
+    };
+
+    // Then JSON5, for JSON with comments or trailing commas
+    control.check_cancelled()?;
+    if let Ok(result) = parse_json5_content(content) {
+        return Ok((result, InputFormat::Json5));
     }
 
-    // Then try YAML
-    parse_yaml_content(content)
+    // Then try YAML, which also covers flow mappings like `{a: 1}`
+    control.check_cancelled()?;
+    match parse_yaml_content(content) {
+        Ok(result) => Ok((result, InputFormat::Yaml)),
+        Err(_) if looks_like_json => Err(json_error),
+        Err(e) => Err(e),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::json_value::parse_json_document;
+    use crate::jsonl_reader::{parse_json_content, parse_json_content_with_control};
 
     #[test]
-    fn test_parse_single_json_object() {
-        let content = r#"{"name": "test", "value": 42}"#;
-        let result = parse_json_content(content).unwrap();
-
-        match result {
-            ParseResult::Single(value) => {
-                assert_eq!(value["name"], "test");
-                assert_eq!(value["value"], 42);
-            }
-            _ => panic!("Expected Single result"),
-        }
+    fn test_parse_json_text_sequence() {
+        let content = "\u{1e}{\"a\": 1}\n\u{1e}{\"a\": \n\u{1e}[2]\n";
+        let parsed = parse_text_input(content).unwrap();
+        assert_eq!(parsed.root_name("-"), "- (JSON text sequence)");
+        let ParseResult::JsonL {
+            values,
+            errors,
+            separator,
+            ..
+        } = parsed.content
+        else {
+            panic!("Expected JsonL result");
+        };
+        assert_eq!(separator, JsonSeparator::RecordSeparator);
+        assert_eq!(
+            values,
+            [serde_json::json!({"a": 1}), serde_json::json!([2])]
+        );
+        assert_eq!(
+            (errors[0].line, errors[0].offset, errors[0].index),
+            (2, 10, 1)
+        );
+
+        assert_eq!(sniff_content(content.as_bytes(), &[]), ContentKind::Json);
     }
 
     #[test]
-    fn test_parse_single_json_array() {
-        let content = r#"[1, 2, 3, "four"]"#;
-        let result = parse_json_content(content).unwrap();
+    fn test_syntax_error_location_and_preview() {
+        let content = "{\n  \"a\": 1,\n  \"b\": ,\n  \"c\": 3\n}\n";
+        let error = match parse_json_content(content) {
+            Err(ParseError::InvalidJson(error)) => error,
+            other => panic!("Expected InvalidJson error, got {:?}", other),
+        };
+
+        let location = error.location.unwrap();
+        assert_eq!(location.line, 3);
+        assert_eq!(location.column, 8);
+        assert_eq!(&content[location.offset..location.offset + 1], ",");
+        assert_eq!(error.context_start_line, 1);
+        assert_eq!(error.context.len(), 5);
+        assert_eq!(
+            error.source_preview(),
+            "  1 | {\n  2 |   \"a\": 1,\n> 3 |   \"b\": ,\n    |        ^\n  4 |   \"c\": 3\n  5 | }\n"
+        );
+    }
 
-        match result {
-            ParseResult::Single(value) => {
-                assert!(value.is_array());
-                assert_eq!(value.as_array().unwrap().len(), 4);
-            }
-            _ => panic!("Expected Single result"),
-        }
+    #[test]
+    fn test_parse_file_error_has_path() {
+        let path = std::env::temp_dir().join(format!("slopjson-error-{}.json", std::process::id()));
+        std::fs::write(&path, "{\"a\": }").unwrap();
+        let result = parse_file(&path, None, &ParseControl::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let error = result.map(|parsed| parsed.content).unwrap_err();
+        assert_eq!(
+            error.syntax_error().unwrap().path.as_deref(),
+            Some(path.as_path())
+        );
+        assert_eq!(error.syntax_error().unwrap().source_text, None);
+        assert!(error
+            .to_string()
+            .ends_with(&format!("in {}", path.display())));
     }
 
     #[test]
-    fn test_parse_jsonl_multiple_objects() {
-        let content = r#"{"name": "first"}
-{"name": "second", "value": 42}
-{"name": "third"}"#;
-        let result = parse_json_content(content).unwrap();
+    fn test_stdin_error_keeps_source_text() {
+        let error = parse_reader(&b"a: 1\nb: [2\n"[..], None, &ParseControl::default())
+            .map(|parsed| parsed.content)
+            .unwrap_err();
+        let syntax_error = error.syntax_error().unwrap();
+        assert_eq!(syntax_error.path, None);
+        assert_eq!(syntax_error.source_text.as_deref(), Some("a: 1\nb: [2\n"));
+    }
 
-        match result {
-            ParseResult::JsonL(values) => {
-                assert_eq!(values.len(), 3);
-                assert_eq!(values[0]["name"], "first");
-                assert_eq!(values[1]["name"], "second");
-                assert_eq!(values[1]["value"], 42);
-                assert_eq!(values[2]["name"], "third");
-            }
-            _ => panic!("Expected JsonL result"),
-        }
+    #[test]
+    fn test_parse_file_error_has_compression() {
+        use std::io::Write;
+
+        // Compressed without a compression extension, so only the content tells
+        let path =
+            std::env::temp_dir().join(format!("slopjson-gz-error-{}.json", std::process::id()));
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"{\"a\": }").unwrap();
+        encoder.finish().unwrap();
+        let result = parse_file(&path, None, &ParseControl::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let error = result.map(|parsed| parsed.content).unwrap_err();
+        let syntax_error = error.syntax_error().unwrap();
+        assert_eq!(syntax_error.path.as_deref(), Some(path.as_path()));
+        assert_eq!(syntax_error.compression, Some(Compression::Gzip));
     }
 
     #[test]
-    fn test_parse_jsonl_with_empty_lines() {
-        let content = r#"{"name": "first"}
+    fn test_input_format_names() {
+        assert_eq!("NDJSON".parse(), Ok(InputFormat::JsonL));
+        assert_eq!("yml".parse(), Ok(InputFormat::Yaml));
+        assert_eq!("jsonc".parse(), Ok(InputFormat::Json5));
+        assert_eq!(
+            InputFormat::from_path(Path::new("Cargo.toml")),
+            Some(InputFormat::Toml)
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("export.TSV")),
+            Some(InputFormat::Csv(CsvOptions::TSV))
+        );
+        assert_eq!(InputFormat::Csv(CsvOptions::TSV).label(), "TSV");
+        assert!("xml".parse::<InputFormat>().is_err());
+        assert_eq!(
+            InputFormat::from_path(Path::new("data.Parquet")),
+            Some(InputFormat::Parquet)
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("events.mpk")),
+            Some(InputFormat::MsgPack)
+        );
+        assert_eq!(InputFormat::Bson.label(), "BSON");
+        assert_eq!(
+            InputFormat::from_path(Path::new("frame.feather")),
+            Some(InputFormat::Arrow)
+        );
+        assert_eq!(InputFormat::from_path(Path::new("data")), None);
+    }
 
-{"name": "second"}
+    #[test]
+    fn test_parse_file_binary_formats() {
+        let path = std::env::temp_dir().join(format!("slopjson-{}.cbor", std::process::id()));
+        // {"a": h'01'}
+        std::fs::write(&path, [0xa1, 0x61, b'a', 0x41, 0x01]).unwrap();
+        let result = parse_file(&path, None, &ParseControl::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let parsed = result.unwrap();
+        assert_eq!(parsed.format, Some(InputFormat::Cbor));
+        assert!(!parsed.format_detected);
+        assert!(matches!(
+            parsed.content,
+            ParseResult::Single(value) if value == serde_json::json!({"a": {"$binary": "AQ=="}})
+        ));
+
+        // Binary content is never read as text, even if it would be valid JSON
+        let result = parse_reader(
+            "{}".as_bytes(),
+            Some(InputFormat::Cbor),
+            &ParseControl::default(),
+        );
+        assert!(matches!(result, Err(ParseError::InvalidCbor(_))));
+    }
 
-{"name": "third"}"#;
-        let result = parse_json_content(content).unwrap();
+    #[test]
+    fn test_parse_reader_with_format() {
+        let content = "a: 1\n";
+        let parsed = parse_reader(content.as_bytes(), None, &ParseControl::default()).unwrap();
+        assert!(matches!(parsed.content, ParseResult::Single(_)));
+        assert_eq!(parsed.format, Some(InputFormat::Yaml));
+        assert!(parsed.format_detected);
+
+        // Valid YAML, but not JSON
+        let result = parse_reader(
+            content.as_bytes(),
+            Some(InputFormat::Json),
+            &ParseControl::default(),
+        );
+        assert!(matches!(result, Err(ParseError::InvalidJson(_))));
+
+        let parsed = parse_reader(
+            "{\"a\": 1}".as_bytes(),
+            Some(InputFormat::JsonL),
+            &ParseControl::default(),
+        )
+        .unwrap();
+        assert!(matches!(parsed.content, ParseResult::JsonL { values, .. } if values.len() == 1));
+        assert!(!parsed.format_detected);
+    }
 
-        match result {
-            ParseResult::JsonL(values) => {
-                assert_eq!(values.len(), 3);
-                assert_eq!(values[0]["name"], "first");
-                assert_eq!(values[1]["name"], "second");
-                assert_eq!(values[2]["name"], "third");
-            }
-            _ => panic!("Expected JsonL result"),
+    #[test]
+    fn test_parse_file_from_named_pipe() {
+        let path = std::env::temp_dir().join(format!("slopjson-fifo-{}", std::process::id()));
+        let status = std::process::Command::new("mkfifo").arg(&path).status();
+        if !status.is_ok_and(|status| status.success()) {
+            // mkfifo is not available everywhere
+            return;
         }
+
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || {
+            std::fs::write(writer_path, "{\"a\": 1}\n{\"a\": 2}\n").unwrap();
+        });
+        let result = parse_file(&path, None, &ParseControl::default()).map(|parsed| parsed.content);
+        writer.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let Ok(ParseResult::JsonL { values, errors, .. }) = result else {
+            panic!("expected JSONL, got {:?}", result);
+        };
+        assert_eq!(values.len(), 2);
+        assert!(errors.is_empty());
     }
 
     #[test]
-    fn test_parse_invalid_json() {
-        let content = r#"{"name": "test", invalid}"#;
-        let result = parse_json_content(content);
-
-        assert!(result.is_err());
-        match result {
-            Err(ParseError::InvalidJson(_)) => {}
-            _ => panic!("Expected InvalidJson error"),
-        }
+    fn test_parse_file_detects_format() {
+        let dir = std::env::temp_dir();
+        let write = |name: &str, data: &[u8]| {
+            let path = dir.join(format!("slopjson-sniff-{}-{}", std::process::id(), name));
+            std::fs::write(&path, data).unwrap();
+            let parsed = parse_file(&path, None, &ParseControl::default());
+            std::fs::remove_file(&path).unwrap();
+            parsed
+        };
+
+        let parsed = write("dump", b"---\nname: test\n").unwrap();
+        assert_eq!(parsed.format, Some(InputFormat::Yaml));
+        assert_eq!(parsed.root_name("dump"), "dump (YAML)");
+
+        let parsed = write("export.json.bak", b"{\"a\": 1}\n{\"a\": 2}\n").unwrap();
+        assert!(matches!(parsed.content, ParseResult::JsonL { .. }));
+        assert_eq!(
+            parsed.root_name("export.json.bak"),
+            "export.json.bak (JSONL)"
+        );
+
+        // A YAML flow mapping looks like JSON but isn't
+        let parsed = write("flow.out", b"{name: plain text}").unwrap();
+        assert_eq!(parsed.format, Some(InputFormat::Yaml));
+        // Unquoted keys are also JSON5, which is tried first
+        let parsed = write("keys.out", b"{a: 1}").unwrap();
+        assert_eq!(parsed.format, Some(InputFormat::Json5));
+
+        // Text that looks like JSON reports the JSON error
+        assert!(matches!(
+            write("broken.out", b"{\"a\": [1, 2}"),
+            Err(ParseError::InvalidJson(_))
+        ));
+        // Compressed content is decompressed, then sniffed again
+        let parsed = write("archive.out", &zstd::encode_all(&b"[1, 2]"[..], 0).unwrap()).unwrap();
+        assert_eq!(parsed.root_name("archive.out"), "archive.out (JSON, zstd)");
+        assert!(matches!(
+            write("truncated.out", &[0x1f, 0x8b, 8, 0, 0, 0]),
+            Err(ParseError::IoError(_))
+        ));
+
+        // Files with a known extension are not labeled
+        let parsed = write("data.json", b"{\"a\": 1}").unwrap();
+        assert!(!parsed.format_detected);
+        assert_eq!(parsed.root_name("data.json"), "data.json");
+
+        // Unless JSON turns out to be JSON5
+        let parsed = write("tsconfig.json", b"{\n  // strict\n  \"strict\": true,\n}").unwrap();
+        assert_eq!(parsed.root_name("tsconfig.json"), "tsconfig.json (JSON5)");
+        assert!(matches!(
+            write("broken.json", b"{\"a\": [1, 2}"),
+            Err(ParseError::InvalidJson(_))
+        ));
     }
 
     #[test]
-    fn test_parse_yaml_object() {
-        let content = r#"
-name: test
-value: 42
-nested:
-  key: value
-"#;
-        let result = parse_yaml_content(content).unwrap();
+    fn test_parse_file_decompresses_by_inner_extension() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("slopjson-{}.yaml.gz", std::process::id()));
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"name: test\n").unwrap();
+        encoder.finish().unwrap();
+        let parsed = parse_file(&path, None, &ParseControl::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let parsed = parsed.unwrap();
+        assert_eq!(parsed.format, Some(InputFormat::Yaml));
+        assert!(!parsed.format_detected);
+        assert_eq!(parsed.compression, Some(Compression::Gzip));
+        assert_eq!(parsed.root_name("data.yaml.gz"), "data.yaml.gz (gzip)");
+
+        // A single line is still JSONL when the inner extension says so
+        let path = dir.join(format!("slopjson-{}.jsonl.zst", std::process::id()));
+        std::fs::write(&path, zstd::encode_all(&b"{\"a\": 1}\n"[..], 0).unwrap()).unwrap();
+        let parsed = parse_file(&path, None, &ParseControl::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let parsed = parsed.unwrap();
+        assert!(
+            matches!(parsed.content, ParseResult::JsonL { ref values, .. } if values.len() == 1)
+        );
+        assert_eq!(
+            parsed.root_name("data.jsonl.zst"),
+            "data.jsonl.zst (JSONL, zstd)"
+        );
+    }
 
-        match result {
-            ParseResult::Single(value) => {
-                assert_eq!(value["name"], "test");
-                assert_eq!(value["value"], 42);
-                assert_eq!(value["nested"]["key"], "value");
-            }
-            _ => panic!("Expected Single result"),
-        }
+    #[test]
+    fn test_duplicate_keys_kept() {
+        let content = r#"{"name": "a", "server": {"port": 1, "port": 2, "port": 3}, "name": "b"}"#;
+        let parsed = parse_text_input(content).unwrap();
+        let value = match parsed.content {
+            ParseResult::Single(value) => value,
+            other => panic!("Expected Single result, got {:?}", other),
+        };
+        let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["name", "server", "name (dup #2)"]);
+        assert_eq!(value["name (dup #2)"], "b");
+        assert_eq!(value["server"]["port (dup #3)"], 3);
+        assert_eq!(
+            parsed.warnings,
+            [
+                r#"Duplicate key "port" in $.server, shown as "port (dup #2)""#,
+                r#"Duplicate key "port" in $.server, shown as "port (dup #3)""#,
+                r#"Duplicate key "name" in $, shown as "name (dup #2)""#,
+            ]
+        );
+
+        // Numbers are still kept exactly as written
+        let (value, warnings) = parse_json_document(r#"[{"id": 18446744073709551617}]"#).unwrap();
+        assert_eq!(value[0]["id"].to_string(), "18446744073709551617");
+        assert!(warnings.is_empty());
     }
 
     #[test]
-    fn test_parse_yaml_array() {
-        let content = r#"
-- name: first
-  value: 1
-- name: second
-  value: 2
-"#;
-        let result = parse_yaml_content(content).unwrap();
+    fn test_parse_file_duplicate_key_warnings() {
+        let path = std::env::temp_dir().join(format!("slopjson-dup-{}.json", std::process::id()));
+        std::fs::write(&path, "{\n  \"a\": [{\"b\": 1, \"b\": 2}]\n}\n").unwrap();
+        let parsed = parse_file(&path, None, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            parsed.warnings,
+            [r#"Duplicate key "b" in $.a[0], shown as "b (dup #2)""#]
+        );
+    }
 
-        match result {
-            ParseResult::Single(value) => {
-                assert!(value.is_array());
-                let arr = value.as_array().unwrap();
-                assert_eq!(arr.len(), 2);
-                assert_eq!(arr[0]["name"], "first");
-                assert_eq!(arr[1]["value"], 2);
-            }
-            _ => panic!("Expected Single result"),
+    #[test]
+    fn test_parse_bytes_decompresses_whatever_the_format() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"{\"a\": [1, 2]}").unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let parsed =
+            parse_bytes(gzipped, Some(InputFormat::Json), &ParseControl::default()).unwrap();
+        match &parsed.content {
+            ParseResult::Single(value) => assert_eq!(value["a"][1], 2),
+            other => panic!("Expected Single result, got {:?}", other),
         }
+        assert_eq!(parsed.format, Some(InputFormat::Json));
+        assert!(!parsed.format_detected);
+        assert_eq!(parsed.compression, Some(Compression::Gzip));
+
+        let zstd = zstd::encode_all(&b"a: 1\n"[..], 0).unwrap();
+        let parsed = parse_bytes(zstd, Some(InputFormat::Yaml), &ParseControl::default()).unwrap();
+        assert_eq!(parsed.format, Some(InputFormat::Yaml));
+        assert_eq!(parsed.compression, Some(Compression::Zstd));
     }
 
     #[test]
-    fn test_parse_invalid_yaml() {
-        let content = r#"
-name: test
-  invalid: indentation
-"#;
-        let result = parse_yaml_content(content);
-
-        assert!(result.is_err());
-        match result {
-            Err(ParseError::InvalidYaml(_)) => {}
-            _ => panic!("Expected InvalidYaml error"),
+    fn test_parse_bytes_detects_encoding() {
+        let utf16: Vec<u8> = "\u{feff}{\"name\": \"café\"}"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(sniff_content(&utf16, &utf16), ContentKind::Json);
+        let parsed = parse_bytes(utf16, None, &ParseControl::default()).unwrap();
+        match &parsed.content {
+            ParseResult::Single(value) => assert_eq!(value["name"], "café"),
+            other => panic!("Expected Single result, got {:?}", other),
+        }
+        assert_eq!(
+            parsed.root_name("export.json"),
+            "export.json (JSON, UTF-16LE)"
+        );
+        assert!(parsed.warnings.is_empty());
+
+        let parsed = parse_bytes(
+            b"name: Jos\xE9\n".to_vec(),
+            Some(InputFormat::Yaml),
+            &ParseControl::default(),
+        )
+        .unwrap();
+        match &parsed.content {
+            ParseResult::Single(value) => assert_eq!(value["name"], "José"),
+            other => panic!("Expected Single result, got {:?}", other),
         }
+        assert_eq!(parsed.root_name("old.yaml"), "old.yaml (Latin-1)");
+        assert_eq!(parsed.warnings.len(), 1);
+
+        let parsed =
+            parse_bytes(b"\xEF\xBB\xBF[1]".to_vec(), None, &ParseControl::default()).unwrap();
+        assert_eq!(parsed.root_name("list.json"), "list.json (JSON, UTF-8 BOM)");
     }
 
     #[test]
@@ -473,12 +1520,11 @@ name: test
         writer.close().unwrap();
 
         // Parse the Parquet data
-        let result = parse_parquet_content(&buffer).unwrap();
+        let result = parse_parquet_content(buffer).unwrap();
 
         match result {
-            ParseResult::Single(value) => {
-                assert!(value.is_array());
-                let arr = value.as_array().unwrap();
+            ParseResult::PagedParquet(parquet) => {
+                let arr = parquet.read_rows(0, parquet.num_rows()).unwrap();
                 assert_eq!(arr.len(), 3);
 
                 // Check first row
@@ -493,73 +1539,50 @@ name: test
                 assert_eq!(arr[2]["id"], 3);
                 assert_eq!(arr[2]["name"], "Charlie");
             }
-            _ => panic!("Expected Single result with array"),
+            _ => panic!("Expected Parquet result"),
         }
     }
 
     #[test]
-    fn test_parse_parquet_with_nullable_fields() {
-        use arrow::array::{BooleanArray, Float64Array};
+    fn test_parse_file_detects_parquet() {
+        use arrow::array::Int64Array;
         use arrow::datatypes::{DataType, Field, Schema};
         use arrow::record_batch::RecordBatch;
         use parquet::arrow::arrow_writer::ArrowWriter;
-        use parquet::file::properties::WriterProperties;
         use std::sync::Arc;
 
-        // Create schema with nullable fields
-        let schema = Schema::new(vec![
-            Field::new("active", DataType::Boolean, true),
-            Field::new("score", DataType::Float64, true),
-        ]);
-
-        // Create sample data with nulls
-        let active_array = BooleanArray::from(vec![Some(true), None, Some(false)]);
-        let score_array = Float64Array::from(vec![Some(95.5), None, Some(87.0)]);
-
-        // Create record batch
-        let batch = RecordBatch::try_new(
-            Arc::new(schema),
-            vec![Arc::new(active_array), Arc::new(score_array)],
-        )
-        .unwrap();
-
-        // Write to Parquet in memory
-        let mut buffer = Vec::new();
-        let props = WriterProperties::builder().build();
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch =
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![1, 2]))])
+                .unwrap();
+        let path =
+            std::env::temp_dir().join(format!("slopjson-sniff-{}-data.out", std::process::id()));
         let mut writer =
-            ArrowWriter::try_new(&mut buffer, batch.schema().clone(), Some(props)).unwrap();
+            ArrowWriter::try_new(std::fs::File::create(&path).unwrap(), schema, None).unwrap();
         writer.write(&batch).unwrap();
         writer.close().unwrap();
 
-        // Parse the Parquet data
-        let result = parse_parquet_content(&buffer).unwrap();
-
-        match result {
-            ParseResult::Single(value) => {
-                assert!(value.is_array());
-                let arr = value.as_array().unwrap();
-                assert_eq!(arr.len(), 3);
-
-                // Check first row
-                assert_eq!(arr[0]["active"], true);
-                assert_eq!(arr[0]["score"].as_f64().unwrap(), 95.5);
-
-                // Check second row (with nulls)
-                assert_eq!(arr[1]["active"], Value::Null);
-                assert_eq!(arr[1]["score"], Value::Null);
-
-                // Check third row
-                assert_eq!(arr[2]["active"], false);
-                assert_eq!(arr[2]["score"].as_f64().unwrap(), 87.0);
-            }
-            _ => panic!("Expected Single result with array"),
-        }
+        let parsed = parse_file(&path, None, &ParseControl::default());
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let parsed = parsed.unwrap();
+        assert!(parsed.format_detected);
+        assert_eq!(parsed.root_name("data.out"), "data.out (Parquet)");
+        let ParseResult::PagedParquet(parquet) = parsed.content else {
+            panic!("expected Parquet");
+        };
+        assert_eq!(parquet.num_rows(), 2);
+
+        // Piped Parquet is detected from the whole content
+        let parsed = parse_reader(data.as_slice(), None, &ParseControl::default()).unwrap();
+        assert!(matches!(parsed.content, ParseResult::PagedParquet(_)));
     }
 
     #[test]
     fn test_parse_invalid_parquet() {
         let invalid_data = b"not a parquet file";
-        let result = parse_parquet_content(invalid_data);
+        let result = parse_parquet_content(invalid_data.to_vec());
 
         assert!(result.is_err());
         match result {
@@ -568,6 +1591,21 @@ name: test
         }
     }
 
+    #[test]
+    fn test_parse_cancelled() {
+        let content: String = (0..2500).map(|i| format!("{{\"id\": {}}}\n", i)).collect();
+        let control = ParseControl::new(Arc::new(AtomicBool::new(true)), |_| {});
+
+        assert!(matches!(
+            parse_json_content_with_control(&content, &control),
+            Err(ParseError::Cancelled)
+        ));
+        assert!(matches!(
+            parse_text_detecting_format("key: value", &control, false, &mut Vec::new()),
+            Err(ParseError::Cancelled)
+        ));
+    }
+
     #[test]
     fn test_parse_text_content_json() {
         let content = r#"{"name": "test", "value": 42}"#;
@@ -589,7 +1627,7 @@ name: test
         let result = parse_text_content(content).unwrap();
 
         match result {
-            ParseResult::JsonL(values) => {
+            ParseResult::JsonL { values, .. } => {
                 assert_eq!(values.len(), 2);
                 assert_eq!(values[0]["name"], "first");
                 assert_eq!(values[1]["name"], "second");
@@ -635,4 +1673,24 @@ nested:
             _ => panic!("Expected Single result"),
         }
     }
+
+    #[test]
+    fn test_parse_text_content_jsonc_fallback() {
+        // Not JSON because of the comments, and not YAML because of the tab indent
+        let content = "{\n\t// Use tabs\n\t\"editor.insertSpaces\": false,\n\t\"files.exclude\": {\"**/.git\": true,},\n}\n";
+        let result = parse_text_content(content).unwrap();
+
+        match result {
+            ParseResult::Single(value) => {
+                assert_eq!(value["editor.insertSpaces"], false);
+                assert_eq!(value["files.exclude"]["**/.git"], true);
+            }
+            _ => panic!("Expected Single result"),
+        }
+
+        let (_, format) =
+            parse_text_detecting_format(content, &ParseControl::default(), true, &mut Vec::new())
+                .unwrap();
+        assert_eq!(format, InputFormat::Json5);
+    }
 }
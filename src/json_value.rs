@@ -0,0 +1,313 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Building JSON values while reading JSON and other formats, keeping what
+//! `serde_json::Value` would lose: numbers exactly as written, NaN and
+//! infinities, and every occurrence of a duplicated key.
+
+use crate::path_formatting::{build_array_path, build_object_path};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fmt;
+
+/// Key of the single-member object that holds a float JSON numbers can't,
+/// as in `{"$float": "NaN"}`, `{"$float": "Infinity"}` or `{"$float": "-Infinity"}`.
+pub const FLOAT_KEY: &str = "$float";
+
+/// Key of the single-member object that holds a TOML date or time.
+///
+/// JSON has no date type, so TOML datetimes are kept apart from strings as
+/// `{"$datetime": "1979-05-27T07:32:00Z"}`, which also covers local dates,
+/// times and datetimes.
+pub const TOML_DATETIME_KEY: &str = "$datetime";
+
+/// Key of the object that holds a byte string, as base64.
+pub const BINARY_KEY: &str = "$binary";
+
+/// Key of the member giving the media type of a `$binary` object, such as an
+/// image output of a notebook cell.
+pub const MIME_KEY: &str = "$mime";
+
+/// Where a document's values come from, which decides whether an object such
+/// as `{"$float": "NaN"}` stands for a value converted from another format or
+/// is data that happens to use the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueOrigin {
+    /// Read from JSON or JSONL, whose objects are always the file's own data
+    Json,
+    /// Converted from another format, such as YAML, TOML, Parquet or BSON
+    Converted,
+}
+
+/// Converts a float to a JSON number, or NaN and infinities to `FLOAT_KEY` objects.
+pub fn float_to_json(value: f64) -> Value {
+    match serde_json::Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None => {
+            let name = if value.is_nan() {
+                "NaN"
+            } else if value > 0.0 {
+                "Infinity"
+            } else {
+                "-Infinity"
+            };
+            serde_json::json!({ FLOAT_KEY: name })
+        }
+    }
+}
+
+/// Deserializes a JSON value from another format, such as YAML or JSON5.
+///
+/// Unlike `Value::deserialize`, which turns NaN and infinities into null, this
+/// keeps them as `FLOAT_KEY` objects.
+pub fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+    ValueSeed { duplicates: None }.deserialize(deserializer)
+}
+
+/// Parses a single JSON document, keeping every occurrence of a duplicated key.
+///
+/// `serde_json` keeps only the last value of a key that appears twice in an
+/// object. Here the later occurrences are renamed `name (dup #2)`, `name (dup #3)`
+/// and so on, and a warning is returned for each.
+pub fn parse_json_document(content: &str) -> Result<(Value, Vec<String>), serde_json::Error> {
+    parse_json_at(serde_json::Deserializer::from_str(content), "$")
+}
+
+/// Parses one value of a file holding several, such as a JSONL line, as
+/// `parse_json_document` does, with the warnings naming keys under `path`,
+/// the path of the value in the file.
+pub fn parse_json_value_at(
+    content: &[u8],
+    path: &str,
+) -> Result<(Value, Vec<String>), serde_json::Error> {
+    parse_json_at(serde_json::Deserializer::from_slice(content), path)
+}
+
+fn parse_json_at<'de, R: serde_json::de::Read<'de>>(
+    mut deserializer: serde_json::Deserializer<R>,
+    path: &str,
+) -> Result<(Value, Vec<String>), serde_json::Error> {
+    let duplicates = RefCell::new(DuplicateKeys {
+        path: path.to_string(),
+        ..Default::default()
+    });
+    let value = ValueSeed {
+        duplicates: Some(&duplicates),
+    }
+    .deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok((value, duplicates.into_inner().warnings))
+}
+
+/// With `arbitrary_precision`, `serde_json` hands numbers to visitors as a map
+/// with this single key and the number's text as its value.
+const SERDE_JSON_NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// The duplicate keys found so far, and the path of the value being read.
+#[derive(Default)]
+struct DuplicateKeys {
+    path: String,
+    /// Length of `path` before each segment that was entered
+    path_lengths: Vec<usize>,
+    warnings: Vec<String>,
+}
+
+/// Reads a JSON value, renaming duplicate keys if `duplicates` is given and
+/// otherwise keeping the last value of each key.
+#[derive(Clone, Copy)]
+struct ValueSeed<'a> {
+    duplicates: Option<&'a RefCell<DuplicateKeys>>,
+}
+
+impl ValueSeed<'_> {
+    /// Appends `segment` to the tracked path while a member or element is read.
+    fn enter(&self, segment: impl FnOnce(&str) -> String) {
+        if let Some(duplicates) = self.duplicates {
+            let duplicates = &mut *duplicates.borrow_mut();
+            duplicates.path_lengths.push(duplicates.path.len());
+            let next = segment(&duplicates.path);
+            duplicates.path = next;
+        }
+    }
+
+    fn leave(&self) {
+        if let Some(duplicates) = self.duplicates {
+            let duplicates = &mut *duplicates.borrow_mut();
+            let len = duplicates.path_lengths.pop().unwrap_or(0);
+            duplicates.path.truncate(len);
+        }
+    }
+
+    /// Returns the name to store `key` under in `object`.
+    fn member_name(&self, object: &serde_json::Map<String, Value>, key: String) -> String {
+        let Some(duplicates) = self.duplicates.filter(|_| object.contains_key(&key)) else {
+            return key;
+        };
+        let name = (2..)
+            .map(|n| format!("{} (dup #{})", key, n))
+            .find(|name| !object.contains_key(name))
+            .unwrap_or_default();
+        let duplicates = &mut *duplicates.borrow_mut();
+        let warning = format!(
+            "Duplicate key \"{}\" in {}, shown as \"{}\"",
+            key, duplicates.path, name
+        );
+        duplicates.warnings.push(warning);
+        name
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ValueSeed<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_i128<E>(self, value: i128) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_u128<E>(self, value: u128) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(float_to_json(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        loop {
+            self.enter(|path| build_array_path(path, values.len()));
+            let element = seq.next_element_seed(self);
+            self.leave();
+            match element? {
+                Some(value) => values.push(value),
+                None => return Ok(Value::Array(values)),
+            }
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut object = serde_json::Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == SERDE_JSON_NUMBER_TOKEN && object.is_empty() {
+                let text: String = map.next_value()?;
+                return text.parse().map(Value::Number).map_err(de::Error::custom);
+            }
+            let name = self.member_name(&object, key);
+            self.enter(|path| build_object_path(path, &name));
+            let value = map.next_value_seed(self);
+            self.leave();
+            object.insert(name, value?);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_reader::{parse_toml_content, parse_yaml_content};
+    use crate::json_reader::ParseResult;
+    use crate::jsonl_reader::parse_json_content;
+
+    #[test]
+    fn test_key_order_preserved() {
+        let keys = |result: ParseResult| match result {
+            ParseResult::Single(Value::Object(map)) => map.keys().cloned().collect::<Vec<_>>(),
+            other => panic!("Expected an object, got {:?}", other),
+        };
+        let expected = ["zeta", "alpha", "mid"];
+        assert_eq!(
+            keys(parse_json_content(r#"{"zeta": 1, "alpha": 2, "mid": 3}"#).unwrap()),
+            expected
+        );
+        assert_eq!(
+            keys(parse_yaml_content("zeta: 1\nalpha: 2\nmid: 3\n").unwrap()),
+            expected
+        );
+        assert_eq!(
+            keys(parse_toml_content("zeta = 1\nalpha = 2\nmid = 3\n").unwrap()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_numbers_kept_exact() {
+        let content = r#"{"id": 18446744073709551617, "price": 0.1000000000000000055}"#;
+        match parse_json_content(content).unwrap() {
+            ParseResult::Single(value) => assert_eq!(value.to_string(), content.replace(' ', "")),
+            other => panic!("Expected Single result, got {:?}", other),
+        }
+
+        match parse_yaml_content("big: 18446744073709551617\nnan: .nan\ninf: -.inf\n").unwrap() {
+            ParseResult::Single(value) => {
+                assert_eq!(value["big"].to_string(), "18446744073709551617");
+                assert_eq!(value["nan"], serde_json::json!({ "$float": "NaN" }));
+                assert_eq!(value["inf"], serde_json::json!({ "$float": "-Infinity" }));
+            }
+            other => panic!("Expected Single result, got {:?}", other),
+        }
+
+        assert_eq!(
+            float_to_json(f64::INFINITY),
+            serde_json::json!({ "$float": "Infinity" })
+        );
+        assert_eq!(float_to_json(1.5), serde_json::json!(1.5));
+    }
+}
@@ -0,0 +1,341 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::compression::Compression;
+use crate::encoding::UTF8_BOM;
+use crate::json_reader::{ParseControl, ParseError, ParseProgress, SyntaxError};
+use crate::json_value::parse_json_value_at;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+
+/// Size of the chunks read while scanning for line breaks.
+const SCAN_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Where the indexed lines are read from.
+#[derive(Debug)]
+enum LineData {
+    /// The file mapped into memory
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+    /// The open file, read with a seek per line. It is locked from the seek
+    /// to the end of the read, as lines are also read by search on another thread.
+    File(Mutex<File>),
+    /// The decompressed content of a compressed file
+    Memory(Vec<u8>),
+}
+
+/// An index of the lines of a JSONL file, for parsing lines on demand.
+///
+/// Only the starting offset of each non-blank line is kept in memory, so
+/// memory use grows with the number of lines rather than the size of the file,
+/// except for compressed files, whose decompressed content is kept whole.
+/// Indexed lines are numbered from 0 without the blank lines; `source_line`
+/// gives their line number in the file.
+#[derive(Debug)]
+pub struct JsonLIndex {
+    data: LineData,
+    line_starts: Vec<u64>,
+    /// For each run of blank lines, the indexed line that follows it and the
+    /// number of blank lines before that line in all
+    blank_runs: Vec<(usize, usize)>,
+}
+
+/// Records a non-blank line starting at `start`, after `blank_lines` blank lines in all.
+fn push_line(
+    line_starts: &mut Vec<u64>,
+    blank_runs: &mut Vec<(usize, usize)>,
+    start: u64,
+    blank_lines: usize,
+) {
+    if blank_runs.last().map_or(0, |&(_, blank)| blank) < blank_lines {
+        blank_runs.push((line_starts.len(), blank_lines));
+    }
+    line_starts.push(start);
+}
+
+/// The bytes from `start` up to the next line break or the end of `data`.
+fn line_in(data: &[u8], start: u64) -> Vec<u8> {
+    let rest = data.get(start as usize..).unwrap_or_default();
+    let end = rest
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .unwrap_or(rest.len());
+    rest[..end].to_vec()
+}
+
+/// The start of each non-blank line and the runs of blank lines, as kept by `JsonLIndex`.
+type LineOffsets = (Vec<u64>, Vec<(usize, usize)>);
+
+/// Scans `reader` for line breaks, reporting progress as it goes. Everything
+/// read is also appended to `content`, if given.
+fn scan_lines(
+    reader: &mut impl Read,
+    total_bytes: Option<u64>,
+    control: &ParseControl,
+    mut content: Option<&mut Vec<u8>>,
+) -> Result<LineOffsets, ParseError> {
+    let mut line_starts = Vec::new();
+    let mut blank_runs = Vec::new();
+    let mut blank_lines = 0;
+    let mut buffer = vec![0; SCAN_CHUNK_SIZE];
+    let mut offset: u64 = 0;
+    let mut line_start: u64 = 0;
+    let mut line_has_content = false;
+    loop {
+        control.check_cancelled()?;
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| ParseError::IoError(e.to_string()))?;
+        if read == 0 {
+            break;
+        }
+        if let Some(content) = content.as_deref_mut() {
+            content.extend_from_slice(&buffer[..read]);
+        }
+        // A byte order mark before the first line is not part of it
+        let skip = if offset == 0 && buffer[..read].starts_with(UTF8_BOM) {
+            line_start = UTF8_BOM.len() as u64;
+            UTF8_BOM.len()
+        } else {
+            0
+        };
+        for (idx, byte) in buffer[..read].iter().enumerate().skip(skip) {
+            if *byte == b'\n' {
+                if line_has_content {
+                    push_line(&mut line_starts, &mut blank_runs, line_start, blank_lines);
+                } else {
+                    blank_lines += 1;
+                }
+                line_start = offset + idx as u64 + 1;
+                line_has_content = false;
+            } else if !byte.is_ascii_whitespace() {
+                line_has_content = true;
+            }
+        }
+        offset += read as u64;
+        control.report(ParseProgress {
+            bytes_processed: offset,
+            total_bytes,
+            rows_processed: line_starts.len(),
+        });
+    }
+    if line_has_content {
+        push_line(&mut line_starts, &mut blank_runs, line_start, blank_lines);
+    }
+    Ok((line_starts, blank_runs))
+}
+
+impl JsonLIndex {
+    /// Scans a file for line breaks, reporting progress as it goes.
+    ///
+    /// Lines are not parsed, so lines that are not valid JSON are only found
+    /// when `parse_line` reads them. With the `mmap` feature (on by default),
+    /// the file is memory-mapped once the scan is done.
+    pub fn build(path: &Path, control: &ParseControl) -> Result<Self, ParseError> {
+        let mut file = File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+        let total_bytes = file.metadata().ok().map(|metadata| metadata.len());
+        let (line_starts, blank_runs) = scan_lines(&mut file, total_bytes, control, None)?;
+        Ok(Self {
+            data: Self::line_data(file),
+            line_starts,
+            blank_runs,
+        })
+    }
+
+    /// Decompresses a file and scans it for line breaks, as `build` does.
+    ///
+    /// A compressed stream can't be read from the middle, so the decompressed
+    /// content is kept in memory for reading lines.
+    pub fn build_compressed(
+        path: &Path,
+        compression: Compression,
+        control: &ParseControl,
+    ) -> Result<Self, ParseError> {
+        let file = File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+        let mut reader = compression.decoder(BufReader::new(file))?;
+        let mut content = Vec::new();
+        let (line_starts, blank_runs) = scan_lines(&mut reader, None, control, Some(&mut content))?;
+        Ok(Self {
+            data: LineData::Memory(content),
+            line_starts,
+            blank_runs,
+        })
+    }
+
+    #[cfg(feature = "mmap")]
+    fn line_data(file: File) -> LineData {
+        // SAFETY: the map is only read, and slopjson never writes to the files it
+        // views. Another process truncating the file while it is open is not
+        // guarded against, as with any memory-mapped viewer.
+        match unsafe { memmap2::Mmap::map(&file) } {
+            Ok(map) => LineData::Mapped(map),
+            // Some files can't be mapped; read their lines with seeks instead
+            Err(_) => LineData::File(Mutex::new(file)),
+        }
+    }
+
+    #[cfg(not(feature = "mmap"))]
+    fn line_data(file: File) -> LineData {
+        LineData::File(Mutex::new(file))
+    }
+
+    /// Number of non-blank lines in the file.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The 1-based line number in the file of an indexed line, counting blank lines.
+    pub fn source_line(&self, line: usize) -> usize {
+        let runs = self.blank_runs.partition_point(|&(first, _)| first <= line);
+        let blank_lines = runs.checked_sub(1).map_or(0, |run| self.blank_runs[run].1);
+        line + blank_lines + 1
+    }
+
+    /// Reads the raw bytes of a line, without its line break.
+    pub fn line_bytes(&self, line: usize) -> Result<Vec<u8>, ParseError> {
+        let start = *self
+            .line_starts
+            .get(line)
+            .ok_or_else(|| ParseError::IoError(format!("line {} out of range", line + 1)))?;
+
+        let mut bytes = match &self.data {
+            #[cfg(feature = "mmap")]
+            LineData::Mapped(map) => line_in(map, start),
+            LineData::Memory(content) => line_in(content, start),
+            LineData::File(file) => {
+                let file = file.lock().unwrap_or_else(PoisonError::into_inner);
+                let mut reader = BufReader::new(&*file);
+                reader
+                    .seek(SeekFrom::Start(start))
+                    .map_err(|e| ParseError::IoError(e.to_string()))?;
+                let mut bytes = Vec::new();
+                reader
+                    .read_until(b'\n', &mut bytes)
+                    .map_err(|e| ParseError::IoError(e.to_string()))?;
+                bytes
+            }
+        };
+        if bytes.last() == Some(&b'\n') {
+            bytes.pop();
+        }
+        Ok(bytes)
+    }
+
+    /// Reads and parses a single line, as lines of smaller files are parsed.
+    ///
+    /// Duplicate keys are renamed in the value as usual, but as lines are parsed
+    /// after the file is loaded, they are not listed as warnings.
+    pub fn parse_line(&self, line: usize) -> Result<Value, ParseError> {
+        let bytes = self.line_bytes(line)?;
+        parse_json_value_at(&bytes, "$")
+            .map(|(value, _)| value)
+            .map_err(|e| {
+                ParseError::InvalidJson(Box::new(SyntaxError::from_json(
+                    &e,
+                    &String::from_utf8_lossy(&bytes),
+                )))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_skips_blank_lines() {
+        let path =
+            std::env::temp_dir().join(format!("slopjson-index-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "{\"a\": 1}\n\n  \n[2, 3]\r\n\"last\"").unwrap();
+
+        let index = JsonLIndex::build(&path, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(index.line_count(), 3);
+        let source_lines: Vec<usize> = (0..3).map(|line| index.source_line(line)).collect();
+        assert_eq!(source_lines, [1, 4, 5]);
+        assert_eq!(index.parse_line(0).unwrap(), serde_json::json!({"a": 1}));
+        assert_eq!(index.parse_line(1).unwrap(), serde_json::json!([2, 3]));
+        assert_eq!(index.parse_line(2).unwrap(), serde_json::json!("last"));
+        assert!(index.line_bytes(3).is_err());
+    }
+
+    #[test]
+    fn test_index_skips_byte_order_mark() {
+        let path =
+            std::env::temp_dir().join(format!("slopjson-index-bom-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "\u{feff}{\"a\": 1}\n{\"b\": 2}\n").unwrap();
+
+        let index = JsonLIndex::build(&path, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(index.line_count(), 2);
+        assert_eq!(index.parse_line(0).unwrap(), serde_json::json!({"a": 1}));
+    }
+
+    #[test]
+    fn test_index_keeps_duplicate_keys() {
+        let path =
+            std::env::temp_dir().join(format!("slopjson-index-dup-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "{\"a\": 1, \"a\": 2}\n{\"n\": 1.50}\n").unwrap();
+
+        let index = JsonLIndex::build(&path, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            index.parse_line(0).unwrap(),
+            serde_json::json!({"a": 1, "a (dup #2)": 2})
+        );
+        assert_eq!(index.parse_line(1).unwrap()["n"].to_string(), "1.50");
+    }
+
+    #[test]
+    fn test_index_reports_invalid_line() {
+        let path = std::env::temp_dir().join(format!(
+            "slopjson-index-invalid-{}.jsonl",
+            std::process::id()
+        ));
+        std::fs::write(&path, "{\"a\": 1}\n{oops\n").unwrap();
+
+        let index = JsonLIndex::build(&path, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(index.line_count(), 2);
+        assert!(index.parse_line(0).is_ok());
+        assert!(matches!(
+            index.parse_line(1),
+            Err(ParseError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn test_index_finds_lines_across_scan_chunks() {
+        let path =
+            std::env::temp_dir().join(format!("slopjson-index-long-{}.jsonl", std::process::id()));
+        let long_line = format!("\"{}\"", "x".repeat(SCAN_CHUNK_SIZE));
+        std::fs::write(&path, format!("{}\n[1,\n{}", long_line, long_line)).unwrap();
+
+        let index = JsonLIndex::build(&path, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(index.line_count(), 3);
+        assert!(index.parse_line(0).is_ok());
+        assert!(index.parse_line(1).is_err());
+        assert_eq!(index.line_bytes(2).unwrap().len(), long_line.len());
+    }
+}
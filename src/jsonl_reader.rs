@@ -0,0 +1,629 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading JSON content that may hold several values: JSONL, values written
+//! one after another, and RFC 7464 JSON text sequences.
+
+use crate::json_reader::{
+    JsonSeparator, LineError, ParseControl, ParseError, ParseProgress, ParseResult, SyntaxError,
+};
+use crate::json_value::{parse_json_document, parse_json_value_at};
+use crate::path_formatting::build_array_path;
+use serde_json::Value;
+
+/// Precedes each value of an RFC 7464 JSON text sequence.
+pub const RECORD_SEPARATOR: char = '\u{1e}';
+
+/// Number of JSONL lines parsed between progress reports.
+const ROWS_PER_PROGRESS_REPORT: usize = 1000;
+
+/// Parses content as JSON or JSONL format.
+///
+/// Content with several lines is parsed line by line as JSONL. Lines that are
+/// not valid JSON are recorded as `LineError`s rather than failing the whole
+/// parse. If most lines fail (e.g. pretty-printed JSON), the content is parsed
+/// as a single JSON document instead.
+///
+/// Content that is neither, but holds several objects or arrays written one
+/// after another (`{...}{...}`, or pretty-printed objects in a row), is read
+/// as a stream of values, and content starting with an ASCII record separator
+/// as an RFC 7464 JSON text sequence. Both give `ParseResult::JsonL`, with the
+/// `separator` telling them apart.
+///
+/// # Arguments
+///
+/// * `content` - The string content to parse
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::JsonL { .. })` if content is JSONL (possibly with some bad lines)
+/// * `Ok(ParseResult::Single(_))` if content is valid single JSON
+/// * `Err(ParseError::InvalidJson(_))` if content is neither valid JSON nor JSONL
+pub fn parse_json_content(content: &str) -> Result<ParseResult, ParseError> {
+    parse_json_content_with_control(content, &ParseControl::default())
+}
+
+/// Parses content as JSON or JSONL format, reporting progress and honoring cancellation.
+///
+/// See `parse_json_content`. Progress is reported every few thousand JSONL lines.
+pub fn parse_json_content_with_control(
+    content: &str,
+    control: &ParseControl,
+) -> Result<ParseResult, ParseError> {
+    parse_json_collecting_warnings(content, control, &mut Vec::new())
+}
+
+/// Parses content as JSON or JSONL, adding a warning to `warnings` for each
+/// duplicate key in the document or values read (see `parse_json_document`).
+pub fn parse_json_collecting_warnings(
+    content: &str,
+    control: &ParseControl,
+    warnings: &mut Vec<String>,
+) -> Result<ParseResult, ParseError> {
+    if content.trim_start().starts_with(RECORD_SEPARATOR) {
+        return parse_json_text_sequence(content, control, warnings);
+    }
+
+    // Try JSONL format if there are multiple lines
+    let mut jsonl = None;
+    if content.lines().nth(1).is_some() {
+        let mut line_warnings = Vec::new();
+        match parse_json_lines(content, control, true, &mut line_warnings)? {
+            ParseResult::JsonL {
+                values,
+                lines,
+                errors,
+                ..
+            } if !values.is_empty() && errors.is_empty() => {
+                warnings.extend(line_warnings);
+                return Ok(ParseResult::JsonL {
+                    values,
+                    lines,
+                    errors,
+                    separator: JsonSeparator::Newline,
+                });
+            }
+            ParseResult::Single(value) => {
+                warnings.extend(line_warnings);
+                return Ok(ParseResult::Single(value));
+            }
+            ParseResult::JsonL {
+                values,
+                lines,
+                errors,
+                ..
+            } => jsonl = Some((values, lines, errors, line_warnings)),
+            _ => {}
+        }
+    }
+
+    // Try to parse as single JSON
+    control.check_cancelled()?;
+    let error = match parse_json_document(content) {
+        Ok((value, duplicates)) => {
+            warnings.extend(duplicates);
+            return Ok(ParseResult::Single(value));
+        }
+        Err(e) => e,
+    };
+
+    // Values written one after another are preferred to JSONL with bad lines,
+    // unless they too stop at an error
+    let mut stream_warnings = Vec::new();
+    let stream = parse_json_stream(content, control, &mut stream_warnings)?;
+    if stream.values.len() > 1 && stream.error.is_none() {
+        warnings.extend(stream_warnings);
+        return Ok(ParseResult::JsonL {
+            values: stream.values,
+            lines: stream.lines,
+            errors: Vec::new(),
+            separator: JsonSeparator::Concatenated,
+        });
+    }
+    match jsonl {
+        Some((values, lines, errors, line_warnings))
+            if !values.is_empty() && values.len() >= errors.len() =>
+        {
+            warnings.extend(line_warnings);
+            Ok(ParseResult::JsonL {
+                values,
+                lines,
+                errors,
+                separator: JsonSeparator::Newline,
+            })
+        }
+        _ if stream.values.len() > 1 => {
+            warnings.extend(stream_warnings);
+            Ok(ParseResult::JsonL {
+                values: stream.values,
+                lines: stream.lines,
+                errors: stream.error.into_iter().collect(),
+                separator: JsonSeparator::Concatenated,
+            })
+        }
+        _ => Err(ParseError::InvalidJson(Box::new(SyntaxError::from_json(
+            &error, content,
+        )))),
+    }
+}
+
+/// Values read one after another by `parse_json_stream`.
+struct JsonStream {
+    values: Vec<Value>,
+    /// 1-based line that each value starts on
+    lines: Vec<usize>,
+    /// The error that stopped the stream, if any
+    error: Option<LineError>,
+}
+
+/// Reads objects and arrays written one after another, with or without
+/// whitespace between them, until the content ends or a value fails to parse.
+///
+/// Nothing after an error is read, as there is no telling where the next value
+/// starts. Content that does not start with an object or array gives no
+/// values, so that scalars separated by spaces are left to YAML.
+///
+/// The stream is only used to find where each value ends; the values are
+/// read with `parse_json_value_at`, adding their duplicate keys to `warnings`.
+fn parse_json_stream(
+    content: &str,
+    control: &ParseControl,
+    warnings: &mut Vec<String>,
+) -> Result<JsonStream, ParseError> {
+    let total_bytes = Some(content.len() as u64);
+    let mut values = Vec::new();
+    let mut lines = Vec::new();
+    // Line breaks are counted up to where the last value started
+    let (mut line, mut counted) = (1, 0);
+    let mut stream =
+        serde_json::Deserializer::from_str(content).into_iter::<serde::de::IgnoredAny>();
+    loop {
+        let value_start = stream.byte_offset();
+        let parsed = stream.next().map(|read| {
+            read.and_then(|_| {
+                let path = build_array_path("$", values.len());
+                parse_json_value_at(
+                    &content.as_bytes()[value_start..stream.byte_offset()],
+                    &path,
+                )
+            })
+        });
+        let Some(parsed) = parsed else {
+            return Ok(JsonStream {
+                values,
+                lines,
+                error: None,
+            });
+        };
+        let rest = &content[value_start..];
+        let start = value_start + rest.len() - rest.trim_start().len();
+        line += content[counted..start].matches('\n').count();
+        counted = start;
+        let value = match parsed {
+            Ok((value, _)) if values.is_empty() && !value.is_object() && !value.is_array() => {
+                return Ok(JsonStream {
+                    values: Vec::new(),
+                    lines: Vec::new(),
+                    error: None,
+                });
+            }
+            Ok((value, duplicates)) => {
+                warnings.extend(duplicates);
+                value
+            }
+            Err(e) => {
+                // Report the error at the line where the bad value starts
+                let error = LineError {
+                    line,
+                    offset: content[..start].rfind('\n').map_or(0, |idx| idx + 1),
+                    message: e.to_string(),
+                    index: values.len(),
+                };
+                return Ok(JsonStream {
+                    values,
+                    lines,
+                    error: Some(error),
+                });
+            }
+        };
+        values.push(value);
+        lines.push(line);
+        if values.len().is_multiple_of(ROWS_PER_PROGRESS_REPORT) {
+            control.check_cancelled()?;
+            control.report(ParseProgress {
+                bytes_processed: stream.byte_offset() as u64,
+                total_bytes,
+                rows_processed: values.len(),
+            });
+        }
+    }
+}
+
+/// Parses an RFC 7464 JSON text sequence, in which each value is preceded by
+/// an ASCII record separator and usually followed by a newline.
+///
+/// Records that are not valid JSON are recorded as `LineError`s at the line
+/// they start on, as for JSONL, and duplicate keys are added to `warnings`.
+fn parse_json_text_sequence(
+    content: &str,
+    control: &ParseControl,
+    warnings: &mut Vec<String>,
+) -> Result<ParseResult, ParseError> {
+    let total_bytes = Some(content.len() as u64);
+    let mut values = Vec::new();
+    let mut lines = Vec::new();
+    let mut errors = Vec::new();
+    let mut offset = 0;
+    let mut line = 1;
+    for (idx, record) in content.split(RECORD_SEPARATOR).enumerate() {
+        let record_offset = offset;
+        let record_line = line;
+        offset += record.len() + RECORD_SEPARATOR.len_utf8();
+        line += record.matches('\n').count();
+        if record.trim().is_empty() {
+            continue;
+        }
+        let path = build_array_path("$", values.len());
+        match parse_json_value_at(record.as_bytes(), &path) {
+            Ok((value, duplicates)) => {
+                values.push(value);
+                lines.push(record_line);
+                warnings.extend(duplicates);
+            }
+            Err(e) => errors.push(LineError {
+                line: record_line,
+                offset: content[..record_offset]
+                    .rfind('\n')
+                    .map_or(0, |idx| idx + 1),
+                message: e.to_string(),
+                index: values.len(),
+            }),
+        }
+        if (idx + 1).is_multiple_of(ROWS_PER_PROGRESS_REPORT) {
+            control.check_cancelled()?;
+            control.report(ParseProgress {
+                bytes_processed: offset.min(content.len()) as u64,
+                total_bytes,
+                rows_processed: values.len(),
+            });
+        }
+    }
+
+    Ok(ParseResult::JsonL {
+        values,
+        lines,
+        errors,
+        separator: JsonSeparator::RecordSeparator,
+    })
+}
+
+/// Parses content as JSONL, one JSON value per line, without falling back to a
+/// single JSON document.
+///
+/// Used when the format is known to be JSONL; lines that fail to parse are
+/// returned as errors however many there are. Duplicate keys in the lines are
+/// added to `warnings`.
+pub fn parse_jsonl_content_with_control(
+    content: &str,
+    control: &ParseControl,
+    warnings: &mut Vec<String>,
+) -> Result<ParseResult, ParseError> {
+    parse_json_lines(content, control, false, warnings)
+}
+
+/// Parses each non-blank line of `content` as JSON, recording the lines that fail
+/// and adding the duplicate keys of the others to `warnings`.
+///
+/// With `single_fallback`, the first bad line prompts an attempt to parse the whole
+/// content as one JSON document, which is returned as `ParseResult::Single` if it
+/// succeeds, with only its own duplicate keys added to `warnings`.
+fn parse_json_lines(
+    content: &str,
+    control: &ParseControl,
+    single_fallback: bool,
+    warnings: &mut Vec<String>,
+) -> Result<ParseResult, ParseError> {
+    let total_bytes = Some(content.len() as u64);
+    let mut json_values: Vec<Value> = Vec::new();
+    let mut lines = Vec::new();
+    let mut errors: Vec<LineError> = Vec::new();
+    let mut tried_single = !single_fallback;
+    let mut offset = 0;
+    let mut line_warnings = Vec::new();
+
+    for (idx, segment) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += segment.len();
+        let line = segment.strip_suffix('\n').unwrap_or(segment);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let path = build_array_path("$", json_values.len());
+        match parse_json_value_at(line.as_bytes(), &path) {
+            Ok((value, duplicates)) => {
+                json_values.push(value);
+                lines.push(idx + 1);
+                line_warnings.extend(duplicates);
+            }
+            Err(e) => {
+                // The first bad line may just mean this is a multi-line JSON document
+                if !tried_single {
+                    tried_single = true;
+                    control.check_cancelled()?;
+                    if let Ok((value, duplicates)) = parse_json_document(content) {
+                        warnings.extend(duplicates);
+                        return Ok(ParseResult::Single(value));
+                    }
+                }
+                errors.push(LineError {
+                    line: idx + 1,
+                    offset: line_offset,
+                    message: e.to_string(),
+                    index: json_values.len(),
+                });
+            }
+        }
+        if (idx + 1).is_multiple_of(ROWS_PER_PROGRESS_REPORT) {
+            control.check_cancelled()?;
+            control.report(ParseProgress {
+                bytes_processed: offset as u64,
+                total_bytes,
+                rows_processed: json_values.len(),
+            });
+        }
+    }
+
+    warnings.extend(line_warnings);
+    Ok(ParseResult::JsonL {
+        values: json_values,
+        lines,
+        errors,
+        separator: JsonSeparator::Newline,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_single_json_object() {
+        let content = r#"{"name": "test", "value": 42}"#;
+        let result = parse_json_content(content).unwrap();
+
+        match result {
+            ParseResult::Single(value) => {
+                assert_eq!(value["name"], "test");
+                assert_eq!(value["value"], 42);
+            }
+            _ => panic!("Expected Single result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_single_json_array() {
+        let content = r#"[1, 2, 3, "four"]"#;
+        let result = parse_json_content(content).unwrap();
+
+        match result {
+            ParseResult::Single(value) => {
+                assert!(value.is_array());
+                assert_eq!(value.as_array().unwrap().len(), 4);
+            }
+            _ => panic!("Expected Single result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_jsonl_multiple_objects() {
+        let content = r#"{"name": "first"}
+{"name": "second", "value": 42}
+{"name": "third"}"#;
+        let result = parse_json_content(content).unwrap();
+
+        match result {
+            ParseResult::JsonL { values, .. } => {
+                assert_eq!(values.len(), 3);
+                assert_eq!(values[0]["name"], "first");
+                assert_eq!(values[1]["name"], "second");
+                assert_eq!(values[1]["value"], 42);
+                assert_eq!(values[2]["name"], "third");
+            }
+            _ => panic!("Expected JsonL result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_jsonl_with_empty_lines() {
+        let content = r#"{"name": "first"}
+
+{"name": "second"}
+
+{"name": "third"}"#;
+        let result = parse_json_content(content).unwrap();
+
+        match result {
+            ParseResult::JsonL { values, .. } => {
+                assert_eq!(values.len(), 3);
+                assert_eq!(values[0]["name"], "first");
+                assert_eq!(values[1]["name"], "second");
+                assert_eq!(values[2]["name"], "third");
+            }
+            _ => panic!("Expected JsonL result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_jsonl_with_bad_lines() {
+        let content = "{\"name\": \"first\"}\n{\"name\": oops}\r\n\n{\"name\": \"third\"}\n[1,\n";
+        let result = parse_json_content(content).unwrap();
+
+        match result {
+            ParseResult::JsonL { values, errors, .. } => {
+                assert_eq!(values.len(), 2);
+                assert_eq!(values[1]["name"], "third");
+
+                assert_eq!(errors.len(), 2);
+                assert_eq!(errors[0].line, 2);
+                assert_eq!(errors[0].offset, 18);
+                assert_eq!(errors[0].index, 1);
+                assert!(errors[0].message.contains("expected value"));
+                assert_eq!(errors[1].line, 5);
+                assert_eq!(errors[1].index, 2);
+            }
+            _ => panic!("Expected JsonL result"),
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_multiline_json() {
+        // Mostly-unparseable lines mean this is broken JSON, not JSONL
+        let content = "{\n  \"name\": \"test\",\n  \"value\": [1, 2\n}\n";
+        match parse_json_content(content) {
+            Err(ParseError::InvalidJson(_)) => {}
+            other => panic!("Expected InvalidJson error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_concatenated_json() {
+        for content in [
+            "{\"a\": 1}{\"a\": 2} {\"a\": 3}",
+            "{\n  \"a\": 1\n}\n{\n  \"a\": 2\n}{\"a\": 3}\n",
+        ] {
+            match parse_json_content(content).unwrap() {
+                ParseResult::JsonL {
+                    values,
+                    errors,
+                    separator,
+                    ..
+                } => {
+                    assert_eq!(values.len(), 3);
+                    assert_eq!(values[2]["a"], 3);
+                    assert!(errors.is_empty());
+                    assert_eq!(separator, JsonSeparator::Concatenated);
+                }
+                other => panic!("Expected JsonL result, got {:?}", other),
+            }
+        }
+
+        // A value cut off at the end is reported where it starts
+        let content = "{\n  \"a\": 1\n}\n{\n  \"a\": 2\n}\n{\n  \"a\":";
+        let Ok(ParseResult::JsonL { values, errors, .. }) = parse_json_content(content) else {
+            panic!("Expected JsonL result");
+        };
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            (errors[0].line, errors[0].offset, errors[0].index),
+            (7, 26, 2)
+        );
+
+        // Scalars separated by spaces are not a stream of values
+        assert!(parse_json_content("1 2 3").is_err());
+    }
+
+    #[test]
+    fn test_forced_jsonl_never_reads_single_document() {
+        // Auto-detection reads this as one multi-line document
+        let content = "{\n\"a\": 1\n}\n";
+        assert!(matches!(
+            parse_json_content(content),
+            Ok(ParseResult::Single(_))
+        ));
+
+        let result =
+            parse_jsonl_content_with_control(content, &ParseControl::default(), &mut Vec::new());
+        let Ok(ParseResult::JsonL { values, errors, .. }) = result else {
+            panic!("expected JSONL, got {:?}", result);
+        };
+        assert!(values.is_empty());
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn test_duplicate_keys_in_values() {
+        let parse = |content: &str| {
+            let mut warnings = Vec::new();
+            let result =
+                parse_json_collecting_warnings(content, &ParseControl::default(), &mut warnings);
+            let Ok(ParseResult::JsonL { values, .. }) = result else {
+                panic!("expected JSONL, got {:?}", result);
+            };
+            (values, warnings)
+        };
+
+        let (values, warnings) = parse("{\"a\": 1}\n{\"a\": 2, \"a\": 3}\n");
+        assert_eq!(values[1], serde_json::json!({"a": 2, "a (dup #2)": 3}));
+        assert_eq!(
+            warnings,
+            ["Duplicate key \"a\" in $[1], shown as \"a (dup #2)\""]
+        );
+
+        let (values, warnings) = parse("{\"b\": 1, \"b\": 2}{\"c\": 3}");
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            warnings,
+            ["Duplicate key \"b\" in $[0], shown as \"b (dup #2)\""]
+        );
+
+        let (values, warnings) = parse("\u{1e}[1]\n\u{1e}{\"c\": {\"d\": 1, \"d\": 2}}\n");
+        assert_eq!(values[1]["c"]["d (dup #2)"], 2);
+        assert_eq!(
+            warnings,
+            ["Duplicate key \"d\" in $[1].c, shown as \"d (dup #2)\""]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_json() {
+        let content = r#"{"name": "test", invalid}"#;
+        let result = parse_json_content(content);
+
+        assert!(result.is_err());
+        match result {
+            Err(ParseError::InvalidJson(_)) => {}
+            _ => panic!("Expected InvalidJson error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_json_content_reports_progress() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let content: String = (0..2500).map(|i| format!("{{\"id\": {}}}\n", i)).collect();
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let reports_clone = reports.clone();
+        let control = ParseControl::new(Arc::new(AtomicBool::new(false)), move |progress| {
+            reports_clone.borrow_mut().push(progress.clone());
+        });
+
+        let result = parse_json_content_with_control(&content, &control).unwrap();
+        match result {
+            ParseResult::JsonL { values, .. } => assert_eq!(values.len(), 2500),
+            _ => panic!("Expected JsonL result"),
+        }
+
+        let reports = reports.borrow();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].rows_processed, 1000);
+        assert_eq!(reports[1].rows_processed, 2000);
+        assert!(reports[1].bytes_processed > reports[0].bytes_processed);
+        assert_eq!(reports[1].total_bytes, Some(content.len() as u64));
+    }
+}
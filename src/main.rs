@@ -12,47 +12,92 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
+mod archive;
+mod arrow_file;
+mod arrow_json;
+mod avro_file;
+mod binary_reader;
+mod command_line;
+mod compression;
+mod config_reader;
+mod content_sniffing;
+mod csv_reader;
+mod dataset;
 mod document_store;
+mod encoding;
+mod file_loader;
 mod json_reader;
+mod json_value;
+mod jsonl_index;
+mod jsonl_reader;
+mod notebook;
+mod parquet_file;
 mod path_formatting;
 mod search;
 mod tree_builder;
 mod value_formatting;
 mod value_lookup;
 
-use document_store::{JsonLDocument, StoredDocument};
+use command_line::{stdin_is_redirected, CommandLine, USAGE};
+use compression::Compression;
+use document_store::{KeyOrder, StoredDocument, DEFAULT_PARQUET_ROWS_PER_PAGE};
+use file_loader::FileLoader;
 use gtk::prelude::*;
 use gtk::{
     AccelGroup, Application, ApplicationWindow, Box as GtkBox, Button, CellRendererText,
-    CheckButton, Clipboard, Entry, FileChooserAction, FileChooserDialog, Menu, MenuBar, MenuItem,
-    Orientation, Paned, ResponseType, ScrolledWindow, Separator, TextBuffer, TextView, TreeStore,
-    TreeView, TreeViewColumn,
+    CheckButton, CheckMenuItem, Clipboard, Entry, FileChooserAction, FileChooserDialog, Menu,
+    MenuBar, MenuItem, Orientation, Paned, ProgressBar, RadioMenuItem, ResponseType,
+    ScrolledWindow, Separator, TextBuffer, TextView, TreeStore, TreeView, TreeViewColumn,
 };
-use json_reader::{parse_file, parse_text_content, ParseResult};
-use search::{find_all_occurrences, find_occurrence_to_highlight};
-use std::path::Path;
-use tree_builder::{add_jsonl_to_tree, add_single_value_to_tree};
-use value_formatting::{format_value_for_display, format_value_literal};
+use json_reader::{parse_text_input, InputSource, ParsedInput, SourceLocation, SyntaxError};
+use json_value::ValueOrigin;
+use search::{find_occurrence_to_highlight, spawn_search, SearchMatch, SearchTarget};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tree_builder::{
+    add_archive_to_tree, add_failed_load_to_tree, add_jsonl_to_tree, add_notebook_to_tree,
+    add_paged_to_tree, add_single_value_to_tree, is_placeholder, materialize_path,
+    populate_placeholder, reorder_children, unload_children,
+};
+use value_formatting::format_value_for_display;
+
+/// An input that failed to load with a syntax error, kept for "Jump to Error".
+struct FailedLoad {
+    /// The error as shown in the value pane
+    message: String,
+    error: SyntaxError,
+}
+
+/// Inputs that failed to load with a syntax error, by the doc_id of their root row.
+type FailedLoads = std::rc::Rc<std::cell::RefCell<std::collections::HashMap<i64, FailedLoad>>>;
 
 fn main() {
     // Read command-line arguments before GTK initialization
-    // Collect all arguments after the program name
-    let file_paths: Vec<String> = std::env::args().skip(1).collect();
+    let mut command_line = match CommandLine::parse(std::env::args().skip(1)) {
+        Ok(command_line) => command_line,
+        Err(message) => {
+            eprintln!("slopjson: {}\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+    // With no files, read piped input, as in `curl ... | slopjson`
+    if command_line.inputs.is_empty() && stdin_is_redirected() {
+        command_line.inputs.push(InputSource::Stdin);
+    }
 
     let app = Application::builder()
         .application_id("com.example.slopjson")
         .build();
 
-    let file_paths_clone = file_paths.clone();
     app.connect_activate(move |app| {
-        build_ui(app, &file_paths_clone);
+        build_ui(app, &command_line);
     });
 
     // Run with empty args to prevent GTK from trying to handle file arguments
     app.run_with_args(&[] as &[&str]);
 }
 
-fn build_ui(app: &Application, initial_files: &[String]) {
+fn build_ui(app: &Application, command_line: &CommandLine) {
     let window = ApplicationWindow::builder()
         .application(app)
         .title("slopjson")
@@ -170,19 +215,60 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let documents: std::rc::Rc<std::cell::RefCell<Vec<Option<StoredDocument>>>> =
         std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
 
+    // Whether collapsing a row frees its children (they are recreated on the next expand)
+    let unload_collapsed_rows: std::rc::Rc<std::cell::Cell<bool>> =
+        std::rc::Rc::new(std::cell::Cell::new(false));
+
+    // Number of rows decoded at a time when paging through a Parquet file
+    let parquet_rows_per_page: std::rc::Rc<std::cell::Cell<usize>> =
+        std::rc::Rc::new(std::cell::Cell::new(DEFAULT_PARQUET_ROWS_PER_PAGE));
+
+    // Whether object members are shown in file order or sorted by key
+    let key_order: std::rc::Rc<std::cell::Cell<KeyOrder>> =
+        std::rc::Rc::new(std::cell::Cell::new(KeyOrder::default()));
+
+    let failed_loads: FailedLoads = std::rc::Rc::new(std::cell::RefCell::new(Default::default()));
+
+    let tree_store_for_collapse = tree_store.clone();
+    let documents_for_collapse = documents.clone();
+    let unload_collapsed_rows_for_collapse = unload_collapsed_rows.clone();
+    tree_view.connect_row_collapsed(move |_tree_view, iter, _path| {
+        if unload_collapsed_rows_for_collapse.get() {
+            unload_children(&tree_store_for_collapse, iter);
+
+            // Also drop any rows that were decoded on demand for this node
+            let doc_id = tree_store_for_collapse
+                .value(iter, 4)
+                .get::<i64>()
+                .unwrap_or(-1);
+            let data_path = tree_store_for_collapse
+                .value(iter, 3)
+                .get::<String>()
+                .unwrap_or_default();
+            let mut docs = documents_for_collapse.borrow_mut();
+            if let Some(doc) = docs.get_mut(doc_id as usize).and_then(|doc| doc.as_mut()) {
+                doc.release_rows(&data_path);
+            }
+        }
+    });
+
     // Handle tree selection
     let selection = tree_view.selection();
     let path_entry_clone = path_entry.clone();
     let value_text_buffer = value_text_view.buffer().unwrap();
     let value_text_buffer_clone = value_text_buffer.clone();
     let documents_for_selection = documents.clone();
+    let failed_loads_for_selection = failed_loads.clone();
 
-    // We'll update this in the selection handler
+    // We'll update these in the selection handler
     let remove_file_menu_item_for_selection =
         std::rc::Rc::new(std::cell::RefCell::new(None::<MenuItem>));
+    let jump_to_error_menu_item_for_selection =
+        std::rc::Rc::new(std::cell::RefCell::new(None::<MenuItem>));
 
     selection.connect_changed({
         let remove_file_menu_item_for_selection = remove_file_menu_item_for_selection.clone();
+        let jump_to_error_menu_item_for_selection = jump_to_error_menu_item_for_selection.clone();
         move |sel| {
             if let Some((model, iter)) = sel.selected() {
                 let path = model.value(&iter, 2).get::<String>().unwrap_or_default();
@@ -194,22 +280,41 @@ fn build_ui(app: &Application, initial_files: &[String]) {
 
                 // Format the JSON value nicely
                 let preview = model.value(&iter, 1).get::<String>().unwrap_or_default();
-                let formatted_value = {
+                let (formatted_value, image) = {
                     let docs = documents_for_selection.borrow();
-                    let value = docs
-                        .get(doc_id as usize)
-                        .and_then(|doc| doc.as_ref())
-                        .and_then(|doc| doc.lookup_value(&data_path));
-                    format_value_for_display(value, &preview)
+                    let doc = docs.get(doc_id as usize).and_then(|doc| doc.as_ref());
+                    let value = doc.and_then(|doc| doc.lookup_value(&data_path));
+                    let origin = doc.map_or(ValueOrigin::Json, |doc| doc.value_origin(&data_path));
+                    (
+                        format_value_for_display(value, origin, &preview),
+                        value
+                            .filter(|_| origin == ValueOrigin::Converted)
+                            .and_then(notebook::image_bytes),
+                    )
                 };
+                let failed_loads = failed_loads_for_selection.borrow();
+                let failed_load = failed_loads.get(&doc_id);
 
-                value_text_buffer_clone.set_text(&formatted_value);
+                match failed_load {
+                    Some(failed_load) => value_text_buffer_clone.set_text(&failed_load.message),
+                    None => value_text_buffer_clone.set_text(&formatted_value),
+                }
+                if let Some(image) = image {
+                    show_image(&value_text_buffer_clone, image);
+                }
+                trim_document_cache(&documents_for_selection, doc_id);
 
                 // Enable/disable Remove File menu item based on whether a root node is selected
                 if let Some(ref menu_item) = *remove_file_menu_item_for_selection.borrow() {
                     let is_root_node = model.iter_parent(&iter).is_none();
                     menu_item.set_sensitive(is_root_node);
                 }
+                // Jump to Error shows the source of the selected input that failed to load
+                if let Some(ref menu_item) = *jump_to_error_menu_item_for_selection.borrow() {
+                    menu_item.set_sensitive(failed_load.is_some_and(|failed_load| {
+                        failed_load.error.source_text.is_some() || failed_load.error.path.is_some()
+                    }));
+                }
             } else {
                 path_entry_clone.set_text("");
                 value_text_buffer_clone
@@ -220,6 +325,9 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                 if let Some(ref menu_item) = *remove_file_menu_item_for_selection.borrow() {
                     menu_item.set_sensitive(false);
                 }
+                if let Some(ref menu_item) = *jump_to_error_menu_item_for_selection.borrow() {
+                    menu_item.set_sensitive(false);
+                }
             }
         }
     });
@@ -231,6 +339,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
         path_entry: &Entry,
         value_text_buffer: &TextBuffer,
         documents: &std::rc::Rc<std::cell::RefCell<Vec<Option<StoredDocument>>>>,
+        failed_loads: &FailedLoads,
     ) {
         if let Some((model, iter)) = selection.selected() {
             // Check if this is a root node (no parent)
@@ -242,6 +351,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                     if let Some(doc) = documents.borrow_mut().get_mut(doc_id as usize) {
                         *doc = None;
                     }
+                    failed_loads.borrow_mut().remove(&doc_id);
                 }
 
                 // Clear the display if we deleted the selected item
@@ -264,6 +374,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let value_text_buffer_for_menu = value_text_buffer.clone();
     let tree_view_for_menu = tree_view.clone();
     let documents_for_menu = documents.clone();
+    let failed_loads_for_menu = failed_loads.clone();
     tree_view.connect_button_press_event(move |tree_view, event| {
         // Check for right-click (button 3)
         if event.button() == 3 {
@@ -284,6 +395,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                         let value_text_buffer_clone = value_text_buffer_for_menu.clone();
                         let tree_view_clone = tree_view_for_menu.clone();
                         let documents_clone = documents_for_menu.clone();
+                        let failed_loads_clone = failed_loads_for_menu.clone();
                         remove_item.connect_activate(move |_| {
                             let selection = tree_view_clone.selection();
                             remove_root_node(
@@ -292,6 +404,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                                 &path_entry_clone,
                                 &value_text_buffer_clone,
                                 &documents_clone,
+                                &failed_loads_clone,
                             );
                         });
 
@@ -306,6 +419,155 @@ fn build_ui(app: &Application, initial_files: &[String]) {
         gtk::glib::Propagation::Proceed
     });
 
+    // Create progress toolbar for background loads (initially hidden)
+    let progress_toolbar = GtkBox::new(Orientation::Horizontal, 6);
+    progress_toolbar.set_margin_start(6);
+    progress_toolbar.set_margin_end(6);
+    progress_toolbar.set_margin_top(3);
+    progress_toolbar.set_margin_bottom(3);
+    progress_toolbar.set_visible(false);
+    progress_toolbar.set_no_show_all(true);
+
+    let progress_label = gtk::Label::new(None);
+    progress_toolbar.pack_start(&progress_label, false, false, 0);
+
+    let progress_bar = ProgressBar::new();
+    progress_bar.set_show_text(true);
+    progress_bar.set_hexpand(true);
+    progress_bar.set_valign(gtk::Align::Center);
+    progress_toolbar.pack_start(&progress_bar, true, true, 0);
+
+    let cancel_load_button = Button::with_label("Cancel");
+    progress_toolbar.pack_start(&cancel_load_button, false, false, 0);
+
+    // Files are parsed on a worker thread and added to the tree when done
+    let file_loader = {
+        let tree_store = tree_store.clone();
+        let value_text_buffer = value_text_buffer.clone();
+        let documents = documents.clone();
+        let parquet_rows_per_page = parquet_rows_per_page.clone();
+        let selection = selection.clone();
+        let failed_loads = failed_loads.clone();
+        FileLoader::new(
+            progress_toolbar.clone(),
+            progress_label.clone(),
+            progress_bar.clone(),
+            move |result, name| {
+                load_parse_result(
+                    result,
+                    name,
+                    &tree_store,
+                    &selection,
+                    &value_text_buffer,
+                    "Error parsing file",
+                    &documents,
+                    &failed_loads,
+                    parquet_rows_per_page.get(),
+                );
+            },
+        )
+    };
+
+    let file_loader_for_cancel = file_loader.clone();
+    cancel_load_button.connect_clicked(move |_| {
+        file_loader_for_cancel.cancel();
+    });
+
+    // Create child rows lazily, the first time a row is expanded. Pages of
+    // Parquet, Arrow and Avro rows are decoded on the loader's worker thread
+    // first, and the row keeps its placeholder child until they are ready
+    let tree_store_for_expand = tree_store.clone();
+    let documents_for_expand = documents.clone();
+    let key_order_for_expand = key_order.clone();
+    let file_loader_for_expand = file_loader.clone();
+    // Rows whose page is being decoded, by doc_id and data path
+    let decoding_rows: std::rc::Rc<std::cell::RefCell<std::collections::HashSet<(i64, String)>>> =
+        Default::default();
+    tree_view.connect_row_expanded(move |tree_view, iter, path| {
+        let doc_id = tree_store_for_expand
+            .value(iter, 4)
+            .get::<i64>()
+            .unwrap_or(-1);
+        let data_path = tree_store_for_expand
+            .value(iter, 3)
+            .get::<String>()
+            .unwrap_or_default();
+        let has_placeholder = tree_store_for_expand
+            .iter_children(Some(iter))
+            .is_some_and(|child| is_placeholder(&tree_store_for_expand, &child));
+        let decoder = if has_placeholder {
+            documents_for_expand
+                .borrow()
+                .get(doc_id as usize)
+                .and_then(|doc| doc.as_ref())
+                .and_then(|doc| doc.page_decoder(&data_path))
+        } else {
+            None
+        };
+        if let Some(decoder) = decoder {
+            if !decoding_rows
+                .borrow_mut()
+                .insert((doc_id, data_path.clone()))
+            {
+                return;
+            }
+            let name = tree_store_for_expand
+                .value(iter, 0)
+                .get::<String>()
+                .unwrap_or_default();
+            let row = gtk::TreeRowReference::new(&tree_store_for_expand, path);
+            let tree_view = tree_view.clone();
+            let tree_store = tree_store_for_expand.clone();
+            let documents = documents_for_expand.clone();
+            let key_order = key_order_for_expand.clone();
+            let decoding_rows = decoding_rows.clone();
+            file_loader_for_expand.decode_page(decoder, name, move |result| {
+                decoding_rows
+                    .borrow_mut()
+                    .remove(&(doc_id, data_path.clone()));
+                // The row may have been removed, with its file, while the page was decoded
+                let Some(path) = row.and_then(|row| row.path()) else {
+                    return;
+                };
+                let Some(iter) = tree_store.iter(&path) else {
+                    return;
+                };
+                let Ok(decoded) = result else {
+                    // Cancelled; expanding the row again decodes the page again
+                    tree_view.collapse_row(&path);
+                    return;
+                };
+                if let Some(doc) = documents
+                    .borrow_mut()
+                    .get_mut(doc_id as usize)
+                    .and_then(|doc| doc.as_mut())
+                {
+                    doc.store_page(&data_path, decoded);
+                }
+                if tree_view.row_expanded(&path) {
+                    let docs = documents.borrow();
+                    if let Some(doc) = docs.get(doc_id as usize).and_then(|doc| doc.as_ref()) {
+                        populate_placeholder(&tree_store, &iter, doc, key_order.get());
+                    }
+                }
+                trim_document_cache(&documents, doc_id);
+            });
+            return;
+        }
+        {
+            let docs = documents_for_expand.borrow();
+            if let Some(doc) = docs.get(doc_id as usize).and_then(|doc| doc.as_ref()) {
+                populate_placeholder(
+                    &tree_store_for_expand,
+                    iter,
+                    doc,
+                    key_order_for_expand.get(),
+                );
+            }
+        }
+        trim_document_cache(&documents_for_expand, doc_id);
+    });
+
     // Create menu bar
     let menu_bar = MenuBar::new();
 
@@ -323,16 +585,12 @@ fn build_ui(app: &Application, initial_files: &[String]) {
         ModifierType::CONTROL_MASK,
         gtk::AccelFlags::VISIBLE,
     );
-    let tree_store_for_open = tree_store.clone();
-    let value_text_buffer_for_open = value_text_buffer.clone();
+    let file_loader_for_open = file_loader.clone();
     let window_clone = window.clone();
-    let documents_for_open = documents.clone();
 
     open_menu_item.connect_activate(move |_| {
-        let tree_store_clone = tree_store_for_open.clone();
-        let value_text_buffer_clone = value_text_buffer_for_open.clone();
+        let file_loader_clone = file_loader_for_open.clone();
         let window_clone2 = window_clone.clone();
-        let documents_clone = documents_for_open.clone();
 
         let dialog = FileChooserDialog::new(
             Some("Open File"),
@@ -352,13 +610,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                             .and_then(|n| n.to_str())
                             .unwrap_or("Unknown")
                             .to_string();
-                        load_json_content_from_file(
-                            &path,
-                            Some(&name),
-                            &tree_store_clone,
-                            &value_text_buffer_clone,
-                            &documents_clone,
-                        );
+                        file_loader_clone.load(InputSource::File(path), None, name);
                     }
                 }
             }
@@ -370,6 +622,49 @@ fn build_ui(app: &Application, initial_files: &[String]) {
 
     file_menu.append(&open_menu_item);
 
+    // Open Folder menu item: opens a directory of shards as one dataset
+    let open_folder_menu_item = MenuItem::with_label("Open Folder");
+    open_folder_menu_item.add_accelerator(
+        "activate",
+        &accel_group,
+        *keys::o,
+        ModifierType::CONTROL_MASK | ModifierType::SHIFT_MASK,
+        gtk::AccelFlags::VISIBLE,
+    );
+    let file_loader_for_open_folder = file_loader.clone();
+    let window_for_open_folder = window.clone();
+
+    open_folder_menu_item.connect_activate(move |_| {
+        let file_loader_clone = file_loader_for_open_folder.clone();
+
+        let dialog = FileChooserDialog::new(
+            Some("Open Folder"),
+            Some(&window_for_open_folder),
+            FileChooserAction::SelectFolder,
+        );
+
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Open", ResponseType::Accept);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == ResponseType::Accept {
+                if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                    let name = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("Unknown")
+                        .to_string();
+                    file_loader_clone.load(InputSource::File(path), None, name);
+                }
+            }
+            dialog.close();
+        });
+
+        dialog.show();
+    });
+
+    file_menu.append(&open_folder_menu_item);
+
     // Exit menu item
     let exit_menu_item = MenuItem::with_label("Exit");
     exit_menu_item.add_accelerator(
@@ -402,11 +697,15 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let tree_store_for_clipboard = tree_store.clone();
     let value_text_buffer_for_clipboard = value_text_buffer.clone();
     let documents_for_clipboard = documents.clone();
+    let selection_for_clipboard = selection.clone();
+    let failed_loads_for_clipboard = failed_loads.clone();
 
     paste_menu_item.connect_activate(move |_| {
         let tree_store_clone = tree_store_for_clipboard.clone();
         let value_text_buffer_clone = value_text_buffer_for_clipboard.clone();
         let documents_clone = documents_for_clipboard.clone();
+        let selection_clone = selection_for_clipboard.clone();
+        let failed_loads_clone = failed_loads_for_clipboard.clone();
 
         let clipboard = Clipboard::get(&gtk::gdk::SELECTION_CLIPBOARD);
         clipboard.request_text(move |_clipboard, text| {
@@ -415,8 +714,10 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                     &content,
                     Some("Clipboard"),
                     &tree_store_clone,
+                    &selection_clone,
                     &value_text_buffer_clone,
                     &documents_clone,
+                    &failed_loads_clone,
                 );
             } else {
                 value_text_buffer_clone.set_text("Clipboard is empty or does not contain text");
@@ -489,6 +790,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let path_entry_for_remove = path_entry.clone();
     let value_text_buffer_for_remove = value_text_buffer.clone();
     let documents_for_remove = documents.clone();
+    let failed_loads_for_remove = failed_loads.clone();
 
     remove_file_menu_item.connect_activate(move |_| {
         let selection = selection_for_remove.clone();
@@ -498,6 +800,7 @@ fn build_ui(app: &Application, initial_files: &[String]) {
             &path_entry_for_remove,
             &value_text_buffer_for_remove,
             &documents_for_remove,
+            &failed_loads_for_remove,
         );
     });
 
@@ -511,14 +814,148 @@ fn build_ui(app: &Application, initial_files: &[String]) {
         gtk::AccelFlags::VISIBLE,
     );
 
+    // Jump to Error menu item: shows the source of the selected failed input at the error
+    let jump_to_error_menu_item = MenuItem::with_label("Jump to Error");
+    // Enabled when an input that failed to load is selected
+    jump_to_error_menu_item.set_sensitive(false);
+    jump_to_error_menu_item.add_accelerator(
+        "activate",
+        &accel_group,
+        *keys::j,
+        ModifierType::CONTROL_MASK,
+        gtk::AccelFlags::VISIBLE,
+    );
+    *jump_to_error_menu_item_for_selection.borrow_mut() = Some(jump_to_error_menu_item.clone());
+    let value_text_view_for_jump = value_text_view.clone();
+    let selection_for_jump = selection.clone();
+    let failed_loads_for_jump = failed_loads.clone();
+    jump_to_error_menu_item.connect_activate(move |_| {
+        let buffer = value_text_view_for_jump.buffer().unwrap();
+        let doc_id = selection_for_jump
+            .selected()
+            .map(|(model, iter)| model.value(&iter, 4).get::<i64>().unwrap_or(-1));
+        let failed_loads = failed_loads_for_jump.borrow();
+        let Some(error) = doc_id
+            .and_then(|doc_id| failed_loads.get(&doc_id))
+            .map(|failed_load| &failed_load.error)
+        else {
+            buffer.set_text("Select an input that failed to load to jump to its error");
+            return;
+        };
+        let Some(location) = error.location else {
+            buffer.set_text(&format!(
+                "{}\n\nThe parser did not report a location",
+                error
+            ));
+            return;
+        };
+
+        let source = match (&error.source_text, &error.path) {
+            (Some(text), _) => Ok(text.clone()),
+            (None, Some(path)) => read_error_source(path, error.compression),
+            (None, None) => Err("The source is no longer available".to_string()),
+        };
+        match source {
+            Ok(source) => show_error_source(&value_text_view_for_jump, &source, location),
+            Err(message) => buffer.set_text(&format!(
+                "{}\n\n{}\n\n{}",
+                error,
+                error.source_preview(),
+                message
+            )),
+        }
+    });
+
     edit_menu.append(&paste_menu_item);
     edit_menu.append(&copy_menu_item);
     edit_menu.append(&remove_file_menu_item);
     edit_menu.append(&find_menu_item);
+    edit_menu.append(&jump_to_error_menu_item);
+
+    // View menu
+    let view_menu = Menu::new();
+    let view_menu_item = MenuItem::with_label("View");
+    view_menu_item.set_submenu(Some(&view_menu));
+
+    // Unload Collapsed Rows menu item
+    let unload_collapsed_menu_item = CheckMenuItem::with_label("Unload Collapsed Rows");
+    unload_collapsed_menu_item.set_active(unload_collapsed_rows.get());
+    let unload_collapsed_rows_for_menu = unload_collapsed_rows.clone();
+    unload_collapsed_menu_item.connect_toggled(move |item| {
+        unload_collapsed_rows_for_menu.set(item.is_active());
+    });
+    view_menu.append(&unload_collapsed_menu_item);
+
+    // Page Size submenu, for Parquet, Arrow and Avro files and large JSONL files;
+    // applies to files opened afterwards
+    let page_size_menu = Menu::new();
+    let page_size_menu_item = MenuItem::with_label("Page Size");
+    page_size_menu_item.set_submenu(Some(&page_size_menu));
+    let mut page_size_group: Option<RadioMenuItem> = None;
+    for rows_per_page in [100, 1000, 10000, 100000] {
+        let item = match &page_size_group {
+            Some(group) => RadioMenuItem::with_label_from_widget(
+                group,
+                Some(&format!("{} Rows", rows_per_page)),
+            ),
+            None => RadioMenuItem::with_label(&format!("{} Rows", rows_per_page)),
+        };
+        item.set_active(rows_per_page == parquet_rows_per_page.get());
+        let parquet_rows_per_page_for_menu = parquet_rows_per_page.clone();
+        item.connect_toggled(move |item| {
+            if item.is_active() {
+                parquet_rows_per_page_for_menu.set(rows_per_page);
+            }
+        });
+        page_size_menu.append(&item);
+        page_size_group.get_or_insert(item);
+    }
+    view_menu.append(&page_size_menu_item);
+
+    // Key Order submenu; rows already in the tree are reordered in place
+    let key_order_menu = Menu::new();
+    let key_order_menu_item = MenuItem::with_label("Key Order");
+    key_order_menu_item.set_submenu(Some(&key_order_menu));
+    let file_order_item = RadioMenuItem::with_label("File Order");
+    let sorted_order_item =
+        RadioMenuItem::with_label_from_widget(&file_order_item, Some("Sorted by Key"));
+    for (item, order) in [
+        (&file_order_item, KeyOrder::File),
+        (&sorted_order_item, KeyOrder::Sorted),
+    ] {
+        item.set_active(order == key_order.get());
+        let key_order_for_menu = key_order.clone();
+        let tree_store_for_menu = tree_store.clone();
+        let documents_for_menu = documents.clone();
+        item.connect_toggled(move |item| {
+            if !item.is_active() || key_order_for_menu.get() == order {
+                return;
+            }
+            key_order_for_menu.set(order);
+            let docs = documents_for_menu.borrow();
+            if let Some(root_iter) = tree_store_for_menu.iter_first() {
+                loop {
+                    let doc_id = tree_store_for_menu
+                        .value(&root_iter, 4)
+                        .get::<i64>()
+                        .unwrap_or(-1);
+                    if let Some(doc) = docs.get(doc_id as usize).and_then(|doc| doc.as_ref()) {
+                        reorder_children(&tree_store_for_menu, &root_iter, doc, order);
+                    }
+                    if !tree_store_for_menu.iter_next(&root_iter) {
+                        break;
+                    }
+                }
+            }
+        });
+        key_order_menu.append(item);
+    }
+    view_menu.append(&key_order_menu_item);
 
     // Add menus to menu bar
     menu_bar.append(&file_menu_item);
     menu_bar.append(&edit_menu_item);
+    menu_bar.append(&view_menu_item);
 
     // Create search toolbar (initially hidden)
     let search_toolbar = GtkBox::new(Orientation::Horizontal, 6);
@@ -549,13 +986,6 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let close_search_button = Button::with_label("Close");
     search_toolbar.pack_start(&close_search_button, false, false, 0);
 
-    // Match information: tree path and character position within the value
-    #[derive(Clone)]
-    struct SearchMatch {
-        path: gtk::TreePath,
-        is_key_match: bool,
-    }
-
     // Search state: store all matching occurrences and current index
     let search_matches: std::rc::Rc<std::cell::RefCell<Vec<SearchMatch>>> =
         std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
@@ -566,163 +996,10 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let current_case_sensitive: std::rc::Rc<std::cell::RefCell<bool>> =
         std::rc::Rc::new(std::cell::RefCell::new(false));
 
-    // Function to perform search
-    let perform_search = std::rc::Rc::new({
-        let documents_for_search = documents.clone();
-        move |tree_store: &TreeStore,
-              search_text: &str,
-              case_sensitive: bool,
-              search_matches: &std::rc::Rc<std::cell::RefCell<Vec<SearchMatch>>>,
-              search_current_index: &std::rc::Rc<std::cell::RefCell<Option<usize>>>,
-              current_selection: Option<&gtk::TreePath>| {
-            let mut matches = Vec::new();
-            let search_text_lower = if case_sensitive {
-                search_text.to_string()
-            } else {
-                search_text.to_lowercase()
-            };
-
-            // Recursively search through tree - only search leaf nodes
-            fn search_tree(
-                tree_store: &TreeStore,
-                iter: &gtk::TreeIter,
-                search_text: &str,
-                search_text_lower: &str,
-                case_sensitive: bool,
-                matches: &mut Vec<SearchMatch>,
-                documents: &std::rc::Rc<std::cell::RefCell<Vec<Option<StoredDocument>>>>,
-            ) {
-                // Check if this node has children
-                let has_children = tree_store.iter_children(Some(iter)).is_some();
-
-                // Only search leaf nodes (nodes with no children)
-                if !has_children {
-                    if let Some(path) = tree_store.path(iter) {
-                        // Get key (column 0) and value (columns 1 and 3)
-                        let key = tree_store
-                            .value(iter, 0)
-                            .get::<String>()
-                            .unwrap_or_default();
-                        let value_preview = tree_store
-                            .value(iter, 1)
-                            .get::<String>()
-                            .unwrap_or_default();
-                        let data_path = tree_store
-                            .value(iter, 3)
-                            .get::<String>()
-                            .unwrap_or_default();
-                        let doc_id = tree_store.value(iter, 4).get::<i64>().unwrap_or(-1);
-
-                        // Find all occurrences in the key
-                        let key_occurrences =
-                            find_all_occurrences(&key, search_text, case_sensitive);
-                        for (_start, _end) in key_occurrences {
-                            matches.push(SearchMatch {
-                                path: path.clone(),
-                                is_key_match: true,
-                            });
-                        }
-
-                        // Find all occurrences in the value
-                        // We need to format it the same way as we display it, so offsets match
-                        let value_to_search = {
-                            let docs = documents.borrow();
-                            let value = docs
-                                .get(doc_id as usize)
-                                .and_then(|doc| doc.as_ref())
-                                .and_then(|doc| doc.lookup_value(&data_path));
-                            value
-                                .map(format_value_literal)
-                                .unwrap_or_else(|| value_preview.clone())
-                        };
-                        let value_occurrences =
-                            find_all_occurrences(&value_to_search, search_text, case_sensitive);
-                        for (_start, _end) in value_occurrences {
-                            matches.push(SearchMatch {
-                                path: path.clone(),
-                                is_key_match: false,
-                            });
-                        }
-                    }
-                } else {
-                    // This node has children, so recursively search children
-                    if let Some(mut child_iter) = tree_store.iter_children(Some(iter)) {
-                        loop {
-                            search_tree(
-                                tree_store,
-                                &child_iter,
-                                search_text,
-                                search_text_lower,
-                                case_sensitive,
-                                matches,
-                                documents,
-                            );
-                            if !tree_store.iter_next(&mut child_iter) {
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Search from each root node
-            if let Some(mut root_iter) = tree_store.iter_first() {
-                loop {
-                    search_tree(
-                        tree_store,
-                        &root_iter,
-                        search_text,
-                        &search_text_lower,
-                        case_sensitive,
-                        &mut matches,
-                        &documents_for_search,
-                    );
-                    if !tree_store.iter_next(&mut root_iter) {
-                        break;
-                    }
-                }
-            }
-
-            let is_empty = matches.is_empty();
-
-            // Find the starting index based on current selection (before moving matches)
-            let starting_index = if is_empty {
-                None
-            } else if let Some(current_path) = current_selection {
-                // Find the first match at or after the current selection
-                // Compare paths by depth and indices lexicographically
-                let current_depth = current_path.depth();
-                let current_indices: Vec<i32> = current_path.indices().to_vec();
-
-                matches
-                    .iter()
-                    .position(|m| {
-                        let match_depth = m.path.depth();
-                        let match_indices: Vec<i32> = m.path.indices().to_vec();
-
-                        // Compare lexicographically: first by depth, then by indices
-                        if current_depth < match_depth {
-                            true
-                        } else if current_depth > match_depth {
-                            false
-                        } else {
-                            // Same depth, compare indices lexicographically
-                            current_indices <= match_indices
-                        }
-                    })
-                    .or(Some(0)) // If no match found after current position, start from beginning
-            } else {
-                Some(0) // No current selection, start from beginning
-            };
-
-            *search_matches.borrow_mut() = matches;
-            *search_current_index.borrow_mut() = starting_index;
-        }
-    });
-
     // Function to navigate to search result and highlight the occurrence
     let navigate_to_match = std::rc::Rc::new({
         let documents_for_navigation = documents.clone();
+        let key_order_for_navigation = key_order.clone();
         move |tree_view: &TreeView,
               selection: &gtk::TreeSelection,
               tree_store: &TreeStore,
@@ -735,7 +1012,27 @@ fn build_ui(app: &Application, initial_files: &[String]) {
             if let Some(idx) = index {
                 if idx < matches.len() {
                     let search_match = &matches[idx];
-                    let path = &search_match.path;
+
+                    // Create the rows leading to the match if they have not been loaded yet
+                    let materialized = {
+                        let docs = documents_for_navigation.borrow();
+                        docs.get(search_match.doc_id as usize)
+                            .and_then(|doc| doc.as_ref())
+                            .and_then(|doc| {
+                                materialize_path(
+                                    tree_store,
+                                    doc,
+                                    search_match.doc_id,
+                                    &search_match.data_path,
+                                    key_order_for_navigation.get(),
+                                )
+                            })
+                            .and_then(|iter| tree_store.path(&iter))
+                    };
+                    let Some(tree_path) = materialized else {
+                        return;
+                    };
+                    let path = &tree_path;
 
                     // Expand all parent nodes to show the path to the leaf
                     // We need to expand from root to leaf, so we'll go up from the leaf
@@ -772,11 +1069,11 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                             .unwrap_or_default();
                         let formatted_value = {
                             let docs = documents_for_navigation.borrow();
-                            let value = docs
-                                .get(doc_id as usize)
-                                .and_then(|doc| doc.as_ref())
-                                .and_then(|doc| doc.lookup_value(&data_path));
-                            format_value_for_display(value, &preview)
+                            let doc = docs.get(doc_id as usize).and_then(|doc| doc.as_ref());
+                            let value = doc.and_then(|doc| doc.lookup_value(&data_path));
+                            let origin =
+                                doc.map_or(ValueOrigin::Json, |doc| doc.value_origin(&data_path));
+                            format_value_for_display(value, origin, &preview)
                         };
 
                         // Set the text in the buffer
@@ -811,7 +1108,9 @@ fn build_ui(app: &Application, initial_files: &[String]) {
                                 // This contains all matches (both key and value) for the current path, in order
                                 let mut path_matches: Vec<(usize, bool)> = Vec::new();
                                 for (i, m) in matches.iter().enumerate() {
-                                    if m.path == *path {
+                                    if m.doc_id == search_match.doc_id
+                                        && m.data_path == search_match.data_path
+                                    {
                                         path_matches.push((i, m.is_key_match));
                                     }
                                 }
@@ -883,6 +1182,131 @@ fn build_ui(app: &Application, initial_files: &[String]) {
         }
     });
 
+    // Function to perform search
+    // Searches the stored documents rather than the tree rows, since rows are
+    // only created when their parent is expanded. The search runs on a worker
+    // thread over copies of the documents, so the lines, pages and archive
+    // members it reads are not kept by the tree
+    let perform_search = std::rc::Rc::new({
+        let documents_for_search = documents.clone();
+        let key_order_for_search = key_order.clone();
+        let navigate_to_match = navigate_to_match.clone();
+        let tree_store = tree_store.clone();
+        let tree_view = tree_view.clone();
+        let selection = selection.clone();
+        let value_text_buffer = value_text_buffer.clone();
+        let value_text_view = value_text_view.clone();
+        let search_matches = search_matches.clone();
+        let search_current_index = search_current_index.clone();
+        let search_cancel: std::rc::Rc<std::cell::RefCell<Option<Arc<AtomicBool>>>> =
+            std::rc::Rc::new(std::cell::RefCell::new(None));
+        let prev_button = prev_button.clone();
+        let next_button = next_button.clone();
+        move |search_text: &str, case_sensitive: bool| {
+            // A search still running is for text that has since changed
+            if let Some(cancelled) = search_cancel.borrow_mut().take() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            *search_matches.borrow_mut() = Vec::new();
+            *search_current_index.borrow_mut() = None;
+            prev_button.set_sensitive(false);
+            next_button.set_sensitive(false);
+            if search_text.is_empty() {
+                return;
+            }
+
+            // Identify the selected node so the search can start from it
+            let selected_node = selection.selected().map(|(_model, iter)| {
+                (
+                    tree_store.value(&iter, 4).get::<i64>().unwrap_or(-1),
+                    tree_store
+                        .value(&iter, 3)
+                        .get::<String>()
+                        .unwrap_or_default(),
+                )
+            });
+
+            // Search each document, in the order of the root nodes
+            let mut targets = Vec::new();
+            let docs = documents_for_search.borrow();
+            if let Some(root_iter) = tree_store.iter_first() {
+                loop {
+                    let root_name = tree_store
+                        .value(&root_iter, 0)
+                        .get::<String>()
+                        .unwrap_or_default();
+                    let doc_id = tree_store.value(&root_iter, 4).get::<i64>().unwrap_or(-1);
+                    if let Some(doc) = docs.get(doc_id as usize).and_then(|doc| doc.as_ref()) {
+                        let selected_path = selected_node
+                            .as_ref()
+                            .filter(|(selected_doc_id, _)| *selected_doc_id == doc_id)
+                            .map(|(_, data_path)| data_path.clone());
+                        targets.push(SearchTarget {
+                            doc_id,
+                            root_name,
+                            document: doc.shared_copy(),
+                            selected_path,
+                        });
+                    }
+                    if !tree_store.iter_next(&root_iter) {
+                        break;
+                    }
+                }
+            }
+
+            let cancelled = Arc::new(AtomicBool::new(false));
+            *search_cancel.borrow_mut() = Some(cancelled.clone());
+            let receiver = spawn_search(
+                targets,
+                search_text.to_string(),
+                case_sensitive,
+                key_order_for_search.get(),
+                cancelled.clone(),
+            );
+
+            let search_text = search_text.to_string();
+            let navigate_to_match = navigate_to_match.clone();
+            let tree_store = tree_store.clone();
+            let tree_view = tree_view.clone();
+            let selection = selection.clone();
+            let value_text_buffer = value_text_buffer.clone();
+            let value_text_view = value_text_view.clone();
+            let search_matches = search_matches.clone();
+            let search_current_index = search_current_index.clone();
+            let prev_button = prev_button.clone();
+            let next_button = next_button.clone();
+            glib::spawn_future_local(async move {
+                let Ok((matches, starting_index)) = receiver.recv().await else {
+                    return;
+                };
+                // Drop the results if another search started after this one finished
+                if cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                // Show the first match at or after the current selection
+                let has_matches = !matches.is_empty();
+                prev_button.set_sensitive(has_matches);
+                next_button.set_sensitive(has_matches);
+                if has_matches {
+                    (*navigate_to_match)(
+                        &tree_view,
+                        &selection,
+                        &tree_store,
+                        &value_text_buffer,
+                        &value_text_view,
+                        &matches,
+                        starting_index,
+                        &search_text,
+                        case_sensitive,
+                    );
+                }
+                *search_matches.borrow_mut() = matches;
+                *search_current_index.borrow_mut() = starting_index;
+            });
+        }
+    });
+
     // Connect search entry changes
     let tree_store_for_search = tree_store.clone();
     let tree_view_for_search = tree_view.clone();
@@ -893,148 +1317,37 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let search_current_index_clone = search_current_index.clone();
     let current_search_text_clone = current_search_text.clone();
     let current_case_sensitive_clone = current_case_sensitive.clone();
-    let case_sensitive_check_clone = case_sensitive_check.clone();
-    let prev_button_clone = prev_button.clone();
-    let next_button_clone = next_button.clone();
 
     search_entry.connect_changed({
         let perform_search = perform_search.clone();
-        let navigate_to_match = navigate_to_match.clone();
-        let tree_store_clone = tree_store_for_search.clone();
-        let tree_view_clone = tree_view_for_search.clone();
-        let selection_clone = selection_for_search.clone();
-        let value_text_buffer_clone = value_text_buffer_for_search.clone();
-        let value_text_view_clone = value_text_view_for_search.clone();
-        let search_matches_clone2 = search_matches_clone.clone();
-        let search_current_index_clone2 = search_current_index_clone.clone();
         let current_search_text_clone2 = current_search_text_clone.clone();
         let current_case_sensitive_clone2 = current_case_sensitive_clone.clone();
-        let case_sensitive_check_clone2 = case_sensitive_check_clone.clone();
-        let prev_button_clone2 = prev_button_clone.clone();
-        let next_button_clone2 = next_button_clone.clone();
+        let case_sensitive_check_clone = case_sensitive_check.clone();
         move |entry| {
             let search_text = entry.text().to_string();
-            if search_text.is_empty() {
-                *search_matches_clone2.borrow_mut() = Vec::new();
-                *search_current_index_clone2.borrow_mut() = None;
-                prev_button_clone2.set_sensitive(false);
-                next_button_clone2.set_sensitive(false);
-                return;
-            }
-
-            let case_sensitive = case_sensitive_check_clone2.is_active();
+            let case_sensitive = case_sensitive_check_clone.is_active();
             *current_search_text_clone2.borrow_mut() = search_text.clone();
             *current_case_sensitive_clone2.borrow_mut() = case_sensitive;
-
-            // Get current selection path to start search from current view position
-            let current_path = selection_clone
-                .selected()
-                .and_then(|(_model, iter)| tree_store_clone.path(&iter));
-
-            (*perform_search)(
-                &tree_store_clone,
-                &search_text,
-                case_sensitive,
-                &search_matches_clone2,
-                &search_current_index_clone2,
-                current_path.as_ref(),
-            );
-
-            let matches = search_matches_clone2.borrow();
-            let has_matches = !matches.is_empty();
-            prev_button_clone2.set_sensitive(has_matches);
-            next_button_clone2.set_sensitive(has_matches);
-
-            if has_matches {
-                let current_idx = search_current_index_clone2.borrow();
-                (*navigate_to_match)(
-                    &tree_view_clone,
-                    &selection_clone,
-                    &tree_store_clone,
-                    &value_text_buffer_clone,
-                    &value_text_view_clone,
-                    &matches,
-                    *current_idx,
-                    &search_text,
-                    case_sensitive,
-                );
-            }
+            (*perform_search)(&search_text, case_sensitive);
         }
     });
 
     // Connect case sensitivity checkbox
-    let tree_store_for_case = tree_store_for_search.clone();
-    let tree_view_for_case = tree_view_for_search.clone();
-    let selection_for_case = selection_for_search.clone();
-    let value_text_buffer_for_case = value_text_buffer_for_search.clone();
-    let value_text_view_for_case = value_text_view_for_search.clone();
-    let search_entry_for_case = search_entry.clone();
-    let search_matches_for_case = search_matches_clone.clone();
-    let search_current_index_for_case = search_current_index_clone.clone();
-    let current_search_text_for_case = current_search_text_clone.clone();
-    let current_case_sensitive_for_case = current_case_sensitive_clone.clone();
-    let prev_button_for_case = prev_button_clone.clone();
-    let next_button_for_case = next_button_clone.clone();
-
     case_sensitive_check.connect_toggled({
         let perform_search = perform_search.clone();
-        let navigate_to_match = navigate_to_match.clone();
-        let tree_store_clone = tree_store_for_case.clone();
-        let tree_view_clone = tree_view_for_case.clone();
-        let selection_clone = selection_for_case.clone();
-        let value_text_buffer_clone = value_text_buffer_for_case.clone();
-        let value_text_view_clone = value_text_view_for_case.clone();
-        let search_entry_clone = search_entry_for_case.clone();
-        let search_matches_clone2 = search_matches_for_case.clone();
-        let search_current_index_clone2 = search_current_index_for_case.clone();
-        let current_search_text_clone2 = current_search_text_for_case.clone();
-        let current_case_sensitive_clone2 = current_case_sensitive_for_case.clone();
-        let case_sensitive_check_clone = case_sensitive_check.clone();
-        let prev_button_clone2 = prev_button_for_case.clone();
-        let next_button_clone2 = next_button_for_case.clone();
-        move |_| {
+        let search_entry_clone = search_entry.clone();
+        let current_search_text_clone2 = current_search_text_clone.clone();
+        let current_case_sensitive_clone2 = current_case_sensitive_clone.clone();
+        move |check| {
             let search_text = search_entry_clone.text().to_string();
             if search_text.is_empty() {
                 return;
             }
 
-            let case_sensitive = case_sensitive_check_clone.is_active();
+            let case_sensitive = check.is_active();
             *current_search_text_clone2.borrow_mut() = search_text.clone();
             *current_case_sensitive_clone2.borrow_mut() = case_sensitive;
-
-            // Get current selection path to start search from current view position
-            let current_path = selection_clone
-                .selected()
-                .and_then(|(_model, iter)| tree_store_clone.path(&iter));
-
-            (*perform_search)(
-                &tree_store_clone,
-                &search_text,
-                case_sensitive,
-                &search_matches_clone2,
-                &search_current_index_clone2,
-                current_path.as_ref(),
-            );
-
-            let matches = search_matches_clone2.borrow();
-            let has_matches = !matches.is_empty();
-            prev_button_clone2.set_sensitive(has_matches);
-            next_button_clone2.set_sensitive(has_matches);
-
-            if has_matches {
-                let current_idx = search_current_index_clone2.borrow();
-                (*navigate_to_match)(
-                    &tree_view_clone,
-                    &selection_clone,
-                    &tree_store_clone,
-                    &value_text_buffer_clone,
-                    &value_text_view_clone,
-                    &matches,
-                    *current_idx,
-                    &search_text,
-                    case_sensitive,
-                );
-            }
+            (*perform_search)(&search_text, case_sensitive);
         }
     });
 
@@ -1212,107 +1525,284 @@ fn build_ui(app: &Application, initial_files: &[String]) {
     let main_box = GtkBox::new(Orientation::Vertical, 0);
     main_box.pack_start(&menu_bar, false, false, 0);
     main_box.pack_start(&search_toolbar, false, false, 0);
+    main_box.pack_start(&progress_toolbar, false, false, 0);
     main_box.pack_start(&paned, true, true, 0);
 
     window.add(&main_box);
     window.show_all();
 
     // Try to load from command line arguments
-    for file_path in initial_files {
-        let path = Path::new(file_path);
-        if path.exists() {
-            let name = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown")
-                .to_string();
-            load_json_content_from_file(
-                path,
-                Some(&name),
-                &tree_store,
-                &value_text_buffer,
-                &documents,
-            );
+    for input in &command_line.inputs {
+        match input {
+            InputSource::Stdin => {
+                file_loader.load(
+                    InputSource::Stdin,
+                    command_line.format_for(input),
+                    "stdin".to_string(),
+                );
+            }
+            InputSource::File(path) if path.exists() => {
+                let name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("Unknown")
+                    .to_string();
+                file_loader.load(input.clone(), command_line.format_for(input), name);
+            }
+            InputSource::File(_) => {}
         }
     }
 }
 
 /// Loads parsed content into the tree store
+///
+/// Errors are shown in the value pane, with the source around a syntax error.
+/// An input with a syntax error also gets a root row, which is selected, so
+/// that "Jump to Error" can show its source for as long as the row is kept.
+/// Warnings, such as duplicate keys, are listed there too and counted on the root row.
+#[allow(clippy::too_many_arguments)]
 fn load_parse_result(
-    result: Result<ParseResult, json_reader::ParseError>,
+    result: Result<ParsedInput, json_reader::ParseError>,
     default_name: &str,
     tree_store: &TreeStore,
+    selection: &gtk::TreeSelection,
     value_text_buffer: &TextBuffer,
     error_prefix: &str,
     documents: &std::rc::Rc<std::cell::RefCell<Vec<Option<StoredDocument>>>>,
+    failed_loads: &FailedLoads,
+    parquet_rows_per_page: usize,
 ) {
-    match result {
-        Ok(ParseResult::JsonL(json_values)) => {
-            let doc = StoredDocument::JsonL(JsonLDocument::new(json_values));
-            let doc_id = {
-                let mut docs = documents.borrow_mut();
-                let doc_id = docs.len() as i64;
-                docs.push(None);
-                doc_id
+    let parsed = match result {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let mut text = format!("{}: {}", error_prefix, e);
+            let Some(syntax_error) = e.syntax_error() else {
+                value_text_buffer.set_text(&text);
+                return;
             };
-            if let StoredDocument::JsonL(doc) = &doc {
-                add_jsonl_to_tree(tree_store, doc.values(), default_name, default_name, doc_id);
+            let preview = syntax_error.source_preview();
+            if !preview.is_empty() {
+                text.push_str("\n\n");
+                text.push_str(&preview);
+                text.push_str("\nEdit > Jump to Error shows the full source");
             }
-            documents.borrow_mut()[doc_id as usize] = Some(doc);
-        }
-        Ok(ParseResult::Single(value)) => {
-            let doc = StoredDocument::Single(value);
+            value_text_buffer.set_text(&text);
+
             let doc_id = {
                 let mut docs = documents.borrow_mut();
-                let doc_id = docs.len() as i64;
                 docs.push(None);
-                doc_id
+                docs.len() as i64 - 1
             };
-            if let StoredDocument::Single(value) = &doc {
-                add_single_value_to_tree(tree_store, value, default_name, doc_id);
-            }
-            documents.borrow_mut()[doc_id as usize] = Some(doc);
+            let row_preview = match syntax_error.location {
+                Some(location) => format!("Syntax error at line {}", location.line),
+                None => "Syntax error".to_string(),
+            };
+            let root_iter = add_failed_load_to_tree(tree_store, default_name, &row_preview, doc_id);
+            failed_loads.borrow_mut().insert(
+                doc_id,
+                FailedLoad {
+                    message: text,
+                    error: syntax_error.clone(),
+                },
+            );
+            selection.select_iter(&root_iter);
+            return;
         }
-        Err(e) => {
-            value_text_buffer.set_text(&format!("{}: {}", error_prefix, e));
+    };
+
+    let root_name = parsed.root_name(default_name);
+    let origin = parsed.value_origin();
+    let warnings = parsed.warnings;
+    let doc = StoredDocument::from_parse_result(parsed.content, origin, parquet_rows_per_page);
+    let doc_id = {
+        let mut docs = documents.borrow_mut();
+        let doc_id = docs.len() as i64;
+        docs.push(None);
+        doc_id
+    };
+    match &doc {
+        StoredDocument::JsonL(doc) => add_jsonl_to_tree(
+            tree_store,
+            doc.line_count(),
+            Some(doc.error_count()),
+            doc.item_noun(),
+            &root_name,
+            default_name,
+            doc_id,
+        ),
+        StoredDocument::IndexedJsonL(doc) => add_jsonl_to_tree(
+            tree_store,
+            doc.line_count(),
+            None,
+            "lines",
+            &root_name,
+            default_name,
+            doc_id,
+        ),
+        StoredDocument::Single(value, origin) => {
+            add_single_value_to_tree(tree_store, value, *origin, &root_name, doc_id)
+        }
+        StoredDocument::Paged(doc) => add_paged_to_tree(tree_store, doc, &root_name, doc_id),
+        StoredDocument::Archive(doc) => add_archive_to_tree(tree_store, doc, &root_name, doc_id),
+        StoredDocument::Notebook(doc) => add_notebook_to_tree(tree_store, doc, &root_name, doc_id),
+    }
+    documents.borrow_mut()[doc_id as usize] = Some(doc);
+
+    // The document loaded anyway; count the warnings on its root row and list them
+    if !warnings.is_empty() {
+        let root_count = tree_store.iter_n_children(None);
+        if let Some(root_iter) = tree_store.iter_nth_child(None, root_count - 1) {
+            let preview = tree_store
+                .value(&root_iter, 1)
+                .get::<String>()
+                .unwrap_or_default();
+            let preview = match warnings.len() {
+                1 => format!("{}, 1 warning", preview),
+                count => format!("{}, {} warnings", preview, count),
+            };
+            tree_store.set_value(&root_iter, 1, &preview.to_value());
         }
+        value_text_buffer.set_text(&format!(
+            "Warnings for {}:\n\n{}",
+            root_name,
+            warnings.join("\n")
+        ));
     }
 }
 
-fn load_json_content_from_file(
-    path: &Path,
-    name: Option<&str>,
-    tree_store: &TreeStore,
-    value_text_buffer: &TextBuffer,
+/// Files larger than this are not loaded into the value pane by "Jump to Error".
+const MAX_ERROR_SOURCE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Reads the file a syntax error came from, for "Jump to Error".
+///
+/// Files that were decompressed to be parsed are decompressed with the same
+/// codec, up to the same size limit.
+fn read_error_source(
+    path: &std::path::Path,
+    compression: Option<Compression>,
+) -> Result<String, String> {
+    use std::io::Read;
+
+    if let Some(compression) = compression {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        let mut data = Vec::new();
+        compression
+            .decoder(std::io::BufReader::new(file))
+            .map_err(|e| e.to_string())?
+            .take(MAX_ERROR_SOURCE_BYTES + 1)
+            .read_to_end(&mut data)
+            .map_err(|e| e.to_string())?;
+        if data.len() as u64 > MAX_ERROR_SOURCE_BYTES {
+            return Err(format!(
+                "{} is too large to show in full when decompressed",
+                path.display()
+            ));
+        }
+        return Ok(encoding::decode_text(data).text);
+    }
+
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_ERROR_SOURCE_BYTES {
+        return Err(format!(
+            "{} is too large to show in full ({} bytes)",
+            path.display(),
+            size
+        ));
+    }
+    // Decoded the way the file was parsed, so error offsets line up
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    Ok(encoding::decode_text(data).text)
+}
+
+/// Releases the least recently used pages and archive members of a document
+/// beyond what it keeps decoded (see `StoredDocument::trim_cache`), after its
+/// rows were expanded or selected.
+fn trim_document_cache(
     documents: &std::rc::Rc<std::cell::RefCell<Vec<Option<StoredDocument>>>>,
+    doc_id: i64,
 ) {
-    let display_name = name.unwrap_or("File");
-    let result = parse_file(path);
-    load_parse_result(
-        result,
-        &display_name,
-        tree_store,
-        value_text_buffer,
-        "Error parsing file",
-        documents,
-    );
+    // While the documents are borrowed further up the stack, the next expansion
+    // or selection trims the cache instead
+    if let Ok(mut docs) = documents.try_borrow_mut() {
+        if let Some(doc) = docs.get_mut(doc_id as usize).and_then(|doc| doc.as_mut()) {
+            doc.trim_cache();
+        }
+    }
+}
+
+/// Shows an image, such as a notebook cell's plot, in the value pane in place
+/// of its base64 text. Images that can't be decoded leave the text as it is.
+fn show_image(buffer: &TextBuffer, image: Vec<u8>) {
+    if let Ok(pixbuf) = gtk::gdk_pixbuf::Pixbuf::from_read(std::io::Cursor::new(image)) {
+        buffer.set_text("");
+        buffer.insert_pixbuf(&mut buffer.start_iter(), &pixbuf);
+    }
+}
+
+/// Shows `source` in the value pane, highlighting the error's line and placing the cursor at it.
+fn show_error_source(value_text_view: &TextView, source: &str, location: SourceLocation) {
+    let buffer = value_text_view.buffer().unwrap();
+    buffer.set_text(source);
+
+    let error_tag = buffer.tag_table().and_then(|table| {
+        table.lookup("error-line").or_else(|| {
+            let tag = gtk::TextTag::new(Some("error-line"));
+            tag.set_property("background", "pink");
+            table.add(&tag);
+            Some(tag)
+        })
+    });
+
+    // The location is in bytes; GTK iterators use character offsets
+    let mut byte_offset = location.offset.min(source.len());
+    while !source.is_char_boundary(byte_offset) {
+        byte_offset -= 1;
+    }
+    let char_offset = source[..byte_offset].chars().count() as i32;
+    let error_iter = buffer.iter_at_offset(char_offset);
+
+    if let Some(tag) = error_tag {
+        let line_start = buffer.iter_at_line(error_iter.line());
+        let mut line_end = line_start;
+        line_end.forward_to_line_end();
+        buffer.apply_tag(&tag, &line_start, &line_end);
+    }
+    buffer.place_cursor(&error_iter);
+
+    // Scroll once the new text has been laid out
+    let Some(mark) = buffer.create_mark(None, &error_iter, true) else {
+        return;
+    };
+    let value_text_view = value_text_view.clone();
+    glib::idle_add_local_once(move || {
+        value_text_view.scroll_to_mark(&mark, 0.1, true, 0.0, 0.3);
+        if let Some(buffer) = value_text_view.buffer() {
+            buffer.delete_mark(&mark);
+        }
+    });
 }
 
 fn load_json_content(
     content: &str,
     name: Option<&str>,
     tree_store: &TreeStore,
+    selection: &gtk::TreeSelection,
     value_text_buffer: &TextBuffer,
     documents: &std::rc::Rc<std::cell::RefCell<Vec<Option<StoredDocument>>>>,
+    failed_loads: &FailedLoads,
 ) {
     let display_name = name.unwrap_or("Content");
-    let result = parse_text_content(content);
+    // Pasted text cannot be read again, so a syntax error keeps it
+    let result = parse_text_input(content).map_err(|e| e.with_source_text(content.to_string()));
     load_parse_result(
         result,
         &display_name,
         tree_store,
+        selection,
         value_text_buffer,
         "Error parsing content",
         documents,
+        failed_loads,
+        DEFAULT_PARQUET_ROWS_PER_PAGE,
     );
 }
@@ -0,0 +1,471 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading Jupyter notebooks (`.ipynb`) for a cell-by-cell view.
+//!
+//! A notebook is JSON, but its raw tree is hard to read: sources and outputs
+//! are split into arrays of lines, images are base64 strings and tracebacks
+//! are full of terminal color codes. Each cell is converted to an object with
+//!
+//! - `source`: the cell's lines joined into one string
+//! - `outputs`, for code cells: stream text, results and display data with
+//!   their lines joined, tracebacks joined with ANSI escape codes removed, and
+//!   images as `{"$binary": "<base64>", "$mime": "image/png"}` objects
+//!
+//! followed by the cell's other members as written. The notebook's metadata,
+//! such as its kernel and language, is kept apart from the cells.
+
+use crate::json_reader::{ParseError, ParseResult, SyntaxError};
+use crate::json_value::{parse_json_document, BINARY_KEY, MIME_KEY};
+use base64::Engine;
+use serde_json::{Map, Value};
+
+/// Starts every PNG image.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// A Jupyter notebook, with its cells converted for viewing.
+#[derive(Debug)]
+pub struct Notebook {
+    /// The notebook's metadata, such as `kernelspec` and `language_info`,
+    /// with the `nbformat` version
+    pub metadata: Value,
+    pub cells: Vec<NotebookCell>,
+}
+
+/// A notebook cell, converted as described in the module documentation.
+#[derive(Debug)]
+pub struct NotebookCell {
+    /// `code`, `markdown` or `raw`
+    pub cell_type: String,
+    pub value: Value,
+}
+
+impl NotebookCell {
+    /// The first non-blank line of the cell's source, if it has one.
+    pub fn first_line(&self) -> Option<&str> {
+        self.value
+            .get("source")
+            .and_then(Value::as_str)?
+            .lines()
+            .map(str::trim_end)
+            .find(|line| !line.trim().is_empty())
+    }
+}
+
+impl Notebook {
+    /// Converts a parsed notebook, or returns the value unchanged if it has no
+    /// `cells` list.
+    pub fn from_value(value: Value) -> Result<Self, Value> {
+        let Value::Object(mut map) = value else {
+            return Err(value);
+        };
+        let cells = match map.shift_remove("cells") {
+            Some(Value::Array(cells)) => cells,
+            Some(other) => {
+                map.insert("cells".to_string(), other);
+                return Err(Value::Object(map));
+            }
+            None => return Err(Value::Object(map)),
+        };
+
+        let mut metadata = match map.shift_remove("metadata") {
+            Some(Value::Object(metadata)) => metadata,
+            _ => Map::new(),
+        };
+        if let Some(major) = map.get("nbformat").and_then(Value::as_u64) {
+            let minor = map
+                .get("nbformat_minor")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            metadata.insert(
+                "nbformat".to_string(),
+                Value::String(format!("{}.{}", major, minor)),
+            );
+        }
+        Ok(Self {
+            metadata: Value::Object(metadata),
+            cells: cells.into_iter().map(convert_cell).collect(),
+        })
+    }
+
+    /// The kernel's display name, such as "Python 3 (ipykernel)".
+    pub fn kernel_name(&self) -> Option<&str> {
+        let kernelspec = self.metadata.get("kernelspec")?;
+        kernelspec
+            .get("display_name")
+            .or_else(|| kernelspec.get("name"))
+            .and_then(Value::as_str)
+    }
+
+    /// Describes the notebook as a whole, for its root node.
+    pub fn summary(&self) -> Value {
+        let mut summary = Map::new();
+        summary.insert("cells".to_string(), self.cells.len().into());
+        for cell_type in ["code", "markdown", "raw"] {
+            let count = self
+                .cells
+                .iter()
+                .filter(|cell| cell.cell_type == cell_type)
+                .count();
+            if count > 0 {
+                summary.insert(format!("{}_cells", cell_type), count.into());
+            }
+        }
+        if let Some(kernel) = self.kernel_name() {
+            summary.insert("kernel".to_string(), kernel.into());
+        }
+        let language = self
+            .metadata
+            .get("language_info")
+            .and_then(|info| info.get("name"));
+        if let Some(language) = language {
+            summary.insert("language".to_string(), language.clone());
+        }
+        if let Some(version) = self.metadata.get("nbformat") {
+            summary.insert("nbformat".to_string(), version.clone());
+        }
+        Value::Object(summary)
+    }
+}
+
+/// Parses the content of a `.ipynb` file.
+///
+/// JSON without a `cells` list is shown as it is, with a warning added to `warnings`.
+///
+/// # Returns
+///
+/// * `Ok(ParseResult::Notebook(_))` if content is a notebook
+/// * `Ok(ParseResult::Single(_))` if content is other JSON
+/// * `Err(ParseError::InvalidJson(_))` if content is not valid JSON
+pub fn parse_notebook_content(
+    content: &str,
+    warnings: &mut Vec<String>,
+) -> Result<ParseResult, ParseError> {
+    let (value, duplicates) = parse_json_document(content)
+        .map_err(|e| ParseError::InvalidJson(Box::new(SyntaxError::from_json(&e, content))))?;
+    warnings.extend(duplicates);
+    match Notebook::from_value(value) {
+        Ok(notebook) => Ok(ParseResult::Notebook(notebook)),
+        Err(value) => {
+            warnings.push(
+                "The content has no \"cells\" list, so it is shown as JSON rather than as a notebook"
+                    .to_string(),
+            );
+            Ok(ParseResult::Single(value))
+        }
+    }
+}
+
+/// Converts a cell, putting its joined source and its outputs first.
+fn convert_cell(cell: Value) -> NotebookCell {
+    let Value::Object(mut map) = cell else {
+        return NotebookCell {
+            cell_type: "unknown".to_string(),
+            value: cell,
+        };
+    };
+    let cell_type = map
+        .get("cell_type")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+        .to_string();
+
+    let mut converted = Map::new();
+    if let Some(source) = map.shift_remove("source") {
+        converted.insert("source".to_string(), join_lines(source));
+    }
+    if let Some(outputs) = map.shift_remove("outputs") {
+        let outputs = match outputs {
+            Value::Array(outputs) => {
+                Value::Array(outputs.into_iter().map(convert_output).collect())
+            }
+            other => other,
+        };
+        converted.insert("outputs".to_string(), outputs);
+    }
+    if let Some(Value::Object(attachments)) = map.get_mut("attachments") {
+        for bundle in attachments.values_mut() {
+            convert_mime_bundle(bundle);
+        }
+    }
+    converted.extend(map);
+    NotebookCell {
+        cell_type,
+        value: Value::Object(converted),
+    }
+}
+
+/// Converts an output of a code cell: stream text, results, display data or an error.
+fn convert_output(output: Value) -> Value {
+    let Value::Object(mut map) = output else {
+        return output;
+    };
+    match map.get("output_type").and_then(Value::as_str) {
+        Some("stream") => {
+            if let Some(text) = map.get_mut("text") {
+                *text = join_lines(text.take());
+            }
+        }
+        Some("error") => {
+            if let Some(Value::Array(lines)) = map.get("traceback") {
+                let traceback = lines
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(strip_ansi_codes)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                map.insert("traceback".to_string(), Value::String(traceback));
+            }
+        }
+        _ => {
+            if let Some(data) = map.get_mut("data") {
+                convert_mime_bundle(data);
+            }
+        }
+    }
+    Value::Object(map)
+}
+
+/// Joins the lines of each text representation in a media type bundle, and
+/// turns images into `$binary` objects. JSON representations are kept as they are.
+fn convert_mime_bundle(bundle: &mut Value) {
+    let Value::Object(bundle) = bundle else {
+        return;
+    };
+    for (mime, value) in bundle.iter_mut() {
+        if mime == "application/json" || mime.ends_with("+json") {
+            continue;
+        }
+        let text = join_lines(value.take());
+        *value = match text {
+            Value::String(base64) if is_base64_image(mime) => {
+                let base64: String = base64.split_whitespace().collect();
+                serde_json::json!({ BINARY_KEY: base64, MIME_KEY: mime })
+            }
+            other => other,
+        };
+    }
+}
+
+/// Image types that notebooks store as base64; SVG images are stored as text.
+fn is_base64_image(mime: &str) -> bool {
+    mime.starts_with("image/") && mime != "image/svg+xml"
+}
+
+/// Joins a notebook "multiline string", which may be a string or a list of
+/// lines that each end with a newline. Other values are returned unchanged.
+fn join_lines(value: Value) -> Value {
+    match value {
+        Value::Array(lines) if lines.iter().all(Value::is_string) => Value::String(
+            lines
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .concat(),
+        ),
+        other => other,
+    }
+}
+
+/// Removes the ANSI escape codes that color IPython tracebacks.
+pub fn strip_ansi_codes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Control sequences, such as colors, end with a byte from @ to ~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Operating system commands, such as hyperlinks, end with BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stripped
+}
+
+/// Returns the bytes of a `$binary` object holding an image, such as a
+/// notebook output converted by this module.
+pub fn image_bytes(value: &Value) -> Option<Vec<u8>> {
+    let map = value.as_object()?;
+    if !map.get(MIME_KEY)?.as_str().is_some_and(is_base64_image) {
+        return None;
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(map.get(BINARY_KEY)?.as_str()?)
+        .ok()
+}
+
+/// Reads the width and height of a PNG image from the start of its base64
+/// encoding, without decoding the rest.
+pub fn png_dimensions(base64: &str) -> Option<(u32, u32)> {
+    // The signature, then the IHDR chunk's length, type, width and height
+    let head = base64::engine::general_purpose::STANDARD
+        .decode(base64.get(..32)?)
+        .ok()?;
+    if !head.starts_with(PNG_SIGNATURE) || &head[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(head[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(head[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x1 PNG image, base64-encoded.
+    const PNG_2X1: &str = "iVBORw0KGgoAAAANSUhEUgAAAAIAAAABCAYAAAD0In+KAAAADklEQVR4nGP4z8AAQv8BD/kD/YURmXYAAAAASUVORK5CYII=";
+
+    fn sample_notebook() -> String {
+        serde_json::json!({
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["# Title\n", "\n", "Some text"]
+                },
+                {
+                    "cell_type": "code",
+                    "execution_count": 1,
+                    "metadata": {},
+                    "outputs": [
+                        {"name": "stdout", "output_type": "stream", "text": ["a\n", "b\n"]},
+                        {
+                            "data": {
+                                "application/json": ["kept"],
+                                "image/png": format!("{}\n", PNG_2X1),
+                                "text/plain": ["<Figure>"]
+                            },
+                            "metadata": {},
+                            "output_type": "display_data"
+                        },
+                        {
+                            "ename": "ZeroDivisionError",
+                            "evalue": "division by zero",
+                            "output_type": "error",
+                            "traceback": ["\u{1b}[0;31mZeroDivisionError\u{1b}[0m", "line 2"]
+                        }
+                    ],
+                    "source": ["\n", "x = 1\n", "print(x)"]
+                }
+            ],
+            "metadata": {
+                "kernelspec": {"display_name": "Python 3 (ipykernel)", "name": "python3"},
+                "language_info": {"name": "python"}
+            },
+            "nbformat": 4,
+            "nbformat_minor": 5
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_parse_notebook() {
+        let mut warnings = Vec::new();
+        let notebook = match parse_notebook_content(&sample_notebook(), &mut warnings).unwrap() {
+            ParseResult::Notebook(notebook) => notebook,
+            other => panic!("Expected Notebook result, got {:?}", other),
+        };
+        assert!(warnings.is_empty());
+        assert_eq!(
+            notebook.summary(),
+            serde_json::json!({
+                "cells": 2,
+                "code_cells": 1,
+                "markdown_cells": 1,
+                "kernel": "Python 3 (ipykernel)",
+                "language": "python",
+                "nbformat": "4.5"
+            })
+        );
+
+        let [markdown, code] = notebook.cells.as_slice() else {
+            panic!("Expected two cells");
+        };
+        assert_eq!(markdown.value["source"], "# Title\n\nSome text");
+        assert_eq!(code.cell_type, "code");
+        assert_eq!(code.first_line(), Some("x = 1"));
+        let keys: Vec<&str> = code
+            .value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "source",
+                "outputs",
+                "cell_type",
+                "execution_count",
+                "metadata"
+            ]
+        );
+
+        let outputs = &code.value["outputs"];
+        assert_eq!(outputs[0]["text"], "a\nb\n");
+        assert_eq!(outputs[1]["data"]["text/plain"], "<Figure>");
+        assert_eq!(
+            outputs[1]["data"]["application/json"],
+            serde_json::json!(["kept"])
+        );
+        let image = &outputs[1]["data"]["image/png"];
+        assert_eq!(image[MIME_KEY], "image/png");
+        assert!(image_bytes(image).unwrap().starts_with(PNG_SIGNATURE));
+        assert_eq!(outputs[2]["traceback"], "ZeroDivisionError\nline 2");
+    }
+
+    #[test]
+    fn test_parse_notebook_without_cells() {
+        let mut warnings = Vec::new();
+        let result = parse_notebook_content("{\"a\": 1}", &mut warnings).unwrap();
+        assert!(matches!(result, ParseResult::Single(_)));
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_strip_ansi_codes() {
+        assert_eq!(
+            strip_ansi_codes(
+                "\u{1b}[1;32mok\u{1b}[0m \u{1b}]8;;http://x\u{7}link\u{1b}]8;;\u{1b}\\"
+            ),
+            "ok link"
+        );
+    }
+
+    #[test]
+    fn test_png_dimensions() {
+        assert_eq!(png_dimensions(PNG_2X1), Some((2, 1)));
+        assert_eq!(png_dimensions("aGVsbG8="), None);
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (C) 2025 Arjun Guha
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::arrow_json::{parquet_metadata_to_json, record_batch_to_json_rows};
+use crate::json_reader::ParseError;
+use bytes::Bytes;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder,
+};
+use parquet::file::reader::ChunkReader;
+use serde_json::Value;
+use std::fs::File;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+
+/// Where a Parquet file's bytes are read from.
+#[derive(Debug)]
+enum ParquetData {
+    /// The open file. Parquet's readers seek clones of it, which share its
+    /// position, so it is locked while rows are read, as search reads rows on
+    /// another thread.
+    File(Mutex<File>),
+    Memory(Bytes),
+}
+
+/// A Parquet file whose rows are decoded on request.
+///
+/// Opening the file only reads its footer; `read_rows` decodes just the row
+/// groups that overlap the requested range.
+#[derive(Debug)]
+pub struct ParquetFile {
+    data: ParquetData,
+    metadata: ArrowReaderMetadata,
+}
+
+impl ParquetFile {
+    /// Opens a Parquet file on disk and reads its footer.
+    pub fn open(path: &Path) -> Result<Self, ParseError> {
+        let file = File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+        let metadata = ArrowReaderMetadata::load(&file, Default::default())
+            .map_err(|e| ParseError::InvalidParquet(e.to_string()))?;
+        Ok(Self {
+            data: ParquetData::File(Mutex::new(file)),
+            metadata,
+        })
+    }
+
+    /// Reads the footer of a Parquet file that is already in memory.
+    pub fn from_bytes(data: Bytes) -> Result<Self, ParseError> {
+        let metadata = ArrowReaderMetadata::load(&data, Default::default())
+            .map_err(|e| ParseError::InvalidParquet(e.to_string()))?;
+        Ok(Self {
+            data: ParquetData::Memory(data),
+            metadata,
+        })
+    }
+
+    /// Total number of rows across all row groups.
+    pub fn num_rows(&self) -> usize {
+        self.metadata.metadata().file_metadata().num_rows().max(0) as usize
+    }
+
+    /// Number of row groups in the file.
+    pub fn num_row_groups(&self) -> usize {
+        self.metadata.metadata().num_row_groups()
+    }
+
+    /// Number of rows in the file's largest row group.
+    pub fn max_row_group_rows(&self) -> usize {
+        self.metadata
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|row_group| row_group.num_rows().max(0) as usize)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Describes the file's schema and row groups, as shown under the "Metadata" node.
+    pub fn metadata_json(&self) -> Value {
+        parquet_metadata_to_json(self.metadata.metadata(), self.metadata.schema())
+    }
+
+    /// Decodes up to `len` rows starting at row `start`.
+    ///
+    /// Only the row groups overlapping the range are read from disk.
+    pub fn read_rows(&self, start: usize, len: usize) -> Result<Vec<Value>, ParseError> {
+        let end = start.saturating_add(len).min(self.num_rows());
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        // Find the row groups overlapping [start, end) and the first one's starting row
+        let mut row_groups = Vec::new();
+        let mut first_group_start = None;
+        let mut group_start = 0;
+        for (idx, row_group) in self.metadata.metadata().row_groups().iter().enumerate() {
+            let group_end = group_start + row_group.num_rows().max(0) as usize;
+            if group_end > start && group_start < end {
+                first_group_start.get_or_insert(group_start);
+                row_groups.push(idx);
+            }
+            group_start = group_end;
+        }
+        let Some(first_group_start) = first_group_start else {
+            return Ok(Vec::new());
+        };
+
+        let offset = start - first_group_start;
+        // Held until every row has been read, see `ParquetData::File`
+        let mut file_lock = None;
+        let reader = match &self.data {
+            ParquetData::File(file) => {
+                let file = file_lock.insert(file.lock().unwrap_or_else(PoisonError::into_inner));
+                let file = file
+                    .try_clone()
+                    .map_err(|e| ParseError::IoError(e.to_string()))?;
+                self.build_reader(file, row_groups, offset, end - start)?
+            }
+            ParquetData::Memory(data) => {
+                self.build_reader(data.clone(), row_groups, offset, end - start)?
+            }
+        };
+
+        let mut rows = Vec::with_capacity(end - start);
+        for batch in reader {
+            let batch = batch.map_err(|e| ParseError::InvalidParquet(e.to_string()))?;
+            let batch_rows = record_batch_to_json_rows(&batch)
+                .map_err(|e| ParseError::InvalidParquet(e.to_string()))?;
+            rows.extend(batch_rows);
+        }
+        Ok(rows)
+    }
+
+    fn build_reader<T: ChunkReader + 'static>(
+        &self,
+        input: T,
+        row_groups: Vec<usize>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<ParquetRecordBatchReader, ParseError> {
+        ParquetRecordBatchReaderBuilder::new_with_metadata(input, self.metadata.clone())
+            .with_row_groups(row_groups)
+            .with_offset(offset)
+            .with_limit(limit)
+            .build()
+            .map_err(|e| ParseError::InvalidParquet(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::arrow_writer::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    /// Writes `0..num_rows` to a Parquet file with the given row group size.
+    fn write_parquet_file(name: &str, num_rows: i64, row_group_size: usize) -> PathBuf {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from_iter_values(0..num_rows))],
+        )
+        .unwrap();
+
+        let path = std::env::temp_dir().join(format!("{}-{}.parquet", name, std::process::id()));
+        let file = File::create(&path).unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(row_group_size)
+            .build();
+        let mut writer = ArrowWriter::try_new(file, schema, Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_rows_across_row_groups() {
+        let path = write_parquet_file("slopjson-paged", 100, 30);
+        let parquet = ParquetFile::open(&path).unwrap();
+        assert_eq!(parquet.num_rows(), 100);
+        assert_eq!(parquet.num_row_groups(), 4);
+
+        let rows = parquet.read_rows(25, 10).unwrap();
+        let ids: Vec<i64> = rows.iter().map(|row| row["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, (25..35).collect::<Vec<_>>());
+
+        // Ranges past the end are truncated
+        assert_eq!(parquet.read_rows(95, 10).unwrap().len(), 5);
+        assert!(parquet.read_rows(100, 10).unwrap().is_empty());
+
+        assert_eq!(parquet.metadata_json()["num_rows"], 100);
+
+        // The same file read from memory decodes the same rows
+        let in_memory =
+            ParquetFile::from_bytes(Bytes::from(std::fs::read(&path).unwrap())).unwrap();
+        assert_eq!(in_memory.read_rows(25, 10).unwrap(), rows);
+        std::fs::remove_file(path).unwrap();
+    }
+}
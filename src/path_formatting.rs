@@ -1,3 +1,5 @@
+use crate::value_lookup::{parse_json_path, PathSegment};
+
 /// Formats a path component (key) for display in a JSON path.
 /// Returns either `.key` format for valid identifiers or `["key"]` format for keys with spaces/special chars.
 pub fn format_path_component(key: &str) -> String {
@@ -29,6 +31,28 @@ pub fn build_array_path(base: &str, index: usize) -> String {
     format!("{}[{}]", base, index)
 }
 
+/// Builds a path by appending a parsed path segment to a base path.
+pub fn build_segment_path(base: &str, segment: &PathSegment) -> String {
+    match segment {
+        PathSegment::Key(key) => build_object_path(base, key),
+        PathSegment::Index(index) => build_array_path(base, *index),
+    }
+}
+
+/// Expands a data path into the chain of paths leading to it, starting at `$`.
+///
+/// The paths are rebuilt with the same formatting the tree uses, so each entry
+/// matches the data path stored on the corresponding tree row.
+pub fn ancestor_data_paths(path: &str) -> Option<Vec<String>> {
+    let segments = parse_json_path(path)?;
+    let mut paths = vec!["$".to_string()];
+    for segment in &segments {
+        let next = build_segment_path(paths.last()?, segment);
+        paths.push(next);
+    }
+    Some(paths)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,6 +138,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_segment_path() {
+        assert_eq!(
+            build_segment_path("$", &PathSegment::Key("my field".to_string())),
+            "$[\"my field\"]"
+        );
+        assert_eq!(build_segment_path("$.x", &PathSegment::Index(3)), "$.x[3]");
+    }
+
+    #[test]
+    fn test_ancestor_data_paths() {
+        assert_eq!(
+            ancestor_data_paths("$.foo[0][\"my key\"]").unwrap(),
+            vec!["$", "$.foo", "$.foo[0]", "$.foo[0][\"my key\"]"]
+        );
+        assert_eq!(ancestor_data_paths("$").unwrap(), vec!["$"]);
+        assert!(ancestor_data_paths("foo").is_none());
+    }
+
     #[test]
     fn test_full_path_examples() {
         // Simple nested object
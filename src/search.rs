@@ -13,6 +13,218 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::document_store::{KeyOrder, StoredDocument};
+use crate::json_reader::{ParseControl, ParseError};
+use crate::path_formatting::build_segment_path;
+use crate::value_formatting::format_value_literal;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// A search hit inside a stored document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentMatch {
+    /// Data path of the tree node containing the hit
+    pub data_path: String,
+    /// Whether the hit is in the node's key rather than its value
+    pub is_key_match: bool,
+}
+
+/// A search hit in one of the open documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    /// Index of the document in the window's document list
+    pub doc_id: i64,
+    /// Data path of the tree node containing the hit
+    pub data_path: String,
+    /// Whether the hit is in the node's key rather than its value
+    pub is_key_match: bool,
+}
+
+/// A document to search, in the order its root row appears in the tree.
+pub struct SearchTarget {
+    pub doc_id: i64,
+    /// Display name of the document's root row
+    pub root_name: String,
+    /// A copy of the stored document, from `StoredDocument::shared_copy`
+    pub document: StoredDocument,
+    /// Data path of the currently selected node, if it is in this document
+    pub selected_path: Option<String>,
+}
+
+/// The matches of a search, and the index of the match to show first.
+pub type SearchResults = (Vec<SearchMatch>, Option<usize>);
+
+/// Searches documents on a worker thread, so the tree stays responsive while
+/// large files are read.
+///
+/// The receiver gets the results once every document has been searched. Nothing
+/// is sent if `cancelled` is set first, as it is when the search text changes.
+pub fn spawn_search(
+    targets: Vec<SearchTarget>,
+    search_text: String,
+    case_sensitive: bool,
+    key_order: KeyOrder,
+    cancelled: Arc<AtomicBool>,
+) -> async_channel::Receiver<SearchResults> {
+    let (sender, receiver) = async_channel::bounded(1);
+    std::thread::spawn(move || {
+        let control = ParseControl::new(cancelled, |_| {});
+        if let Ok(results) =
+            search_documents(targets, &search_text, case_sensitive, key_order, &control)
+        {
+            let _ = sender.send_blocking(results);
+        }
+    });
+    receiver
+}
+
+/// Searches documents in order, as `spawn_search` does on its worker thread.
+///
+/// The match shown first is the first one at or after the selected node,
+/// wrapping around to the first match if there is none.
+pub fn search_documents(
+    targets: Vec<SearchTarget>,
+    search_text: &str,
+    case_sensitive: bool,
+    key_order: KeyOrder,
+    control: &ParseControl,
+) -> Result<SearchResults, ParseError> {
+    let mut matches = Vec::new();
+    let mut selected_index = None;
+    for mut target in targets {
+        let (doc_matches, doc_selected_index) = find_document_matches(
+            &mut target.document,
+            &target.root_name,
+            search_text,
+            case_sensitive,
+            target.selected_path.as_deref(),
+            key_order,
+            control,
+        )?;
+        if let Some(local_index) = doc_selected_index {
+            selected_index = Some(matches.len() + local_index);
+        }
+        matches.extend(doc_matches.into_iter().map(|m| SearchMatch {
+            doc_id: target.doc_id,
+            data_path: m.data_path,
+            is_key_match: m.is_key_match,
+        }));
+    }
+
+    let starting_index = if matches.is_empty() {
+        None
+    } else {
+        Some(
+            selected_index
+                .filter(|idx| *idx < matches.len())
+                .unwrap_or(0),
+        )
+    };
+    Ok((matches, starting_index))
+}
+
+/// Searches the leaf nodes of a document, walking the stored data rather than the tree rows.
+///
+/// Nodes are visited in the same pre-order as the tree view, so matches come back in
+/// display order whether or not their rows have been created yet. Lines, pages and
+/// archive members are read one at a time and released as soon as they have been
+/// searched, so searching a large file does not load all of it.
+///
+/// # Arguments
+///
+/// * `document` - The document to search
+/// * `root_name` - Display name of the document's root row (the key of the root node)
+/// * `search_text` - The text to find
+/// * `case_sensitive` - Whether matching is case sensitive
+/// * `selected_path` - Data path of the currently selected node, if it is in this document
+/// * `key_order` - The order object members are shown in
+/// * `control` - Stops the search once cancelled
+///
+/// # Returns
+///
+/// The matches, plus the number of matches that come before `selected_path`
+/// (`None` if `selected_path` was not visited).
+pub fn find_document_matches(
+    document: &mut StoredDocument,
+    root_name: &str,
+    search_text: &str,
+    case_sensitive: bool,
+    selected_path: Option<&str>,
+    key_order: KeyOrder,
+    control: &ParseControl,
+) -> Result<(Vec<DocumentMatch>, Option<usize>), ParseError> {
+    struct Walker<'a> {
+        document: &'a mut StoredDocument,
+        search_text: &'a str,
+        case_sensitive: bool,
+        selected_path: Option<&'a str>,
+        key_order: KeyOrder,
+        control: &'a ParseControl,
+        matches: Vec<DocumentMatch>,
+        selected_index: Option<usize>,
+    }
+
+    impl Walker<'_> {
+        fn visit(&mut self, key: &str, data_path: &str) -> Result<(), ParseError> {
+            if self.selected_path == Some(data_path) {
+                self.selected_index = Some(self.matches.len());
+            }
+
+            // Only names and segments are kept, so the document can release rows below
+            let children: Vec<_> = self
+                .document
+                .ordered_child_nodes(data_path, self.key_order)
+                .into_iter()
+                .map(|child| (child.name, child.segment))
+                .collect();
+            if children.is_empty() {
+                // Only leaf nodes are searched
+                for _ in find_all_occurrences(key, self.search_text, self.case_sensitive) {
+                    self.matches.push(DocumentMatch {
+                        data_path: data_path.to_string(),
+                        is_key_match: true,
+                    });
+                }
+                // Format the value the same way it is displayed, so offsets match
+                if let Some(value) = self.document.lookup_value(data_path) {
+                    let formatted =
+                        format_value_literal(value, self.document.value_origin(data_path));
+                    for _ in find_all_occurrences(&formatted, self.search_text, self.case_sensitive)
+                    {
+                        self.matches.push(DocumentMatch {
+                            data_path: data_path.to_string(),
+                            is_key_match: false,
+                        });
+                    }
+                }
+                return Ok(());
+            }
+
+            for (name, segment) in children {
+                self.control.check_cancelled()?;
+                let child_path = build_segment_path(data_path, &segment);
+                self.visit(&name, &child_path)?;
+                // Frees the line, page or member just searched; other nodes hold nothing to free
+                self.document.release_rows(&child_path);
+            }
+            Ok(())
+        }
+    }
+
+    let mut walker = Walker {
+        document,
+        search_text,
+        case_sensitive,
+        selected_path,
+        key_order,
+        control,
+        matches: Vec::new(),
+        selected_index: None,
+    };
+    walker.visit(root_name, "$")?;
+    Ok((walker.matches, walker.selected_index))
+}
+
 /// Finds all occurrences of a pattern in text, returning (start, end) **character** offsets.
 ///
 /// Important: GTK's `TextBuffer::iter_at_offset` expects offsets in *characters*, not bytes.
@@ -138,6 +350,7 @@ pub fn find_occurrence_to_highlight(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::json_value::ValueOrigin;
 
     #[test]
     fn test_find_all_occurrences_case_sensitive() {
@@ -304,4 +517,180 @@ mod tests {
         let occ = find_all_occurrences(text, pattern, true);
         assert_eq!(occ, vec![(7, 14), (20, 27)]);
     }
+
+    #[test]
+    fn test_find_document_matches_leaves_in_tree_order() {
+        let mut doc = StoredDocument::Single(
+            Arc::new(serde_json::json!({
+                "b": {"name": "match here"},
+                "a": ["no", "match match"],
+            })),
+            ValueOrigin::Json,
+        );
+        let control = ParseControl::default();
+        let (matches, selected) = find_document_matches(
+            &mut doc,
+            "root",
+            "match",
+            true,
+            None,
+            KeyOrder::File,
+            &control,
+        )
+        .unwrap();
+        let paths: Vec<&str> = matches.iter().map(|m| m.data_path.as_str()).collect();
+        // Object keys are visited in the order the tree shows them
+        assert_eq!(paths, vec!["$.b.name", "$.a[1]", "$.a[1]"]);
+        assert!(matches.iter().all(|m| !m.is_key_match));
+        assert_eq!(selected, None);
+
+        let (matches, _) = find_document_matches(
+            &mut doc,
+            "root",
+            "match",
+            true,
+            None,
+            KeyOrder::Sorted,
+            &control,
+        )
+        .unwrap();
+        let paths: Vec<&str> = matches.iter().map(|m| m.data_path.as_str()).collect();
+        assert_eq!(paths, vec!["$.a[1]", "$.a[1]", "$.b.name"]);
+    }
+
+    #[test]
+    fn test_find_document_matches_keys_and_selection() {
+        let mut doc = StoredDocument::Single(
+            Arc::new(serde_json::json!({
+                "first": 1,
+                "second": {"first": 2},
+            })),
+            ValueOrigin::Json,
+        );
+        let (matches, selected) = find_document_matches(
+            &mut doc,
+            "root",
+            "first",
+            true,
+            Some("$.second"),
+            KeyOrder::File,
+            &ParseControl::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                DocumentMatch {
+                    data_path: "$.first".to_string(),
+                    is_key_match: true,
+                },
+                DocumentMatch {
+                    data_path: "$.second.first".to_string(),
+                    is_key_match: true,
+                },
+            ]
+        );
+        assert_eq!(selected, Some(1));
+    }
+
+    #[test]
+    fn test_find_document_matches_jsonl() {
+        use crate::document_store::JsonLDocument;
+
+        let mut doc = StoredDocument::JsonL(JsonLDocument::new(
+            vec![
+                serde_json::json!({"name": "alpha"}),
+                serde_json::json!({"name": "beta"}),
+            ],
+            vec![1, 2],
+            Vec::new(),
+        ));
+        let (matches, _) = find_document_matches(
+            &mut doc,
+            "file (JSONL)",
+            "beta",
+            true,
+            None,
+            KeyOrder::File,
+            &ParseControl::default(),
+        )
+        .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].data_path, "$[1].name");
+    }
+
+    #[test]
+    fn test_search_reads_lazy_lines_without_keeping_them() {
+        use crate::document_store::IndexedJsonLDocument;
+        use crate::jsonl_index::JsonLIndex;
+
+        let path =
+            std::env::temp_dir().join(format!("slopjson-search-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "{\"name\": \"alpha\"}\n{\"name\": \"beta\"}\n").unwrap();
+        let index = JsonLIndex::build(&path, &ParseControl::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut doc = StoredDocument::IndexedJsonL(IndexedJsonLDocument::new(index, 1000));
+        let nothing_parsed =
+            |doc: &StoredDocument| doc.child_nodes("$").iter().all(|line| line.value.is_none());
+
+        // Searching a copy, as the worker thread does, leaves the tree's document untouched
+        let target = SearchTarget {
+            doc_id: 3,
+            root_name: "big.jsonl".to_string(),
+            document: doc.shared_copy(),
+            selected_path: Some("$[1]".to_string()),
+        };
+        let (matches, starting_index) = search_documents(
+            vec![target],
+            "beta",
+            true,
+            KeyOrder::File,
+            &ParseControl::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            matches,
+            vec![SearchMatch {
+                doc_id: 3,
+                data_path: "$[1].name".to_string(),
+                is_key_match: false,
+            }]
+        );
+        assert_eq!(starting_index, Some(0));
+        assert!(nothing_parsed(&doc));
+
+        // The searched document releases each line once it has been searched
+        find_document_matches(
+            &mut doc,
+            "big.jsonl",
+            "a",
+            true,
+            None,
+            KeyOrder::File,
+            &ParseControl::default(),
+        )
+        .unwrap();
+        assert!(nothing_parsed(&doc));
+    }
+
+    #[test]
+    fn test_search_stops_when_cancelled() {
+        let mut doc = StoredDocument::Single(
+            Arc::new(serde_json::json!({"a": "match"})),
+            ValueOrigin::Json,
+        );
+        let control = ParseControl::new(Arc::new(AtomicBool::new(true)), |_| {});
+        assert!(matches!(
+            find_document_matches(
+                &mut doc,
+                "root",
+                "match",
+                true,
+                None,
+                KeyOrder::File,
+                &control
+            ),
+            Err(ParseError::Cancelled)
+        ));
+    }
 }
@@ -13,13 +13,23 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::path_formatting::{build_array_path, build_object_path};
+use crate::document_store::{
+    ArchiveDocument, KeyOrder, NotebookDocument, PagedDocument, StoredDocument,
+};
+use crate::json_value::ValueOrigin;
+use crate::path_formatting::{ancestor_data_paths, build_segment_path};
 use crate::value_formatting::format_value_preview;
 use glib::ToValue;
-use gtk::prelude::{TreeStoreExt, TreeStoreExtManual};
+use gtk::prelude::{TreeModelExt, TreeStoreExt, TreeStoreExtManual};
 use gtk::{TreeIter, TreeStore};
 use serde_json::Value;
 
+/// Display name of the placeholder row that stands in for unloaded children.
+const PLACEHOLDER_NAME: &str = "Loading...";
+
+/// Preview shown for a node whose value has not been parsed yet.
+const UNPARSED_PREVIEW: &str = "...";
+
 /// Sets all column values for a tree node.
 ///
 /// # Arguments
@@ -27,7 +37,7 @@ use serde_json::Value;
 /// * `tree_store` - The tree store to update
 /// * `iter` - The tree iterator for the node
 /// * `name` - Display name for the node (key or index)
-/// * `value` - The JSON value to store
+/// * `preview` - Preview of the node's value, see `format_value_preview`
 /// * `display_path` - The JSON path string for display in the UI
 /// * `data_path` - The JSON path string for value lookup
 /// * `doc_id` - Identifier for the source document
@@ -35,102 +45,298 @@ pub fn set_tree_node_values(
     tree_store: &TreeStore,
     iter: &TreeIter,
     name: &str,
-    value: &Value,
+    preview: &str,
     display_path: &str,
     data_path: &str,
     doc_id: i64,
 ) {
     tree_store.set_value(iter, 0, &name.to_value());
-    tree_store.set_value(iter, 1, &format_value_preview(value).to_value());
+    tree_store.set_value(iter, 1, &preview.to_value());
     tree_store.set_value(iter, 2, &display_path.to_value());
     tree_store.set_value(iter, 3, &data_path.to_value());
     tree_store.set_value(iter, 4, &doc_id.to_value());
 }
 
-/// Recursively populates a tree store with JSON values.
+/// Appends a placeholder child so the node shows an expander before its children exist.
+///
+/// The placeholder is the only row with an empty data path, which is how
+/// `is_placeholder` tells it apart from real nodes.
+pub fn add_placeholder_child(tree_store: &TreeStore, parent: &TreeIter, doc_id: i64) {
+    let iter = tree_store.append(Some(parent));
+    tree_store.set_value(&iter, 0, &PLACEHOLDER_NAME.to_value());
+    tree_store.set_value(&iter, 1, &"".to_value());
+    tree_store.set_value(&iter, 2, &"".to_value());
+    tree_store.set_value(&iter, 3, &"".to_value());
+    tree_store.set_value(&iter, 4, &doc_id.to_value());
+}
+
+/// Returns true if the row is a placeholder for children that have not been created yet.
+pub fn is_placeholder(tree_store: &TreeStore, iter: &TreeIter) -> bool {
+    tree_store
+        .value(iter, 3)
+        .get::<String>()
+        .map(|data_path| data_path.is_empty())
+        .unwrap_or(false)
+}
+
+/// Returns true if the value has children to show in the tree.
+fn has_children(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(arr) => !arr.is_empty(),
+        _ => false,
+    }
+}
+
+/// Populates one level of the tree with the children of a document node.
+///
+/// Children that have children of their own get a placeholder row instead of
+/// being populated recursively, so rows are only created as the user expands them.
 ///
 /// # Arguments
 ///
 /// * `tree_store` - The tree store to populate
-/// * `parent` - The parent iterator (None for root)
-/// * `value` - The JSON value to add
-/// * `display_path` - The JSON path string for display in the UI
-/// * `data_path` - The JSON path string for value lookup
+/// * `parent` - The parent iterator
+/// * `document` - The document the node belongs to
+/// * `display_path` - The parent's JSON path string for display in the UI
+/// * `data_path` - The parent's JSON path string for value lookup
 /// * `doc_id` - Identifier for the source document
-pub fn populate_tree(
+/// * `key_order` - The order to show object members in
+pub fn populate_children(
     tree_store: &TreeStore,
     parent: &TreeIter,
-    value: &Value,
+    document: &StoredDocument,
     display_path: &str,
     data_path: &str,
     doc_id: i64,
+    key_order: KeyOrder,
 ) {
-    match value {
-        Value::Object(map) => {
-            for (key, val) in map.iter() {
-                let iter = tree_store.append(Some(parent));
-                let new_display_path = build_object_path(display_path, key);
-                let new_data_path = build_object_path(data_path, key);
+    for child in document.ordered_child_nodes(data_path, key_order) {
+        let iter = tree_store.append(Some(parent));
+        let new_display_path = build_segment_path(display_path, &child.segment);
+        let new_data_path = build_segment_path(data_path, &child.segment);
+        match child.value {
+            Some(value) => {
+                let preview = child.preview.unwrap_or_else(|| {
+                    format_value_preview(value, document.value_origin(&new_data_path))
+                });
                 set_tree_node_values(
                     tree_store,
                     &iter,
-                    key,
-                    val,
-                    &new_display_path,
-                    &new_data_path,
-                    doc_id,
-                );
-                populate_tree(
-                    tree_store,
-                    &iter,
-                    val,
+                    &child.name,
+                    &preview,
                     &new_display_path,
                     &new_data_path,
                     doc_id,
                 );
+                if has_children(value) {
+                    add_placeholder_child(tree_store, &iter, doc_id);
+                }
             }
-        }
-        Value::Array(arr) => {
-            for (idx, val) in arr.iter().enumerate() {
-                let iter = tree_store.append(Some(parent));
-                let new_display_path = build_array_path(display_path, idx);
-                let new_data_path = build_array_path(data_path, idx);
-                let name = format!("[{}]", idx);
-                set_tree_node_values(
-                    tree_store,
-                    &iter,
-                    &name,
-                    val,
-                    &new_display_path,
-                    &new_data_path,
-                    doc_id,
-                );
-                populate_tree(
-                    tree_store,
-                    &iter,
-                    val,
-                    &new_display_path,
-                    &new_data_path,
-                    doc_id,
-                );
+            None => {
+                // Not parsed yet: assume it has children, it is parsed when expanded
+                tree_store.set_value(&iter, 0, &child.name.to_value());
+                tree_store.set_value(&iter, 1, &UNPARSED_PREVIEW.to_value());
+                tree_store.set_value(&iter, 2, &new_display_path.to_value());
+                tree_store.set_value(&iter, 3, &new_data_path.to_value());
+                tree_store.set_value(&iter, 4, &doc_id.to_value());
+                add_placeholder_child(tree_store, &iter, doc_id);
             }
         }
-        _ => {
-            // Leaf value, already set in parent call
+    }
+}
+
+/// Replaces a node's placeholder child with its real children.
+///
+/// Does nothing if the node's children have already been created.
+///
+/// # Returns
+///
+/// `true` if the node was populated by this call
+pub fn populate_placeholder(
+    tree_store: &TreeStore,
+    iter: &TreeIter,
+    document: &StoredDocument,
+    key_order: KeyOrder,
+) -> bool {
+    let placeholder = match tree_store.iter_children(Some(iter)) {
+        Some(child) if is_placeholder(tree_store, &child) => child,
+        _ => return false,
+    };
+
+    let display_path = tree_store
+        .value(iter, 2)
+        .get::<String>()
+        .unwrap_or_default();
+    let data_path = tree_store
+        .value(iter, 3)
+        .get::<String>()
+        .unwrap_or_default();
+    let doc_id = tree_store.value(iter, 4).get::<i64>().unwrap_or(-1);
+
+    // Add the real children before removing the placeholder, so an expanded
+    // row never becomes childless (GTK would collapse it)
+    populate_children(
+        tree_store,
+        iter,
+        document,
+        &display_path,
+        &data_path,
+        doc_id,
+        key_order,
+    );
+    tree_store.remove(&placeholder);
+
+    // The node itself may have been parsed just now; show its real preview
+    let preview = tree_store
+        .value(iter, 1)
+        .get::<String>()
+        .unwrap_or_default();
+    if preview == UNPARSED_PREVIEW {
+        if let Some(value) = document.lookup_value(&data_path) {
+            let preview = format_value_preview(value, document.value_origin(&data_path));
+            tree_store.set_value(iter, 1, &preview.to_value());
+        }
+    }
+    true
+}
+
+/// Removes a node's children and puts the placeholder back, freeing their rows.
+///
+/// Does nothing if the node has no children or only a placeholder.
+pub fn unload_children(tree_store: &TreeStore, iter: &TreeIter) {
+    let child = match tree_store.iter_children(Some(iter)) {
+        Some(child) if !is_placeholder(tree_store, &child) => child,
+        _ => return,
+    };
+
+    let doc_id = tree_store.value(iter, 4).get::<i64>().unwrap_or(-1);
+    add_placeholder_child(tree_store, iter, doc_id);
+    // `remove` advances `child` to the next sibling; stop at the placeholder we just added
+    while !is_placeholder(tree_store, &child) {
+        if !tree_store.remove(&child) {
+            break;
+        }
+    }
+}
+
+/// Moves the rows already created under a node, and under its descendants, into `key_order`.
+///
+/// Rows are reordered in place rather than recreated, so expanded rows stay expanded.
+pub fn reorder_children(
+    tree_store: &TreeStore,
+    parent: &TreeIter,
+    document: &StoredDocument,
+    key_order: KeyOrder,
+) {
+    let Some(first) = tree_store.iter_children(Some(parent)) else {
+        return;
+    };
+    if is_placeholder(tree_store, &first) {
+        return;
+    }
+
+    let mut rows = Vec::new();
+    let child = first;
+    loop {
+        rows.push((
+            tree_store
+                .value(&child, 3)
+                .get::<String>()
+                .unwrap_or_default(),
+            child.clone(),
+        ));
+        if !tree_store.iter_next(&child) {
+            break;
+        }
+    }
+
+    let data_path = tree_store
+        .value(parent, 3)
+        .get::<String>()
+        .unwrap_or_default();
+    let new_order: Option<Vec<u32>> = document
+        .ordered_child_nodes(&data_path, key_order)
+        .iter()
+        .map(|child| {
+            let child_path = build_segment_path(&data_path, &child.segment);
+            rows.iter()
+                .position(|(row_path, _)| *row_path == child_path)
+                .map(|position| position as u32)
+        })
+        .collect();
+    if let Some(new_order) = new_order.filter(|order| order.len() == rows.len()) {
+        tree_store.reorder(parent, &new_order);
+    }
+
+    // Tree iterators stay valid across a reorder
+    for (_, row) in &rows {
+        reorder_children(tree_store, row, document, key_order);
+    }
+}
+
+/// Finds the row for a document node, creating any rows along the way that are still placeholders.
+///
+/// # Arguments
+///
+/// * `tree_store` - The tree store to search
+/// * `document` - The document the node belongs to
+/// * `doc_id` - Identifier for the source document
+/// * `data_path` - The JSON path of the node to find
+/// * `key_order` - The order object members are shown in
+///
+/// # Returns
+///
+/// The row's iterator, or `None` if the document has no root row or the path does not exist
+pub fn materialize_path(
+    tree_store: &TreeStore,
+    document: &StoredDocument,
+    doc_id: i64,
+    data_path: &str,
+    key_order: KeyOrder,
+) -> Option<TreeIter> {
+    let paths = ancestor_data_paths(data_path)?;
+
+    let mut current = tree_store.iter_first()?;
+    while tree_store.value(&current, 4).get::<i64>().unwrap_or(-1) != doc_id {
+        if !tree_store.iter_next(&current) {
+            return None;
+        }
+    }
+
+    for path in paths.iter().skip(1) {
+        populate_placeholder(tree_store, &current, document, key_order);
+        let child = tree_store.iter_children(Some(&current))?;
+        while tree_store
+            .value(&child, 3)
+            .get::<String>()
+            .unwrap_or_default()
+            != *path
+        {
+            if !tree_store.iter_next(&child) {
+                return None;
+            }
         }
+        current = child;
     }
+
+    Some(current)
 }
 
 /// Adds a single JSON value to the tree store as a root node.
 ///
+/// Only the root row is created; its children are added when it is expanded.
+///
 /// # Arguments
 ///
 /// * `tree_store` - The tree store to add to
 /// * `value` - The JSON value to add
+/// * `origin` - Whether the value was read as JSON or converted from another format
 /// * `root_name` - Display name for the root node
 pub fn add_single_value_to_tree(
     tree_store: &TreeStore,
     value: &Value,
+    origin: ValueOrigin,
     root_name: &str,
     doc_id: i64,
 ) {
@@ -139,64 +345,144 @@ pub fn add_single_value_to_tree(
     // This ensures proper path generation for nested structures
     let root_path = "$";
     set_tree_node_values(
-        tree_store, &root_iter, root_name, value, root_path, root_path, doc_id,
+        tree_store,
+        &root_iter,
+        root_name,
+        &format_value_preview(value, origin),
+        root_path,
+        root_path,
+        doc_id,
     );
-    populate_tree(tree_store, &root_iter, value, root_path, root_path, doc_id);
+    if has_children(value) {
+        add_placeholder_child(tree_store, &root_iter, doc_id);
+    }
+}
+
+/// Adds an input that failed to load to the tree store as a root node without
+/// children, so that its error stays at hand. Returns the root row.
+pub fn add_failed_load_to_tree(
+    tree_store: &TreeStore,
+    root_name: &str,
+    preview: &str,
+    doc_id: i64,
+) -> TreeIter {
+    let root_iter = tree_store.append(None);
+    tree_store.set_value(&root_iter, 0, &root_name.to_value());
+    tree_store.set_value(&root_iter, 1, &preview.to_value());
+    tree_store.set_value(&root_iter, 2, &root_name.to_value());
+    tree_store.set_value(&root_iter, 3, &"$".to_value());
+    tree_store.set_value(&root_iter, 4, &doc_id.to_value());
+    root_iter
 }
 
-/// Adds a JSONL result to the tree store.
+/// Adds a JSONL result or YAML stream to the tree store.
+///
+/// Only the root row is created; the line or document rows are added when it is expanded.
 ///
 /// # Arguments
 ///
 /// * `tree_store` - The tree store to add to
-/// * `json_values` - The array of JSON values from the JSONL file
-/// * `display_name` - Display name for the root node
+/// * `line_count` - The number of JSON values in the JSONL file
+/// * `error_count` - The number of lines that failed to parse, if known
+/// * `item_noun` - What the values are called in the preview, such as "objects"
+/// * `root_name` - Display name for the root node, including the "(JSONL)" label
 /// * `display_root_path` - The root path string (typically the file name)
 /// * `doc_id` - Identifier for the source document
 pub fn add_jsonl_to_tree(
     tree_store: &TreeStore,
-    json_values: &[Value],
-    display_name: &str,
+    line_count: usize,
+    error_count: Option<usize>,
+    item_noun: &str,
+    root_name: &str,
     display_root_path: &str,
     doc_id: i64,
 ) {
     let root_iter = tree_store.append(None);
-    let root_name = format!("{} (JSONL)", display_name);
+    tree_store.set_value(&root_iter, 0, &root_name.to_value());
+    let preview = match error_count {
+        Some(0) => format!("{} {}", line_count, item_noun),
+        Some(errors) => format!("{} {}, {} errors", line_count, item_noun, errors),
+        None => format!("{} {}, errors unknown", line_count, item_noun),
+    };
+    tree_store.set_value(&root_iter, 1, &preview.to_value());
+    tree_store.set_value(&root_iter, 2, &display_root_path.to_value());
+    tree_store.set_value(&root_iter, 3, &"$".to_value());
+    tree_store.set_value(&root_iter, 4, &doc_id.to_value());
+    if line_count + error_count.unwrap_or(0) > 0 {
+        add_placeholder_child(tree_store, &root_iter, doc_id);
+    }
+}
+
+/// Adds a Parquet, Arrow or Avro file or dataset opened for paging to the tree store.
+///
+/// Only the root row is created, without decoding any rows; the metadata or
+/// shards node and the rows (or pages of rows) are added when it is expanded.
+pub fn add_paged_to_tree(
+    tree_store: &TreeStore,
+    document: &PagedDocument,
+    root_name: &str,
+    doc_id: i64,
+) {
+    let root_iter = tree_store.append(None);
+    tree_store.set_value(&root_iter, 0, &root_name.to_value());
+    let preview = match document.shard_count() {
+        Some(shards) => format!("{} rows in {} shards", document.num_rows(), shards),
+        None => format!("{} rows", document.num_rows()),
+    };
+    tree_store.set_value(&root_iter, 1, &preview.to_value());
+    tree_store.set_value(&root_iter, 2, &"$".to_value());
+    tree_store.set_value(&root_iter, 3, &"$".to_value());
+    tree_store.set_value(&root_iter, 4, &doc_id.to_value());
+    add_placeholder_child(tree_store, &root_iter, doc_id);
+}
+
+/// Adds an archive to the tree store.
+///
+/// Only the root row is created; the member rows are added when it is
+/// expanded, and each member is parsed when its own row is expanded.
+pub fn add_archive_to_tree(
+    tree_store: &TreeStore,
+    document: &ArchiveDocument,
+    root_name: &str,
+    doc_id: i64,
+) {
+    let root_iter = tree_store.append(None);
     tree_store.set_value(&root_iter, 0, &root_name.to_value());
     tree_store.set_value(
         &root_iter,
         1,
-        &format!("{} objects", json_values.len()).to_value(),
+        &format!("{} members", document.member_count()).to_value(),
     );
-    tree_store.set_value(&root_iter, 2, &display_root_path.to_value());
+    tree_store.set_value(&root_iter, 2, &"$".to_value());
     tree_store.set_value(&root_iter, 3, &"$".to_value());
     tree_store.set_value(&root_iter, 4, &doc_id.to_value());
-
-    for (idx, value) in json_values.iter().enumerate() {
-        let line_iter = tree_store.append(Some(&root_iter));
-        let display_path = build_array_path(display_root_path, idx);
-        let name = format!("Line {}", idx + 1);
-        let data_path = build_array_path("$", idx);
-        set_tree_node_values(
-            tree_store,
-            &line_iter,
-            &name,
-            value,
-            &display_path,
-            &data_path,
-            doc_id,
-        );
-        populate_tree(
-            tree_store,
-            &line_iter,
-            value,
-            &display_path,
-            &data_path,
-            doc_id,
-        );
+    if document.member_count() > 0 {
+        add_placeholder_child(tree_store, &root_iter, doc_id);
     }
 }
 
+/// Adds a Jupyter notebook to the tree store.
+///
+/// Only the root row is created; the metadata and cell rows are added when it is expanded.
+pub fn add_notebook_to_tree(
+    tree_store: &TreeStore,
+    document: &NotebookDocument,
+    root_name: &str,
+    doc_id: i64,
+) {
+    let root_iter = tree_store.append(None);
+    tree_store.set_value(&root_iter, 0, &root_name.to_value());
+    let preview = match document.kernel_name() {
+        Some(kernel) => format!("{} cells, {}", document.cell_count(), kernel),
+        None => format!("{} cells", document.cell_count()),
+    };
+    tree_store.set_value(&root_iter, 1, &preview.to_value());
+    tree_store.set_value(&root_iter, 2, &"$".to_value());
+    tree_store.set_value(&root_iter, 3, &"$".to_value());
+    tree_store.set_value(&root_iter, 4, &doc_id.to_value());
+    add_placeholder_child(tree_store, &root_iter, doc_id);
+}
+
 // Note: Tree building functions are tightly coupled to GTK and require GTK initialization.
 // Integration tests for these functions would require GTK to be initialized, which is
 // complex in a test environment. The core logic (path building, value formatting) is
@@ -13,13 +13,25 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use serde_json::Value;
+use crate::json_value::{ValueOrigin, BINARY_KEY, FLOAT_KEY, MIME_KEY, TOML_DATETIME_KEY};
+use crate::notebook::png_dimensions;
+use serde_json::{Number, Value};
+
+/// Follows numbers that change when read as a 64-bit float, as most JSON tools do.
+const INEXACT_NUMBER_MARKER: &str = "(inexact as f64)";
 
 /// Formats a JSON value as a preview string for display in tree nodes.
-pub fn format_value_preview(value: &Value) -> String {
+///
+/// Objects standing for converted values, such as `{"$float": "NaN"}`, are
+/// shown as those values only if `origin` says they were converted; in JSON
+/// they are shown as the objects they are.
+pub fn format_value_preview(value: &Value, origin: ValueOrigin) -> String {
     match value {
         Value::Null => "null".to_string(),
         Value::Bool(b) => b.to_string(),
+        Value::Number(n) if !round_trips_through_f64(n) => {
+            format!("{} {}", n, INEXACT_NUMBER_MARKER)
+        }
         Value::Number(n) => n.to_string(),
         Value::String(s) => {
             if s.chars().count() > 50 {
@@ -30,13 +42,109 @@ pub fn format_value_preview(value: &Value) -> String {
             }
         }
         Value::Array(arr) => format!("Array[{}]", arr.len()),
-        Value::Object(map) => format!("Object{{{}}}", map.len()),
+        Value::Object(map) if origin == ValueOrigin::Json => format!("Object{{{}}}", map.len()),
+        Value::Object(map) => {
+            let tagged =
+                tagged_string(map, TOML_DATETIME_KEY).or_else(|| tagged_string(map, FLOAT_KEY));
+            match (tagged, binary_len(map)) {
+                // Unquoted, unlike strings
+                (Some(text), _) => text.to_string(),
+                (_, Some(len)) => match binary_mime(map) {
+                    Some(mime) => format!("Binary[{}, {} bytes]", mime, len),
+                    None => format!("Binary[{} bytes]", len),
+                },
+                _ => format!("Object{{{}}}", map.len()),
+            }
+        }
+    }
+}
+
+/// Returns true if reading the number as an f64 gives back the same value.
+///
+/// Numbers are kept as written, so this compares the written digits with
+/// those of the nearest f64: `1.50` and `15e-1` round-trip, while
+/// `18446744073709551617` and `0.1000000000000000055` do not.
+fn round_trips_through_f64(number: &Number) -> bool {
+    let text = number.as_str();
+    match text.parse::<f64>() {
+        Ok(float) if float.is_finite() => {
+            normalize_decimal(text) == normalize_decimal(&format!("{:e}", float))
+        }
+        _ => false,
+    }
+}
+
+/// Splits decimal text into its sign, significant digits and the power of ten of
+/// the last digit, so that equal numbers written differently compare equal.
+fn normalize_decimal(text: &str) -> Option<(bool, String, i64)> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (text, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int, frac);
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        // Zero, whatever its sign
+        return Some((false, String::new(), 0));
+    }
+    let exponent = exponent - frac.len() as i64 + (digits.len() - significant.len()) as i64;
+    Some((negative, significant.to_string(), exponent))
+}
+
+/// Returns the number of bytes held by a converted byte string object, which
+/// stores them as base64.
+fn binary_len(map: &serde_json::Map<String, Value>) -> Option<usize> {
+    match map.get(BINARY_KEY) {
+        Some(Value::String(base64)) => {
+            let padding = base64.bytes().rev().take_while(|b| *b == b'=').count();
+            Some((base64.len() / 4 * 3).saturating_sub(padding))
+        }
+        _ => None,
+    }
+}
+
+/// Describes the media type of a converted byte string that has one, such as
+/// an image output of a notebook, adding the size of PNG images as in
+/// "image/png, 640×480".
+fn binary_mime(map: &serde_json::Map<String, Value>) -> Option<String> {
+    let mime = map.get(MIME_KEY)?.as_str()?;
+    let dimensions = match map.get(BINARY_KEY) {
+        Some(Value::String(base64)) if mime == "image/png" => png_dimensions(base64),
+        _ => None,
+    };
+    Some(match dimensions {
+        Some((width, height)) => format!("{}, {}×{}", mime, width, height),
+        None => mime.to_string(),
+    })
+}
+
+/// Returns the text held by a single-member object such as a converted TOML
+/// datetime (`TOML_DATETIME_KEY`) or non-finite float (`FLOAT_KEY`).
+fn tagged_string<'a>(map: &'a serde_json::Map<String, Value>, tag: &str) -> Option<&'a str> {
+    match map.iter().next() {
+        Some((key, Value::String(text))) if map.len() == 1 && key == tag => Some(text),
+        _ => None,
     }
 }
 
 /// Formats a JSON value as a literal string for display in the value viewer.
-/// Strings are displayed as-is, other values are formatted as pretty JSON.
-pub fn format_value_literal(value: &Value) -> String {
+/// Strings are displayed as-is, NaN and infinities converted from another
+/// format by name, and other values are formatted as pretty JSON, with numbers
+/// exactly as written in the source.
+pub fn format_value_literal(value: &Value, origin: ValueOrigin) -> String {
+    if let Some(name) = value
+        .as_object()
+        .filter(|_| origin == ValueOrigin::Converted)
+        .and_then(|map| tagged_string(map, FLOAT_KEY))
+    {
+        return name.to_string();
+    }
     match value {
         Value::String(s) => {
             // Display string literally - escape sequences are already unescaped by serde_json
@@ -51,9 +159,13 @@ pub fn format_value_literal(value: &Value) -> String {
 }
 
 /// Formats a JSON value for display, falling back to the preview text if missing.
-pub fn format_value_for_display(value: Option<&Value>, preview_fallback: &str) -> String {
+pub fn format_value_for_display(
+    value: Option<&Value>,
+    origin: ValueOrigin,
+    preview_fallback: &str,
+) -> String {
     match value {
-        Some(value) => format_value_literal(value),
+        Some(value) => format_value_literal(value, origin),
         None => preview_fallback.to_string(),
     }
 }
@@ -64,25 +176,40 @@ mod tests {
 
     #[test]
     fn test_format_value_preview_null() {
-        assert_eq!(format_value_preview(&Value::Null), "null");
+        assert_eq!(
+            format_value_preview(&Value::Null, ValueOrigin::Json),
+            "null"
+        );
     }
 
     #[test]
     fn test_format_value_preview_bool() {
-        assert_eq!(format_value_preview(&Value::Bool(true)), "true");
-        assert_eq!(format_value_preview(&Value::Bool(false)), "false");
+        assert_eq!(
+            format_value_preview(&Value::Bool(true), ValueOrigin::Json),
+            "true"
+        );
+        assert_eq!(
+            format_value_preview(&Value::Bool(false), ValueOrigin::Json),
+            "false"
+        );
     }
 
     #[test]
     fn test_format_value_preview_number() {
-        assert_eq!(format_value_preview(&serde_json::json!(42)), "42");
-        assert_eq!(format_value_preview(&serde_json::json!(3.14)), "3.14");
+        assert_eq!(
+            format_value_preview(&serde_json::json!(42), ValueOrigin::Json),
+            "42"
+        );
+        assert_eq!(
+            format_value_preview(&serde_json::json!(3.14), ValueOrigin::Json),
+            "3.14"
+        );
     }
 
     #[test]
     fn test_format_value_preview_string_short() {
         assert_eq!(
-            format_value_preview(&serde_json::json!("hello")),
+            format_value_preview(&serde_json::json!("hello"), ValueOrigin::Json),
             "\"hello\""
         );
     }
@@ -90,7 +217,7 @@ mod tests {
     #[test]
     fn test_format_value_preview_string_long() {
         let long_string = "a".repeat(60);
-        let result = format_value_preview(&serde_json::json!(long_string));
+        let result = format_value_preview(&serde_json::json!(long_string), ValueOrigin::Json);
         assert!(result.starts_with('"'));
         assert!(result.ends_with("..."));
         // Length should be: " (1) + 50 chars + " (1) + ... (3) = 55
@@ -100,7 +227,7 @@ mod tests {
     #[test]
     fn test_format_value_preview_array() {
         assert_eq!(
-            format_value_preview(&serde_json::json!([1, 2, 3])),
+            format_value_preview(&serde_json::json!([1, 2, 3]), ValueOrigin::Json),
             "Array[3]"
         );
     }
@@ -108,20 +235,133 @@ mod tests {
     #[test]
     fn test_format_value_preview_object() {
         assert_eq!(
-            format_value_preview(&serde_json::json!({"a": 1, "b": 2})),
+            format_value_preview(&serde_json::json!({"a": 1, "b": 2}), ValueOrigin::Json),
             "Object{2}"
         );
     }
 
+    #[test]
+    fn test_format_value_preview_exact_numbers() {
+        let number = |text: &str| serde_json::from_str::<Value>(text).unwrap();
+        assert_eq!(
+            format_value_preview(&number("18446744073709551617"), ValueOrigin::Json),
+            "18446744073709551617 (inexact as f64)"
+        );
+        assert_eq!(
+            format_value_preview(&number("0.1000000000000000055"), ValueOrigin::Json),
+            "0.1000000000000000055 (inexact as f64)"
+        );
+        assert_eq!(
+            format_value_preview(&number("1.50"), ValueOrigin::Json),
+            "1.50"
+        );
+        assert_eq!(
+            format_value_preview(&number("15e-1"), ValueOrigin::Json),
+            "15e-1"
+        );
+        assert_eq!(
+            format_value_preview(&number("-0.0"), ValueOrigin::Json),
+            "-0.0"
+        );
+        assert_eq!(
+            format_value_preview(&number("9007199254740992"), ValueOrigin::Json),
+            "9007199254740992"
+        );
+        assert_eq!(
+            format_value_literal(
+                &number(r#"{"id": 18446744073709551617}"#),
+                ValueOrigin::Json
+            ),
+            "{\n  \"id\": 18446744073709551617\n}"
+        );
+    }
+
+    #[test]
+    fn test_format_value_non_finite_float() {
+        let nan = serde_json::json!({"$float": "NaN"});
+        assert_eq!(format_value_preview(&nan, ValueOrigin::Converted), "NaN");
+        assert_eq!(format_value_literal(&nan, ValueOrigin::Converted), "NaN");
+        assert_eq!(
+            format_value_preview(
+                &serde_json::json!({"$float": "-Infinity"}),
+                ValueOrigin::Converted
+            ),
+            "-Infinity"
+        );
+    }
+
+    #[test]
+    fn test_format_value_preview_toml_datetime() {
+        assert_eq!(
+            format_value_preview(
+                &serde_json::json!({"$datetime": "1979-05-27T07:32:00Z"}),
+                ValueOrigin::Converted
+            ),
+            "1979-05-27T07:32:00Z"
+        );
+        assert_eq!(
+            format_value_preview(
+                &serde_json::json!({"$datetime": "07:32:00", "b": 2}),
+                ValueOrigin::Converted
+            ),
+            "Object{2}"
+        );
+    }
+
+    #[test]
+    fn test_format_value_preview_binary() {
+        assert_eq!(
+            format_value_preview(
+                &serde_json::json!({"$binary": "AAEC/w=="}),
+                ValueOrigin::Converted
+            ),
+            "Binary[4 bytes]"
+        );
+        assert_eq!(
+            format_value_preview(
+                &serde_json::json!({"$ext": 1, "$binary": "aGk="}),
+                ValueOrigin::Converted
+            ),
+            "Binary[2 bytes]"
+        );
+        let png = "iVBORw0KGgoAAAANSUhEUgAAAAIAAAABCAYAAAD0In+KAAAADklEQVR4nGP4z8AAQv8BD/kD/YURmXYAAAAASUVORK5CYII=";
+        assert_eq!(
+            format_value_preview(
+                &serde_json::json!({"$binary": png, "$mime": "image/png"}),
+                ValueOrigin::Converted
+            ),
+            "Binary[image/png, 2×1, 71 bytes]"
+        );
+    }
+
+    #[test]
+    fn test_format_value_tags_in_json_are_data() {
+        let nan = serde_json::json!({"$float": "NaN"});
+        assert_eq!(format_value_preview(&nan, ValueOrigin::Json), "Object{1}");
+        assert_eq!(
+            format_value_literal(&nan, ValueOrigin::Json),
+            "{\n  \"$float\": \"NaN\"\n}"
+        );
+        assert_eq!(
+            format_value_preview(&serde_json::json!({"$datetime": "x"}), ValueOrigin::Json),
+            "Object{1}"
+        );
+        let mongo = serde_json::json!({"$binary": "aGk=", "$type": "00"});
+        assert_eq!(format_value_preview(&mongo, ValueOrigin::Json), "Object{2}");
+    }
+
     #[test]
     fn test_format_value_literal_string() {
-        assert_eq!(format_value_literal(&serde_json::json!("hello")), "hello");
+        assert_eq!(
+            format_value_literal(&serde_json::json!("hello"), ValueOrigin::Json),
+            "hello"
+        );
     }
 
     #[test]
     fn test_format_value_literal_object() {
         let value = serde_json::json!({"a": 1, "b": 2});
-        let result = format_value_literal(&value);
+        let result = format_value_literal(&value, ValueOrigin::Json);
         assert!(result.contains("\"a\": 1"));
         assert!(result.contains("\"b\": 2"));
     }
@@ -129,7 +369,7 @@ mod tests {
     #[test]
     fn test_format_value_literal_array() {
         let value = serde_json::json!([1, 2, 3]);
-        let result = format_value_literal(&value);
+        let result = format_value_literal(&value, ValueOrigin::Json);
         assert!(result.contains("1"));
         assert!(result.contains("2"));
         assert!(result.contains("3"));
@@ -138,14 +378,14 @@ mod tests {
     #[test]
     fn test_format_value_for_display_value() {
         let value = serde_json::json!({"name": "test", "value": 42});
-        let result = format_value_for_display(Some(&value), "fallback");
+        let result = format_value_for_display(Some(&value), ValueOrigin::Json, "fallback");
         assert!(result.contains("test"));
         assert!(result.contains("42"));
     }
 
     #[test]
     fn test_format_value_for_display_fallback() {
-        let result = format_value_for_display(None, "fallback");
+        let result = format_value_for_display(None, ValueOrigin::Json, "fallback");
         assert_eq!(result, "fallback");
     }
 }
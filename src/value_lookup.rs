@@ -93,7 +93,7 @@ pub fn parse_json_path(path: &str) -> Option<Vec<PathSegment>> {
     Some(segments)
 }
 
-fn lookup_in_value<'a>(value: &'a Value, segments: &[PathSegment]) -> Option<&'a Value> {
+pub fn lookup_in_value<'a>(value: &'a Value, segments: &[PathSegment]) -> Option<&'a Value> {
     let mut current = value;
     for segment in segments {
         match segment {
@@ -117,6 +117,16 @@ pub fn lookup_value<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
 }
 
 pub fn lookup_value_in_jsonl<'a>(values: &'a [Value], path: &str) -> Option<&'a Value> {
+    lookup_value_in_lines(|index| values.get(index), path)
+}
+
+/// Looks up a path whose first segment is a line index, fetching the line with `get_line`.
+///
+/// This lets JSONL documents that parse lines on demand share the lookup logic.
+pub fn lookup_value_in_lines<'a>(
+    get_line: impl FnOnce(usize) -> Option<&'a Value>,
+    path: &str,
+) -> Option<&'a Value> {
     let segments = parse_json_path(path)?;
     if segments.is_empty() {
         return None;
@@ -124,7 +134,7 @@ pub fn lookup_value_in_jsonl<'a>(values: &'a [Value], path: &str) -> Option<&'a
     let (first, rest) = segments.split_first()?;
     match first {
         PathSegment::Index(index) => {
-            let value = values.get(*index)?;
+            let value = get_line(*index)?;
             lookup_in_value(value, rest)
         }
         PathSegment::Key(_) => None,